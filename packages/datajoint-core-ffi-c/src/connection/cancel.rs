@@ -0,0 +1,36 @@
+use crate::error::datajoint_core_set_last_error;
+use datajoint_core::connection::CancelHandle;
+use datajoint_core::error::{DataJointError, ErrorCode};
+
+/// Creates a new cancel handle, which can be attached to executors with
+/// [`executor_set_cancel_handle`][crate::connection::executor_set_cancel_handle].
+#[no_mangle]
+pub extern "C" fn cancel_handle_new() -> *mut CancelHandle {
+    Box::into_raw(Box::new(CancelHandle::new()))
+}
+
+/// Frees a cancel handle.
+///
+/// Statements running under the handle can still be cancelled through other handles
+/// to the same connection or executor.
+#[no_mangle]
+pub unsafe extern "C" fn cancel_handle_free(this: *mut CancelHandle) {
+    if !this.is_null() {
+        Box::from_raw(this);
+    }
+}
+
+/// Cancels every statement currently running under the handle.
+///
+/// This function may be called from any thread, such as a signal handler thread that
+/// responds to an interrupt while another thread waits on a query.
+#[no_mangle]
+pub unsafe extern "C" fn cancel_handle_cancel(this: *const CancelHandle) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let handle = &*this;
+    handle.cancel();
+    ErrorCode::Success as i32
+}
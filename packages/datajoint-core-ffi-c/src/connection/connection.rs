@@ -1,6 +1,8 @@
 use crate::error::datajoint_core_set_last_error;
 use crate::util;
-use datajoint_core::connection::{Connection, ConnectionSettings, Cursor, Executor};
+use datajoint_core::connection::{
+    CancelHandle, Connection, ConnectionSettings, Cursor, Executor, ServerInfo, Transaction,
+};
 use datajoint_core::error::{DataJointError, ErrorCode};
 use datajoint_core::placeholders::PlaceholderArgumentVector;
 use libc::c_char;
@@ -87,6 +89,46 @@ pub extern "C" fn connection_reconnect(this: *mut Connection) -> i32 {
     }
 }
 
+/// Checks that the database server can still be reached with a round trip to the server.
+///
+/// Unlike [`connection_is_connected`], this detects sessions that were dropped by the
+/// server or the network.
+#[no_mangle]
+pub extern "C" fn connection_ping(this: *mut Connection) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let connection = unsafe { &*this };
+    match connection.ping() {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(_) => ErrorCode::Success as i32,
+    }
+}
+
+/// Fetches the database type, version, current user and current database of the
+/// database server.
+///
+/// The output must be freed with [`server_info_free`][crate::connection::server_info_free].
+#[no_mangle]
+pub unsafe extern "C" fn connection_server_info(
+    this: *mut Connection,
+    out: *mut *mut ServerInfo,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let connection = &*this;
+    match connection.try_server_info() {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(info) => {
+            util::mem::handle_output_ptr(out, info);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
 /// Gets the pointer to the connection's internal settings object..
 ///
 /// This pointer should not be freed.
@@ -119,6 +161,50 @@ pub unsafe extern "C" fn connection_executor(
     }
 }
 
+/// Gets a handle for cancelling the statements running over this connection.
+///
+/// Once a handle has been requested, every executor, cursor and query function of the
+/// connection can be interrupted by calling
+/// [`cancel_handle_cancel`][crate::connection::cancel_handle_cancel] from any thread.
+/// The handle must be freed with [`cancel_handle_free`][crate::connection::cancel_handle_free].
+#[no_mangle]
+pub unsafe extern "C" fn connection_cancel_handle(
+    this: *const Connection,
+    out: *mut *mut CancelHandle,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let connection = &*this;
+    util::mem::handle_output_ptr(out, connection.cancel_handle());
+    ErrorCode::Success as i32
+}
+
+/// Begins a transaction, which runs all of its queries on a single connection.
+///
+/// The transaction must be finished with [`transaction_commit`][crate::connection::transaction_commit],
+/// [`transaction_rollback`][crate::connection::transaction_rollback], or
+/// [`transaction_free`][crate::connection::transaction_free], which rolls it back.
+#[no_mangle]
+pub unsafe extern "C" fn connection_begin(
+    this: *mut Connection,
+    out: *mut *mut Transaction,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let connection = &mut *this;
+    match connection.try_begin() {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(transaction) => {
+            util::mem::handle_output_ptr(out, transaction);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
 /// Executes the given non-returning query, returning the number of rows affected.
 ///
 /// The third parameter can be `NULL` or a collection of placeholder arguments to
@@ -170,7 +256,7 @@ pub unsafe extern "C" fn connection_fetch_query(
     this: *mut Connection,
     query: *const c_char,
     args: *mut PlaceholderArgumentVector,
-    out: *mut *mut Cursor,
+    out: *mut *mut Cursor<'static>,
 ) -> i32 {
     if this.is_null() {
         return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
@@ -195,4 +281,4 @@ pub unsafe extern "C" fn connection_fetch_query(
             ErrorCode::Success as i32
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,102 @@
+use crate::error::datajoint_core_set_last_error;
+use datajoint_core::connection::{ConnectionSettings, CredentialProvider};
+use datajoint_core::error::{DataJointError, Error, ErrorCode};
+use datajoint_core::util::IntegerEnum;
+use libc::{c_char, c_void};
+use std::ffi::CStr;
+
+/// The size of the buffer a credential callback writes the password into, including the
+/// terminating nul byte.
+pub const CREDENTIAL_BUFFER_SIZE: usize = 8192;
+
+/// A callback that supplies the password whenever a connection opens a connection pool.
+///
+/// Receives the settings of the connection, a buffer of `buffer_size` bytes to write the
+/// nul-terminated password into and the user data pointer given at registration. Returns
+/// `0` on success, or an error code to fail the connection with.
+pub type CredentialCallback = Option<
+    unsafe extern "C" fn(
+        settings: *const ConnectionSettings,
+        buffer: *mut c_char,
+        buffer_size: usize,
+        user_data: *mut c_void,
+    ) -> i32,
+>;
+
+/// A credential provider that calls a C callback.
+struct CallbackCredentials {
+    callback:
+        unsafe extern "C" fn(*const ConnectionSettings, *mut c_char, usize, *mut c_void) -> i32,
+    user_data: *mut c_void,
+}
+
+// The caller of `connection_settings_set_credential_callback` guarantees that the
+// callback and its user data may be used from any thread.
+unsafe impl Send for CallbackCredentials {}
+unsafe impl Sync for CallbackCredentials {}
+
+impl CallbackCredentials {
+    fn password(&self, settings: &ConnectionSettings) -> Result<String, Error> {
+        let mut buffer = vec![0u8; CREDENTIAL_BUFFER_SIZE];
+        let code = unsafe {
+            (self.callback)(
+                settings,
+                buffer.as_mut_ptr() as *mut c_char,
+                buffer.len(),
+                self.user_data,
+            )
+        };
+        let result: Result<String, Error> = if code != ErrorCode::Success as i32 {
+            Err(DataJointError::new_with_message(
+                "credential callback failed",
+                ErrorCode::from_int(code).unwrap_or(ErrorCode::ConfigurationError),
+            ))
+        } else {
+            match CStr::from_bytes_until_nul(&buffer) {
+                Err(_) => Err(DataJointError::new_with_message(
+                    "credential callback did not write a nul-terminated password",
+                    ErrorCode::ConfigurationError,
+                )),
+                Ok(password) => match password.to_str() {
+                    Err(_) => Err(DataJointError::new(ErrorCode::InvalidUtf8String)),
+                    Ok(password) => Ok(password.to_string()),
+                },
+            }
+        };
+        // Clear the copy of the password left in the buffer.
+        for byte in buffer.iter_mut() {
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+        result
+    }
+}
+
+/// Registers a callback that supplies the password whenever the connection opens a
+/// connection pool, such as one that generates a short-lived authentication token.
+///
+/// The callback may be invoked from any thread that connects with the settings, and
+/// `user_data` is passed to it unchanged. A null callback logs in with the password of
+/// the settings again.
+#[no_mangle]
+pub unsafe extern "C" fn connection_settings_set_credential_callback(
+    this: *mut ConnectionSettings,
+    callback: CredentialCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let settings = &mut *this;
+    settings.credentials = match callback {
+        None => CredentialProvider::Static,
+        Some(callback) => {
+            let credentials = CallbackCredentials {
+                callback,
+                user_data,
+            };
+            CredentialProvider::callback(move |settings| credentials.password(settings))
+        }
+    };
+    ErrorCode::Success as i32
+}
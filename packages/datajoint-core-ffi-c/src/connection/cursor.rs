@@ -1,11 +1,12 @@
 use crate::error::datajoint_core_set_last_error;
-use crate::results::TableRowVector;
+use crate::results::{TableColumnVector, TableRowVector};
 use crate::util;
 use datajoint_core::results::TableRow;
 use datajoint_core::{
     connection::Cursor,
     error::{DataJointError, ErrorCode},
 };
+use libc::size_t;
 
 /// Frees a cursor.
 #[no_mangle]
@@ -32,6 +33,56 @@ pub unsafe extern "C" fn cursor_next(this: *mut Cursor, out: *mut *mut TableRow)
     }
 }
 
+/// Fetches up to the given number of rows, returning fewer rows only once the rows run
+/// out.
+///
+/// Gives an empty vector once there are no more rows. A size of 0 fails with
+/// [`ErrorCode::InvalidBatchSize`].
+#[no_mangle]
+pub unsafe extern "C" fn cursor_next_batch(
+    this: *mut Cursor,
+    size: size_t,
+    out: *mut *mut TableRowVector,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let cursor = &mut *this;
+    match cursor.try_next_batch(size) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(value) => {
+            let rows = TableRowVector::new_described(value, || cursor.try_columns());
+            util::mem::handle_output_ptr(out, rows);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Gives the columns of the rows of the cursor, which are known even if the cursor
+/// returns no rows.
+///
+/// The columns are read from the next row without consuming it, or described by the
+/// database if there are no more rows.
+#[no_mangle]
+pub unsafe extern "C" fn cursor_columns(
+    this: *mut Cursor,
+    out: *mut *mut TableColumnVector,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let cursor = &mut *this;
+    match cursor.try_columns() {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(columns) => {
+            util::mem::handle_output_ptr(out, TableColumnVector::new(columns));
+            ErrorCode::Success as i32
+        }
+    }
+}
+
 /// Fetches all remaining rows.
 #[no_mangle]
 pub unsafe extern "C" fn cursor_rest(this: *mut Cursor, out: *mut *mut TableRowVector) -> i32 {
@@ -43,7 +94,8 @@ pub unsafe extern "C" fn cursor_rest(this: *mut Cursor, out: *mut *mut TableRowV
     match cursor.try_rest() {
         Err(error) => datajoint_core_set_last_error(error) as i32,
         Ok(value) => {
-            util::mem::handle_output_ptr(out, TableRowVector::new(value));
+            let rows = TableRowVector::new_described(value, || cursor.try_columns());
+            util::mem::handle_output_ptr(out, rows);
             ErrorCode::Success as i32
         }
     }
@@ -0,0 +1,70 @@
+use datajoint_core::connection::ExecuteResult;
+
+/// Frees an execute result.
+#[no_mangle]
+pub unsafe extern "C" fn execute_result_free(this: *mut ExecuteResult) {
+    if !this.is_null() {
+        Box::from_raw(this);
+    }
+}
+
+/// Gives the number of rows affected by the statement.
+#[no_mangle]
+pub unsafe extern "C" fn execute_result_rows_affected(this: *const ExecuteResult) -> u64 {
+    if this.is_null() {
+        return 0;
+    }
+    let result = &*this;
+    result.rows_affected
+}
+
+/// Gets the id generated for a row inserted by the statement, such as the value of an
+/// `AUTO_INCREMENT` column.
+///
+/// Returns `true` and writes the id to `out` if the database reported one, or `false`
+/// otherwise. On Postgres, the id is read from the first column returned by an insert with
+/// a `RETURNING` clause.
+#[no_mangle]
+pub unsafe extern "C" fn execute_result_last_insert_id(
+    this: *const ExecuteResult,
+    out: *mut i64,
+) -> bool {
+    if this.is_null() {
+        return false;
+    }
+    let result = &*this;
+    match result.last_insert_id {
+        None => false,
+        Some(id) => {
+            if !out.is_null() {
+                *out = id;
+            }
+            true
+        }
+    }
+}
+
+/// Gets the number of warnings produced by the statement.
+///
+/// Returns `true` and writes the count to `out` if the warnings were counted, or `false`
+/// otherwise. Warnings are only counted by executors set to count them or to treat them
+/// as errors, and only by MySQL.
+#[no_mangle]
+pub unsafe extern "C" fn execute_result_warnings(
+    this: *const ExecuteResult,
+    out: *mut u64,
+) -> bool {
+    if this.is_null() {
+        return false;
+    }
+    let result = &*this;
+    match result.warnings {
+        None => false,
+        Some(warnings) => {
+            if !out.is_null() {
+                *out = warnings;
+            }
+            true
+        }
+    }
+}
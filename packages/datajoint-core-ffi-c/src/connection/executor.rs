@@ -1,12 +1,14 @@
 use crate::error::datajoint_core_set_last_error;
-use crate::results::TableRowVector;
+use crate::results::{TableColumnVector, TableRowVector};
 use crate::util;
-use datajoint_core::connection::{Cursor, Executor};
+use datajoint_core::connection::{CancelHandle, Cursor, DelimitedFormat, ExecuteResult, Executor};
 use datajoint_core::error::{DataJointError, ErrorCode};
 use datajoint_core::placeholders::PlaceholderArgumentVector;
 use datajoint_core::results::TableRow;
-use libc::c_char;
+use datajoint_core::util::IntegerEnum;
+use libc::{c_char, size_t};
 use std::ffi::CStr;
+use std::time::Duration;
 
 /// Frees an executor.
 #[no_mangle]
@@ -16,6 +18,81 @@ pub unsafe extern "C" fn executor_free(this: *mut Executor) {
     }
 }
 
+/// Sets the maximum amount of time, in milliseconds, a statement run by the executor may
+/// take before it fails with [`ErrorCode::QueryTimedOut`] and is cancelled on the server.
+///
+/// Overrides the statement timeout of the connection settings. A value of `0` lets
+/// statements run indefinitely.
+#[no_mangle]
+pub unsafe extern "C" fn executor_set_timeout(this: *mut Executor, timeout_ms: u64) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let executor = &mut *this;
+    executor.set_timeout(match timeout_ms {
+        0 => None,
+        timeout_ms => Some(Duration::from_millis(timeout_ms)),
+    });
+    ErrorCode::Success as i32
+}
+
+/// Sets whether the warnings produced by statements run by the executor are counted and
+/// reported by [`execute_result_warnings`].
+///
+/// Counting warnings takes another round-trip to the server after every statement. Only
+/// MySQL reports warnings.
+#[no_mangle]
+pub unsafe extern "C" fn executor_set_count_warnings(
+    this: *mut Executor,
+    count_warnings: bool,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let executor = &mut *this;
+    executor.set_count_warnings(count_warnings);
+    ErrorCode::Success as i32
+}
+
+/// Sets whether statements run by the executor that produce warnings fail with
+/// [`ErrorCode::StatementWarnings`], whose message holds the warnings.
+///
+/// The statement has already run when it fails. Warnings are counted while this is set.
+/// Only MySQL reports warnings.
+#[no_mangle]
+pub unsafe extern "C" fn executor_set_warnings_as_errors(
+    this: *mut Executor,
+    warnings_as_errors: bool,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let executor = &mut *this;
+    executor.set_warnings_as_errors(warnings_as_errors);
+    ErrorCode::Success as i32
+}
+
+/// Sets the handle used to cancel the statements run by the executor.
+///
+/// The executor keeps its own reference to the handle, so the handle passed in may be
+/// freed at any time.
+#[no_mangle]
+pub unsafe extern "C" fn executor_set_cancel_handle(
+    this: *mut Executor,
+    handle: *const CancelHandle,
+) -> i32 {
+    if this.is_null() || handle.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let executor = &mut *this;
+    executor.set_cancel_handle((*handle).clone());
+    ErrorCode::Success as i32
+}
+
 /// Executes the given query over the connection.
 #[no_mangle]
 pub unsafe extern "C" fn executor_execute(
@@ -36,6 +113,249 @@ pub unsafe extern "C" fn executor_execute(
         Ok(value) => value,
     };
     match executor.try_execute(&query_str.to_string()) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(value) => {
+            if !out_size.is_null() {
+                *out_size = value.rows_affected;
+            }
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Executes the given query over the connection, writing the rows affected, the id of an
+/// inserted row and the number of warnings, if counted, to an execute result.
+///
+/// The third parameter can be `NULL` or a collection of placeholder arguments, which is
+/// owned and deallocated by the library once the query is executed.
+#[no_mangle]
+pub unsafe extern "C" fn executor_execute_result(
+    this: *mut Executor,
+    query: *const c_char,
+    args: *mut PlaceholderArgumentVector,
+    out: *mut *mut ExecuteResult,
+) -> i32 {
+    if this.is_null() || query.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let executor = &*this;
+    let query_str = match CStr::from_ptr(query).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    match if args.is_null() {
+        executor.try_execute(query_str)
+    } else {
+        executor.try_execute_ph(query_str, *Box::from_raw(args))
+    } {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(result) => {
+            util::mem::handle_output_ptr(out, result);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Executes the given query once for every set of placeholder arguments.
+///
+/// `args` points to an array of `args_size` placeholder argument vectors. Every vector in
+/// the array is owned and deallocated by the library, even if the query fails, so the
+/// caller only frees the array itself. Inserts of a single row of values are batched into
+/// statements inserting many rows.
+#[no_mangle]
+pub unsafe extern "C" fn executor_execute_many(
+    this: *mut Executor,
+    query: *const c_char,
+    args: *const *mut PlaceholderArgumentVector,
+    args_size: size_t,
+    out_size: *mut u64,
+) -> i32 {
+    let args: Vec<Option<PlaceholderArgumentVector>> = if args.is_null() {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(args, args_size)
+            .iter()
+            .map(|&row| {
+                if row.is_null() {
+                    None
+                } else {
+                    Some(*Box::from_raw(row))
+                }
+            })
+            .collect()
+    };
+    if this.is_null() || query.is_null() || (args_size > 0 && args.is_empty()) {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let args: Vec<PlaceholderArgumentVector> = match args.into_iter().collect() {
+        None => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+                as i32
+        }
+        Some(args) => args,
+    };
+    let executor = &*this;
+    let query_str = match CStr::from_ptr(query).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    match executor.try_execute_many(query_str, args) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(value) => {
+            if !out_size.is_null() {
+                *out_size = value;
+            }
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Reads an array of `size` UTF-8 strings, such as the column names of a bulk load.
+unsafe fn read_string_array<'a>(
+    strings: *const *const c_char,
+    size: size_t,
+) -> Result<Vec<&'a str>, ErrorCode> {
+    if strings.is_null() {
+        return if size == 0 {
+            Ok(Vec::new())
+        } else {
+            Err(ErrorCode::NullNotAllowed)
+        };
+    }
+    std::slice::from_raw_parts(strings, size)
+        .iter()
+        .map(|&string| {
+            if string.is_null() {
+                Err(ErrorCode::NullNotAllowed)
+            } else {
+                CStr::from_ptr(string)
+                    .to_str()
+                    .map_err(|_| ErrorCode::InvalidUtf8String)
+            }
+        })
+        .collect()
+}
+
+/// Loads rows of values into the given columns of a table, writing the number of rows
+/// loaded to `out_size`.
+///
+/// `columns` points to an array of `columns_size` column names, and `rows` points to an
+/// array of `rows_size` placeholder argument vectors holding a value for every column.
+/// Every vector in the array is owned and deallocated by the library, even if the load
+/// fails, so the caller only frees the array itself.
+///
+/// Postgres streams the rows through `COPY ... FROM STDIN`, and MySQL and SQLite insert
+/// them with batched inserts.
+#[no_mangle]
+pub unsafe extern "C" fn executor_bulk_load(
+    this: *mut Executor,
+    table: *const c_char,
+    columns: *const *const c_char,
+    columns_size: size_t,
+    rows: *const *mut PlaceholderArgumentVector,
+    rows_size: size_t,
+    out_size: *mut u64,
+) -> i32 {
+    let rows: Vec<Option<PlaceholderArgumentVector>> = if rows.is_null() {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(rows, rows_size)
+            .iter()
+            .map(|&row| {
+                if row.is_null() {
+                    None
+                } else {
+                    Some(*Box::from_raw(row))
+                }
+            })
+            .collect()
+    };
+    if this.is_null() || table.is_null() || (rows_size > 0 && rows.is_empty()) {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let rows: Vec<PlaceholderArgumentVector> = match rows.into_iter().collect() {
+        None => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+                as i32
+        }
+        Some(rows) => rows,
+    };
+    let executor = &*this;
+    let table_str = match CStr::from_ptr(table).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    let columns = match read_string_array(columns, columns_size) {
+        Err(code) => return datajoint_core_set_last_error(DataJointError::new(code)) as i32,
+        Ok(columns) => columns,
+    };
+    match executor.try_bulk_load(table_str, &columns, rows) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(value) => {
+            if !out_size.is_null() {
+                *out_size = value;
+            }
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Loads delimited text into the given columns of a table, writing the number of rows
+/// loaded to `out_size`.
+///
+/// `columns` points to an array of `columns_size` column names, and `text` points to
+/// `text_size` bytes of CSV or TSV text, which need not be null-terminated.
+///
+/// Postgres parses the text on the server through `COPY ... FROM STDIN`, and MySQL and
+/// SQLite parse it and insert its rows with batched inserts.
+#[no_mangle]
+pub unsafe extern "C" fn executor_bulk_load_text(
+    this: *mut Executor,
+    table: *const c_char,
+    columns: *const *const c_char,
+    columns_size: size_t,
+    text: *const u8,
+    text_size: size_t,
+    format: DelimitedFormat,
+    out_size: *mut u64,
+) -> i32 {
+    if this.is_null() || table.is_null() || (text.is_null() && text_size > 0) {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    } else if DelimitedFormat::from_int(format as i32).is_none() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::BadPrimitiveEnumValue))
+            as i32;
+    }
+    let executor = &*this;
+    let table_str = match CStr::from_ptr(table).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    let columns = match read_string_array(columns, columns_size) {
+        Err(code) => return datajoint_core_set_last_error(DataJointError::new(code)) as i32,
+        Ok(columns) => columns,
+    };
+    let text: &[u8] = if text.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(text, text_size)
+    };
+    match executor.try_bulk_load_reader(table_str, &columns, text, format) {
         Err(error) => datajoint_core_set_last_error(error) as i32,
         Ok(value) => {
             if !out_size.is_null() {
@@ -96,7 +416,39 @@ pub unsafe extern "C" fn executor_fetch_all(
     match executor.try_fetch_all(&query_str.to_string()) {
         Err(error) => datajoint_core_set_last_error(error) as i32,
         Ok(rows) => {
-            util::mem::handle_output_ptr(out, TableRowVector::new(rows));
+            let rows = TableRowVector::new_described(rows, || executor.try_describe(query_str));
+            util::mem::handle_output_ptr(out, rows);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Describes the columns of the rows returned by the given query, without running it.
+///
+/// The columns are known even for queries that return no rows, and report whether they
+/// may hold nulls through [`table_column_ref_nullable`][crate::results::table_column_ref_nullable].
+#[no_mangle]
+pub unsafe extern "C" fn executor_describe(
+    this: *mut Executor,
+    query: *const c_char,
+    out: *mut *mut TableColumnVector,
+) -> i32 {
+    if this.is_null() || query.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let executor = &*this;
+    let query_str = match CStr::from_ptr(query).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    match executor.try_describe(query_str) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(columns) => {
+            util::mem::handle_output_ptr(out, TableColumnVector::new(columns));
             ErrorCode::Success as i32
         }
     }
@@ -108,12 +460,15 @@ pub unsafe extern "C" fn executor_fetch_all(
 /// bind to the query. Once the query is executed, the [`PlaceholderArgumentVector`]
 /// is owned and deallocated by the library. In other words, the caller does not
 /// need to manually free the placeholder arguments after they are bound to a query.
+///
+/// The cursor owns its own connection, so it stays valid after the executor and the
+/// connection it was created from are freed.
 #[no_mangle]
 pub unsafe extern "C" fn executor_cursor<'c>(
     this: *mut Executor<'c>,
     query: *const c_char,
     args: *mut PlaceholderArgumentVector,
-    out: *mut *mut Cursor<'c>,
+    out: *mut *mut Cursor<'static>,
 ) -> i32 {
     if this.is_null() || query.is_null() {
         return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
@@ -1,9 +1,25 @@
+mod cancel;
 mod connection;
+mod credentials;
 mod cursor;
+mod execute_result;
 mod executor;
+mod observer;
+mod prepared;
+mod script;
+mod server_info;
 mod settings;
+mod transaction;
 
+pub use cancel::*;
 pub use connection::*;
+pub use credentials::*;
 pub use cursor::*;
+pub use execute_result::*;
 pub use executor::*;
+pub use observer::*;
+pub use prepared::*;
+pub use script::*;
+pub use server_info::*;
 pub use settings::*;
+pub use transaction::*;
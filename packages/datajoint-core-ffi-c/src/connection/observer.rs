@@ -0,0 +1,97 @@
+use crate::error::datajoint_core_set_last_error;
+use datajoint_core::connection::{Connection, QueryEvent, QueryObserver};
+use datajoint_core::error::{DataJointError, ErrorCode};
+use libc::{c_char, c_void};
+use std::ffi::CString;
+use std::sync::Arc;
+
+/// A callback invoked for every statement run over a connection.
+///
+/// Receives the SQL text, the redacted placeholder arguments, the number of rows affected
+/// or returned, the duration of the statement in microseconds, the error code of the
+/// statement and the user data pointer given at registration. The strings are only valid
+/// for the duration of the call.
+pub type QueryObserverCallback = Option<
+    unsafe extern "C" fn(
+        query: *const c_char,
+        arguments: *const *const c_char,
+        argument_count: usize,
+        rows: u64,
+        duration_us: u64,
+        error_code: i32,
+        user_data: *mut c_void,
+    ),
+>;
+
+/// A query observer that forwards events to a C callback.
+struct CallbackObserver {
+    callback: unsafe extern "C" fn(
+        *const c_char,
+        *const *const c_char,
+        usize,
+        u64,
+        u64,
+        i32,
+        *mut c_void,
+    ),
+    user_data: *mut c_void,
+}
+
+// The caller of `connection_set_query_observer` guarantees that the callback and its user
+// data may be used from any thread.
+unsafe impl Send for CallbackObserver {}
+unsafe impl Sync for CallbackObserver {}
+
+/// Converts a string to a C string, dropping any interior nul bytes.
+fn to_c_string(string: &str) -> CString {
+    CString::new(string.replace('\0', "")).unwrap()
+}
+
+impl QueryObserver for CallbackObserver {
+    fn on_query(&self, event: &QueryEvent) {
+        let query = to_c_string(event.query);
+        let arguments: Vec<CString> = event
+            .arguments
+            .iter()
+            .map(|argument| to_c_string(argument))
+            .collect();
+        let argument_ptrs: Vec<*const c_char> =
+            arguments.iter().map(|argument| argument.as_ptr()).collect();
+        unsafe {
+            (self.callback)(
+                query.as_ptr(),
+                argument_ptrs.as_ptr(),
+                argument_ptrs.len(),
+                event.rows,
+                event.duration.as_micros().min(u64::MAX as u128) as u64,
+                event.error_code as i32,
+                self.user_data,
+            );
+        }
+    }
+}
+
+/// Registers a callback that is invoked for every statement run over the connection,
+/// for logging and tracing.
+///
+/// The callback may be invoked from any thread that runs queries over the connection,
+/// and `user_data` is passed to it unchanged. A null callback removes the current observer.
+#[no_mangle]
+pub unsafe extern "C" fn connection_set_query_observer(
+    this: *mut Connection,
+    callback: QueryObserverCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let connection = &mut *this;
+    connection.set_query_observer(callback.map(|callback| {
+        Arc::new(CallbackObserver {
+            callback,
+            user_data,
+        }) as Arc<dyn QueryObserver>
+    }));
+    ErrorCode::Success as i32
+}
@@ -0,0 +1,203 @@
+use crate::error::datajoint_core_set_last_error;
+use crate::results::TableRowVector;
+use crate::util;
+use datajoint_core::connection::{Cursor, ExecuteResult, Executor, PreparedStatement};
+use datajoint_core::error::{DataJointError, ErrorCode};
+use datajoint_core::placeholders::PlaceholderArgumentVector;
+use datajoint_core::results::{ColumnIndex, TableColumnRef};
+use libc::{c_char, size_t};
+use std::ffi::CStr;
+
+/// Takes ownership of the placeholder arguments passed to a prepared statement, where
+/// `NULL` binds no arguments.
+unsafe fn take_arguments(args: *mut PlaceholderArgumentVector) -> PlaceholderArgumentVector {
+    if args.is_null() {
+        PlaceholderArgumentVector::new()
+    } else {
+        *Box::from_raw(args)
+    }
+}
+
+/// Prepares the given query on the database server, creating a statement that can be
+/// run any number of times with different placeholder arguments.
+#[no_mangle]
+pub unsafe extern "C" fn executor_prepare<'c>(
+    this: *mut Executor<'c>,
+    query: *const c_char,
+    out: *mut *mut PreparedStatement<'c>,
+) -> i32 {
+    if this.is_null() || query.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let executor = &*this;
+    let query_str = match CStr::from_ptr(query).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    match executor.try_prepare(query_str) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(statement) => {
+            util::mem::handle_output_ptr(out, statement);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Frees a prepared statement.
+#[no_mangle]
+pub unsafe extern "C" fn prepared_statement_free(this: *mut PreparedStatement) {
+    if !this.is_null() {
+        Box::from_raw(this);
+    }
+}
+
+/// Gives the number of placeholder parameters of the statement, or `-1` if the
+/// database does not report it.
+#[no_mangle]
+pub unsafe extern "C" fn prepared_statement_parameter_count(this: *const PreparedStatement) -> i64 {
+    if this.is_null() {
+        return -1;
+    }
+    match (&*this).parameter_count() {
+        None => -1,
+        Some(count) => count as i64,
+    }
+}
+
+/// Gives the number of columns of the rows returned by the statement.
+#[no_mangle]
+pub unsafe extern "C" fn prepared_statement_column_count(this: *const PreparedStatement) -> size_t {
+    if this.is_null() {
+        return 0;
+    }
+    (&*this).columns().len()
+}
+
+/// Gets a column of the rows returned by the statement by ordinal index.
+///
+/// The column reference is valid for as long as the prepared statement.
+#[no_mangle]
+pub unsafe extern "C" fn prepared_statement_get_column<'c>(
+    this: *const PreparedStatement<'c>,
+    ordinal: size_t,
+    out: *mut *mut TableColumnRef<'c>,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let columns = (&*this).columns();
+    match ordinal.owned_ordinal(columns) {
+        Err(err) => datajoint_core_set_last_error(err) as i32,
+        Ok(ordinal) => {
+            util::mem::handle_output_ptr(out, TableColumnRef::Owned(&columns[ordinal]));
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Executes the statement, binding the given placeholder arguments.
+///
+/// The second parameter can be `NULL` or a collection of placeholder arguments, which
+/// is owned and deallocated by the library once the statement is executed.
+#[no_mangle]
+pub unsafe extern "C" fn prepared_statement_execute(
+    this: *mut PreparedStatement,
+    args: *mut PlaceholderArgumentVector,
+    out_size: *mut u64,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let statement = &*this;
+    match statement.try_execute(take_arguments(args)) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(value) => {
+            if !out_size.is_null() {
+                *out_size = value.rows_affected;
+            }
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Executes the statement, binding the given placeholder arguments, and writes the rows
+/// affected, the id of an inserted row and the number of warnings to an execute result.
+///
+/// The second parameter can be `NULL` or a collection of placeholder arguments, which
+/// is owned and deallocated by the library once the statement is executed.
+#[no_mangle]
+pub unsafe extern "C" fn prepared_statement_execute_result(
+    this: *mut PreparedStatement,
+    args: *mut PlaceholderArgumentVector,
+    out: *mut *mut ExecuteResult,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let statement = &*this;
+    match statement.try_execute(take_arguments(args)) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(result) => {
+            util::mem::handle_output_ptr(out, result);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Fetches multiple rows using the statement, binding the given placeholder arguments.
+///
+/// The second parameter can be `NULL` or a collection of placeholder arguments, which
+/// is owned and deallocated by the library once the statement is executed.
+#[no_mangle]
+pub unsafe extern "C" fn prepared_statement_fetch_all(
+    this: *mut PreparedStatement,
+    args: *mut PlaceholderArgumentVector,
+    out: *mut *mut TableRowVector,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let statement = &*this;
+    match statement.try_fetch_all(take_arguments(args)) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(rows) => {
+            let rows = TableRowVector::new_described(rows, || Ok(statement.columns().to_vec()));
+            util::mem::handle_output_ptr(out, rows);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Creates a cursor for the statement, binding the given placeholder arguments.
+///
+/// The second parameter can be `NULL` or a collection of placeholder arguments, which
+/// is owned and deallocated by the library once the statement is executed.
+///
+/// The cursor stays valid after the statement, executor and connection are freed.
+#[no_mangle]
+pub unsafe extern "C" fn prepared_statement_cursor<'c>(
+    this: *mut PreparedStatement<'c>,
+    args: *mut PlaceholderArgumentVector,
+    out: *mut *mut Cursor<'static>,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let statement = &*this;
+    match statement.cursor(take_arguments(args)) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(cursor) => {
+            util::mem::handle_output_ptr(out, cursor);
+            ErrorCode::Success as i32
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::datajoint_core_set_last_error;
+use crate::util;
+use datajoint_core::connection::{Executor, ScriptResult};
+use datajoint_core::error::{DataJointError, ErrorCode};
+use libc::{c_char, size_t};
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+/// Runs the statements of a SQL script in order, stopping at the first statement that
+/// fails.
+///
+/// If `in_transaction` is set, the script runs inside of a single transaction, which is
+/// committed if every statement succeeds and rolled back otherwise.
+///
+/// Returns an error only if the script could not be run. The statement that failed is
+/// reported by the script result written to `out`.
+#[no_mangle]
+pub unsafe extern "C" fn executor_execute_script(
+    this: *mut Executor,
+    script: *const c_char,
+    in_transaction: bool,
+    out: *mut *mut ScriptResult,
+) -> i32 {
+    if this.is_null() || script.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let executor = &*this;
+    let script_str = match CStr::from_ptr(script).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    match executor.try_execute_script(script_str, in_transaction) {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(result) => {
+            util::mem::handle_output_ptr(out, result);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Frees a script result.
+#[no_mangle]
+pub unsafe extern "C" fn script_result_free(this: *mut ScriptResult) {
+    if !this.is_null() {
+        Box::from_raw(this);
+    }
+}
+
+/// Gives the number of statements the script was split into.
+#[no_mangle]
+pub unsafe extern "C" fn script_result_statement_count(this: *const ScriptResult) -> size_t {
+    if this.is_null() {
+        return 0;
+    }
+    (&*this).statements().len()
+}
+
+/// Gets the SQL text of the statement at the given index, or `NULL` if the index is out
+/// of bounds.
+///
+/// [`datajoint_core_cstring_free`][crate::util::datajoint_core_cstring_free] must be called
+/// on the string returned from this function to avoid memory leaks.
+#[no_mangle]
+pub unsafe extern "C" fn script_result_statement(
+    this: *const ScriptResult,
+    index: size_t,
+) -> *const c_char {
+    if this.is_null() {
+        return ptr::null();
+    }
+    optional_string_to_c_str((&*this).statements().get(index).map(String::as_str))
+}
+
+/// Gives the number of rows affected by the statement at the given index, or `-1` if the
+/// statement did not run successfully.
+#[no_mangle]
+pub unsafe extern "C" fn script_result_rows_affected(
+    this: *const ScriptResult,
+    index: size_t,
+) -> i64 {
+    if this.is_null() {
+        return -1;
+    }
+    match (&*this).rows_affected().get(index) {
+        None => -1,
+        Some(&rows_affected) => rows_affected as i64,
+    }
+}
+
+/// Gives the index of the statement that failed, or `-1` if every statement succeeded.
+#[no_mangle]
+pub unsafe extern "C" fn script_result_failed_statement(this: *const ScriptResult) -> i64 {
+    if this.is_null() {
+        return -1;
+    }
+    match (&*this).failed_statement() {
+        None => -1,
+        Some(index) => index as i64,
+    }
+}
+
+/// Gives the error code of the statement that failed, or
+/// [`ErrorCode::Success`] if every statement succeeded.
+#[no_mangle]
+pub unsafe extern "C" fn script_result_error_code(this: *const ScriptResult) -> i32 {
+    if this.is_null() {
+        return ErrorCode::NullNotAllowed as i32;
+    }
+    match (&*this).error() {
+        None => ErrorCode::Success as i32,
+        Some(error) => error.code() as i32,
+    }
+}
+
+/// Gets the error message of the statement that failed, or `NULL` if every statement
+/// succeeded.
+///
+/// [`datajoint_core_cstring_free`][crate::util::datajoint_core_cstring_free] must be called
+/// on the string returned from this function to avoid memory leaks.
+#[no_mangle]
+pub unsafe extern "C" fn script_result_error_message(this: *const ScriptResult) -> *const c_char {
+    if this.is_null() {
+        return ptr::null();
+    }
+    match (&*this).error() {
+        None => ptr::null(),
+        Some(error) => optional_string_to_c_str(Some(&error.message())),
+    }
+}
+
+fn optional_string_to_c_str(string: Option<&str>) -> *const c_char {
+    match string {
+        None => ptr::null(),
+        Some(string) => match CString::new(string) {
+            Err(_) => ptr::null(),
+            Ok(string) => string.into_raw(),
+        },
+    }
+}
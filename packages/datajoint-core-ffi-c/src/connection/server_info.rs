@@ -0,0 +1,95 @@
+use datajoint_core::common::DatabaseType;
+use datajoint_core::connection::ServerInfo;
+use libc::c_char;
+use std::ffi::CString;
+use std::ptr;
+
+/// Frees a server information object.
+#[no_mangle]
+pub unsafe extern "C" fn server_info_free(this: *mut ServerInfo) {
+    if !this.is_null() {
+        Box::from_raw(this);
+    }
+}
+
+/// Gets the database backend of the server.
+#[no_mangle]
+pub unsafe extern "C" fn server_info_database_type(this: *const ServerInfo) -> DatabaseType {
+    if this.is_null() {
+        // Just return a default value since there is no way of representing the
+        // absence of a value with an enum.
+        return DatabaseType::MySql;
+    }
+    let info = &*this;
+    info.database_type
+}
+
+/// Gets the full version string reported by the server.
+///
+/// [`datajoint_core_cstring_free`][crate::util::datajoint_core_cstring_free] must be called
+/// on the string returned from this function to avoid memory leaks.
+#[no_mangle]
+pub unsafe extern "C" fn server_info_version(this: *const ServerInfo) -> *const c_char {
+    if this.is_null() {
+        return ptr::null();
+    }
+    let info = &*this;
+    optional_string_to_c_str(Some(&info.version))
+}
+
+/// Gets the major version of the server, such as `8` for MySQL 8.0.
+#[no_mangle]
+pub unsafe extern "C" fn server_info_major_version(this: *const ServerInfo) -> u32 {
+    if this.is_null() {
+        return 0;
+    }
+    let info = &*this;
+    info.major_version
+}
+
+/// Gets the minor version of the server, such as `0` for MySQL 8.0.
+#[no_mangle]
+pub unsafe extern "C" fn server_info_minor_version(this: *const ServerInfo) -> u32 {
+    if this.is_null() {
+        return 0;
+    }
+    let info = &*this;
+    info.minor_version
+}
+
+/// Gets the user the session is authenticated as, or `NULL` if the database has no users.
+///
+/// [`datajoint_core_cstring_free`][crate::util::datajoint_core_cstring_free] must be called
+/// on the string returned from this function to avoid memory leaks.
+#[no_mangle]
+pub unsafe extern "C" fn server_info_current_user(this: *const ServerInfo) -> *const c_char {
+    if this.is_null() {
+        return ptr::null();
+    }
+    let info = &*this;
+    optional_string_to_c_str(info.current_user.as_deref())
+}
+
+/// Gets the current database of the session, or `NULL` if no database is selected.
+///
+/// [`datajoint_core_cstring_free`][crate::util::datajoint_core_cstring_free] must be called
+/// on the string returned from this function to avoid memory leaks.
+#[no_mangle]
+pub unsafe extern "C" fn server_info_current_database(this: *const ServerInfo) -> *const c_char {
+    if this.is_null() {
+        return ptr::null();
+    }
+    let info = &*this;
+    optional_string_to_c_str(info.current_database.as_deref())
+}
+
+/// Converts an optional string to a nullable C string owned by the caller.
+fn optional_string_to_c_str(string: Option<&str>) -> *const c_char {
+    match string {
+        None => ptr::null(),
+        Some(string) => match CString::new(string) {
+            Err(_) => ptr::null(),
+            Ok(string) => string.into_raw(),
+        },
+    }
+}
@@ -0,0 +1,296 @@
+use crate::error::datajoint_core_set_last_error;
+use crate::results::TableRowVector;
+use crate::util;
+use datajoint_core::connection::{Cursor, ExecuteResult, Transaction};
+use datajoint_core::error::{DataJointError, ErrorCode};
+use datajoint_core::placeholders::PlaceholderArgumentVector;
+use datajoint_core::results::TableRow;
+use libc::c_char;
+use std::ffi::CStr;
+
+/// Frees a transaction, rolling it back if it has not been committed.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_free(this: *mut Transaction) {
+    if !this.is_null() {
+        Box::from_raw(this);
+    }
+}
+
+/// Executes the given query inside of the transaction.
+///
+/// The third parameter can be `NULL` or a collection of placeholder arguments to
+/// bind to the query. Once the query is executed, the [`PlaceholderArgumentVector`]
+/// is owned and deallocated by the library. In other words, the caller does not
+/// need to manually free the placeholder arguments after they are bound to a query.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_execute(
+    this: *mut Transaction,
+    query: *const c_char,
+    args: *mut PlaceholderArgumentVector,
+    out_size: *mut u64,
+) -> i32 {
+    if this.is_null() || query.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let transaction = &mut *this;
+    let query_str = match CStr::from_ptr(query).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    match if args.is_null() {
+        transaction.try_execute(query_str)
+    } else {
+        transaction.try_execute_ph(query_str, *Box::from_raw(args))
+    } {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(value) => {
+            if !out_size.is_null() {
+                *out_size = value.rows_affected;
+            }
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Executes the given query inside of the transaction, writing the rows affected and the
+/// id of an inserted row to an execute result. Warnings are not counted inside of
+/// transactions.
+///
+/// The third parameter can be `NULL` or a collection of placeholder arguments, which is
+/// owned and deallocated by the library once the query is executed.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_execute_result(
+    this: *mut Transaction,
+    query: *const c_char,
+    args: *mut PlaceholderArgumentVector,
+    out: *mut *mut ExecuteResult,
+) -> i32 {
+    if this.is_null() || query.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let transaction = &mut *this;
+    let query_str = match CStr::from_ptr(query).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    match if args.is_null() {
+        transaction.try_execute(query_str)
+    } else {
+        transaction.try_execute_ph(query_str, *Box::from_raw(args))
+    } {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(result) => {
+            util::mem::handle_output_ptr(out, result);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Fetches one row using the given query inside of the transaction.
+///
+/// The third parameter can be `NULL` or a collection of placeholder arguments to
+/// bind to the query. Once the query is executed, the [`PlaceholderArgumentVector`]
+/// is owned and deallocated by the library. In other words, the caller does not
+/// need to manually free the placeholder arguments after they are bound to a query.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_fetch_one(
+    this: *mut Transaction,
+    query: *const c_char,
+    args: *mut PlaceholderArgumentVector,
+    out: *mut *mut TableRow,
+) -> i32 {
+    if this.is_null() || query.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let transaction = &mut *this;
+    let query_str = match CStr::from_ptr(query).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    match if args.is_null() {
+        transaction.try_fetch_one(query_str)
+    } else {
+        transaction.try_fetch_one_ph(query_str, *Box::from_raw(args))
+    } {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(row) => {
+            util::mem::handle_output_ptr(out, row);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Fetches multiple rows using the given query inside of the transaction.
+///
+/// The third parameter can be `NULL` or a collection of placeholder arguments to
+/// bind to the query. Once the query is executed, the [`PlaceholderArgumentVector`]
+/// is owned and deallocated by the library. In other words, the caller does not
+/// need to manually free the placeholder arguments after they are bound to a query.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_fetch_all(
+    this: *mut Transaction,
+    query: *const c_char,
+    args: *mut PlaceholderArgumentVector,
+    out: *mut *mut TableRowVector,
+) -> i32 {
+    if this.is_null() || query.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let transaction = &mut *this;
+    let query_str = match CStr::from_ptr(query).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    match if args.is_null() {
+        transaction.try_fetch_all(query_str)
+    } else {
+        transaction.try_fetch_all_ph(query_str, *Box::from_raw(args))
+    } {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(rows) => {
+            let rows = TableRowVector::new_described(rows, || transaction.try_describe(query_str));
+            util::mem::handle_output_ptr(out, rows);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Creates a cursor for the given query inside of the transaction.
+///
+/// The transaction must not be used again, or freed, until the cursor is freed.
+///
+/// The third parameter can be `NULL` or a collection of placeholder arguments to
+/// bind to the query. Once the query is executed, the [`PlaceholderArgumentVector`]
+/// is owned and deallocated by the library. In other words, the caller does not
+/// need to manually free the placeholder arguments after they are bound to a query.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_cursor<'c>(
+    this: *mut Transaction<'c>,
+    query: *const c_char,
+    args: *mut PlaceholderArgumentVector,
+    out: *mut *mut Cursor<'c>,
+) -> i32 {
+    if this.is_null() || query.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let transaction = &mut *this;
+    let query_str = match CStr::from_ptr(query).to_str() {
+        Err(_) => {
+            return datajoint_core_set_last_error(DataJointError::new(ErrorCode::InvalidUtf8String))
+                as i32
+        }
+        Ok(value) => value,
+    };
+    match if args.is_null() {
+        transaction.cursor(query_str)
+    } else {
+        transaction.cursor_ph(query_str, *Box::from_raw(args))
+    } {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(cursor) => {
+            util::mem::handle_output_ptr(out, cursor);
+            ErrorCode::Success as i32
+        }
+    }
+}
+
+/// Establishes a new savepoint inside of the transaction.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_savepoint(this: *mut Transaction) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let transaction = &mut *this;
+    match transaction.savepoint() {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(_) => ErrorCode::Success as i32,
+    }
+}
+
+/// Releases the most recent savepoint, keeping all changes made since it was established.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_release_savepoint(this: *mut Transaction) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let transaction = &mut *this;
+    match transaction.release_savepoint() {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(_) => ErrorCode::Success as i32,
+    }
+}
+
+/// Discards all changes made since the most recent savepoint was established.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_rollback_to_savepoint(this: *mut Transaction) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let transaction = &mut *this;
+    match transaction.rollback_to_savepoint() {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(_) => ErrorCode::Success as i32,
+    }
+}
+
+/// Gives the number of savepoints currently established inside of the transaction.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_savepoint_depth(this: *const Transaction) -> usize {
+    if this.is_null() {
+        return 0;
+    }
+    let transaction = &*this;
+    transaction.savepoint_depth()
+}
+
+/// Commits the transaction.
+///
+/// The transaction is freed by this call, whether or not the commit succeeds,
+/// and must not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_commit(this: *mut Transaction) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    match Box::from_raw(this).commit() {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(_) => ErrorCode::Success as i32,
+    }
+}
+
+/// Rolls back the transaction.
+///
+/// The transaction is freed by this call, whether or not the rollback succeeds,
+/// and must not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn transaction_rollback(this: *mut Transaction) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    match Box::from_raw(this).rollback() {
+        Err(error) => datajoint_core_set_last_error(error) as i32,
+        Ok(_) => ErrorCode::Success as i32,
+    }
+}
@@ -1,7 +1,9 @@
 mod table_column;
+mod table_column_vector;
 mod table_row;
 mod table_row_vector;
 
 pub use table_column::*;
+pub use table_column_vector::*;
 pub use table_row::*;
 pub use table_row_vector::*;
@@ -36,6 +36,23 @@ pub extern "C" fn table_column_ref_name<'r>(this: *const TableColumnRef<'r>) ->
     }
 }
 
+/// Gives whether the column may hold nulls: `1` if it may, `0` if it may not and `-1` if
+/// unknown.
+///
+/// Only columns described by [`executor_describe`][crate::connection::executor_describe]
+/// know their nullability.
+#[no_mangle]
+pub extern "C" fn table_column_ref_nullable<'r>(this: *const TableColumnRef<'r>) -> i32 {
+    if this.is_null() {
+        return -1;
+    }
+    let column = unsafe { &*this };
+    match column.nullable() {
+        None => -1,
+        Some(nullable) => nullable as i32,
+    }
+}
+
 /// The DataJoint type for the column.
 #[no_mangle]
 pub extern "C" fn table_column_ref_type<'r>(this: *const TableColumnRef<'r>) -> DataJointType {
@@ -0,0 +1,63 @@
+use crate::error::datajoint_core_set_last_error;
+use crate::util;
+use datajoint_core::error::{DataJointError, ErrorCode};
+use datajoint_core::results::{ColumnIndex, TableColumn, TableColumnRef};
+use libc::size_t;
+
+/// A vector of owned table columns, which describes the rows of a query even when no
+/// rows are returned.
+pub struct TableColumnVector {
+    columns: Vec<TableColumn>,
+}
+
+impl TableColumnVector {
+    /// Creates a new table column vector.
+    pub fn new(columns: Vec<TableColumn>) -> Self {
+        TableColumnVector { columns }
+    }
+
+    /// Returns the columns.
+    pub fn columns(&self) -> &[TableColumn] {
+        &self.columns
+    }
+}
+
+/// Frees a table column vector, including all table columns inside.
+#[no_mangle]
+pub unsafe extern "C" fn table_column_vector_free(this: *mut TableColumnVector) {
+    if !this.is_null() {
+        Box::from_raw(this);
+    }
+}
+
+/// Gives the number of columns.
+#[no_mangle]
+pub unsafe extern "C" fn table_column_vector_size(this: *const TableColumnVector) -> size_t {
+    if this.is_null() {
+        return 0;
+    }
+    (&*this).columns().len()
+}
+
+/// Gets a column by ordinal index.
+///
+/// The column reference is valid for as long as the table column vector.
+#[no_mangle]
+pub unsafe extern "C" fn table_column_vector_get<'c>(
+    this: *const TableColumnVector,
+    ordinal: size_t,
+    out: *mut *mut TableColumnRef<'c>,
+) -> i32 {
+    if this.is_null() {
+        return datajoint_core_set_last_error(DataJointError::new(ErrorCode::NullNotAllowed))
+            as i32;
+    }
+    let columns = (&*this).columns();
+    match ordinal.owned_ordinal(columns) {
+        Err(err) => datajoint_core_set_last_error(err) as i32,
+        Ok(ordinal) => {
+            util::mem::handle_output_ptr(out, TableColumnRef::Owned(&columns[ordinal]));
+            ErrorCode::Success as i32
+        }
+    }
+}
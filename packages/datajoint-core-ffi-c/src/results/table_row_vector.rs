@@ -1,19 +1,47 @@
+use crate::results::TableColumnVector;
 use datajoint_core::error::{DataJointError, Error, ErrorCode};
-use datajoint_core::results::TableRow;
+use datajoint_core::results::{TableColumn, TableRow};
 use libc::size_t;
 use std::ptr;
 
 /// A vector of table rows, which is used to communicate the results of returning
 /// queries that return more than one row at a time.
+///
+/// The vector also holds the columns of the rows, which are known even if there are no
+/// rows.
 pub struct TableRowVector {
     rows: Vec<TableRow>,
+    columns: TableColumnVector,
 }
 
 #[allow(dead_code)]
 impl TableRowVector {
-    /// Creates a new table row vector.
+    /// Creates a new table row vector, whose columns are read from the first row.
     pub fn new(table_rows: Vec<TableRow>) -> Self {
-        return TableRowVector { rows: table_rows };
+        TableRowVector::new_described(table_rows, || Ok(Vec::new()))
+    }
+
+    /// Creates a new table row vector, calling the given function for the columns if
+    /// there are no rows to read them from.
+    ///
+    /// The rows were already fetched, so columns that fail to be described are left
+    /// empty rather than failing the fetch.
+    pub fn new_described<F>(table_rows: Vec<TableRow>, describe: F) -> Self
+    where
+        F: FnOnce() -> Result<Vec<TableColumn>, Error>,
+    {
+        let columns = match table_rows.first() {
+            Some(row) => row
+                .columns()
+                .iter()
+                .map(|column| column.to_owned())
+                .collect(),
+            None => describe().unwrap_or_default(),
+        };
+        TableRowVector {
+            rows: table_rows,
+            columns: TableColumnVector::new(columns),
+        }
     }
 
     /// Returns the number of rows.
@@ -59,6 +87,22 @@ pub extern "C" fn table_row_vector_size(this: *const TableRowVector) -> size_t {
     table_rows.row_count()
 }
 
+/// Gives an internal pointer to the columns of the rows, which are known even if there
+/// are no rows.
+///
+/// This pointer should not be freed by the user. Instead, call [`table_row_vector_free`]
+/// to free an entire table row vector.
+#[no_mangle]
+pub extern "C" fn table_row_vector_columns(
+    this: *const TableRowVector,
+) -> *const TableColumnVector {
+    if this.is_null() {
+        return ptr::null();
+    }
+    let table_rows: &TableRowVector = unsafe { &*this };
+    &table_rows.columns
+}
+
 /// Gives an internal pointer to a [`TableRow`] at the given index.
 ///
 /// This pointer should not be freed by the user. Instead, call [`table_row_vector_free`]
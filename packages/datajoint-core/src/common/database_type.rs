@@ -4,6 +4,7 @@
 pub enum DatabaseType {
     MySql,
     Postgres,
+    Sqlite,
 }
 
 /// Trait for types that are database type agnostic, which means they wrap
@@ -0,0 +1,248 @@
+use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::connection::{
+    AsyncCursor, AsyncExecutor, CancelHandle, ConnectionSettings, MockDatabase, MockPool, Pool,
+    QueryObserver, ResultCache, ServerInfo, SharedPool,
+};
+use crate::error::{DataJointError, Error, ErrorCode};
+use crate::placeholders::PlaceholderArgumentCollection;
+use std::sync::{Arc, OnceLock};
+
+/// A single asynchronous connection instance to an arbitrary SQL database.
+///
+/// Unlike [`Connection`][crate::connection::Connection], an `AsyncConnection` owns no
+/// runtime of its own, so it can be used from code that already runs inside of a tokio
+/// runtime.
+pub struct AsyncConnection {
+    /// The settings for the database connection.
+    ///
+    /// If changes to the settings are made after a connection has been established,
+    /// the client should call [`.disconnect()`][AsyncConnection::disconnect] and then
+    /// connect again to use the updated settings.
+    pub settings: ConnectionSettings,
+    pool: Option<SharedPool>,
+    cancel: OnceLock<CancelHandle>,
+    observer: Option<Arc<dyn QueryObserver>>,
+    cache: Option<ResultCache>,
+    // The mock database the connection runs its statements over, if any.
+    mock: Option<MockDatabase>,
+}
+
+impl DatabaseTypeAgnostic for AsyncConnection {
+    fn database_type(&self) -> DatabaseType {
+        match &self.pool {
+            None => self.settings.database_type,
+            Some(pool) => pool.get().database_type(),
+        }
+    }
+}
+
+impl AsyncConnection {
+    /// Creates a new connection to a SQL database based on the given settings.
+    ///
+    /// The connection is not actually established until
+    /// [`.connect()`][AsyncConnection::connect] is called.
+    pub fn new(settings: ConnectionSettings) -> Self {
+        AsyncConnection {
+            settings,
+            pool: None,
+            cancel: OnceLock::new(),
+            observer: None,
+            cache: None,
+            mock: None,
+        }
+    }
+
+    /// Creates a new connection that runs its statements over a mock database rather
+    /// than a database server, for testing code that runs queries.
+    ///
+    /// The database type of the settings is set to the type emulated by the mock database.
+    /// The connection is not usable until [`.connect()`][AsyncConnection::connect] is called, which
+    /// never fails for a mock database unless the result cache cannot be opened.
+    pub fn new_mock(database: MockDatabase) -> Self {
+        let mut settings = ConnectionSettings::new();
+        settings.database_type = database.database_type();
+        let mut connection = AsyncConnection::new(settings);
+        connection.mock = Some(database);
+        connection
+    }
+
+    /// Starts the connection to the SQL database according to the settings the connection
+    /// was initialized with.
+    pub async fn connect(&mut self) -> Result<(), Error> {
+        let cache = ResultCache::new(&self.settings)?;
+        let pool = match &self.mock {
+            None => Pool::connect(&self.settings).await?,
+            Some(database) => Pool::Mock(MockPool::new(database.clone())),
+        };
+        self.pool = Some(SharedPool::new(pool, &self.settings));
+        self.cache = cache;
+        Ok(())
+    }
+
+    fn get_connected_pool(&self) -> Result<&SharedPool, Error> {
+        match &self.pool {
+            Some(pool) if !pool.get().is_closed() => Ok(pool),
+            _ => Err(DataJointError::new(ErrorCode::NotConnected)),
+        }
+    }
+
+    /// Checks if the connection is still connected.
+    pub fn is_connected(&self) -> bool {
+        self.get_connected_pool().is_ok()
+    }
+
+    /// Disconnects from the SQL database.
+    ///
+    /// If the database connection has already been disconnected, this method
+    /// is a no-op.
+    ///
+    /// The connection can be restarted if desired.
+    /// Cursors still reading rows release their connections, and fail with
+    /// [`ErrorCode::NotConnected`] when they are read next.
+    /// Executors created before disconnecting that have a
+    /// [`ReconnectPolicy`][crate::connection::ReconnectPolicy] connect a new pool when they
+    /// run their next statement.
+    pub async fn disconnect(&self) {
+        if let Some(pool) = &self.pool {
+            if !pool.get().is_closed() {
+                pool.close().await;
+            }
+        }
+    }
+
+    /// Creates an executor to interact with the database over this connection.
+    ///
+    /// Panics on error.
+    pub fn executor(&self) -> AsyncExecutor<'_> {
+        self.try_executor().unwrap()
+    }
+
+    /// Creates an executor to interact with the database over this connection.
+    pub fn try_executor(&self) -> Result<AsyncExecutor<'_>, Error> {
+        Ok(AsyncExecutor::new(
+            self.get_connected_pool()?,
+            self.settings.reconnect,
+            self.settings.statement_timeout,
+            self.cancel.get().cloned(),
+            self.observer.clone(),
+            self.cache.as_ref(),
+        ))
+    }
+
+    /// Sets the observer that is notified of every statement run over this connection,
+    /// including statements in cursors.
+    ///
+    /// `None` removes the current observer. Executors that already exist keep the observer
+    /// they were created with.
+    pub fn set_query_observer(&mut self, observer: Option<Arc<dyn QueryObserver>>) {
+        self.observer = observer;
+    }
+
+    /// Gets the handle for cancelling statements running over this connection, which may
+    /// be used from any thread.
+    ///
+    /// Once the handle has been requested, every executor, cursor and query method of the
+    /// connection runs its statements under it, so that
+    /// [`cancel()`][CancelHandle::cancel] interrupts them all.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.get_or_init(CancelHandle::new).clone()
+    }
+
+    /// Checks that the database server can still be reached.
+    ///
+    /// Unlike [`is_connected()`][AsyncConnection::is_connected], this makes a round trip to the
+    /// server, so it detects sessions that were dropped by the server or the network.
+    pub async fn ping(&self) -> Result<(), Error> {
+        self.try_executor()?.ping().await
+    }
+
+    /// Fetches the database type, version, current user and current database of the
+    /// database server.
+    ///
+    /// Panics on error.
+    pub async fn server_info(&self) -> ServerInfo {
+        self.try_server_info().await.unwrap()
+    }
+
+    /// Fetches the database type, version, current user and current database of the
+    /// database server.
+    pub async fn try_server_info(&self) -> Result<ServerInfo, Error> {
+        self.try_executor()?.try_server_info().await
+    }
+
+    /// Executes the given non-returning query, returning the number of rows affected.
+    ///
+    /// Panics on error.
+    pub async fn execute_query(&self, query: &str) -> u64 {
+        self.try_execute_query(query).await.unwrap()
+    }
+
+    /// Executes the given non-returning query, returning the number of rows affected.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// Panics on error.
+    pub async fn execute_query_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> u64 {
+        self.try_execute_query_ph(query, args).await.unwrap()
+    }
+
+    /// Executes the given non-returning query, returning the number of rows affected.
+    pub async fn try_execute_query(&self, query: &str) -> Result<u64, Error> {
+        Ok(self.try_executor()?.try_execute(query).await?.rows_affected)
+    }
+
+    /// Executes the given non-returning query, returning the number of rows affected.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    pub async fn try_execute_query_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<u64, Error> {
+        Ok(self
+            .try_executor()?
+            .try_execute_ph(query, args)
+            .await?
+            .rows_affected)
+    }
+
+    /// Creates a cursor for streaming the results of the given returning query.
+    ///
+    /// Panics on error.
+    pub async fn fetch_query(&self, query: &str) -> AsyncCursor<'static> {
+        self.try_fetch_query(query).await.unwrap()
+    }
+
+    /// Creates a cursor for streaming the results of the given returning query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// Panics on error.
+    pub async fn fetch_query_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> AsyncCursor<'static> {
+        self.try_fetch_query_ph(query, args).await.unwrap()
+    }
+
+    /// Creates a cursor for streaming the results of the given returning query.
+    pub async fn try_fetch_query(&self, query: &str) -> Result<AsyncCursor<'static>, Error> {
+        self.try_executor()?.cursor(query).await
+    }
+
+    /// Creates a cursor for streaming the results of the given returning query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    pub async fn try_fetch_query_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<AsyncCursor<'static>, Error> {
+        self.try_executor()?.cursor_ph(query, args).await
+    }
+}
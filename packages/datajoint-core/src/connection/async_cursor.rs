@@ -0,0 +1,786 @@
+use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::connection::cancel::StatementLimits;
+use crate::connection::observer::ObservedQuery;
+use crate::connection::{CloseSignal, MockDatabase, Pool, SqlxConnection, SqlxTransaction};
+use crate::error::{DataJointError, Error, ErrorCode, SqlxError};
+use crate::placeholders::PlaceholderArgumentCollection;
+use crate::query::{MockQuery, Query};
+use crate::results::{TableColumn, TableRow};
+use crate::types::NativeType;
+use futures::channel::mpsc;
+use futures::future::{BoxFuture, FutureExt};
+use futures::ready;
+use futures::sink::SinkExt;
+use futures::stream::StreamExt;
+use futures_core::stream::{BoxStream, Stream};
+use sqlx::Execute;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::Sleep;
+
+/// The most rows a batch of rows reserves space for up front, so that huge batch sizes
+/// do not allocate more than the rows that are left.
+const BATCH_CAPACITY: usize = 1024;
+
+/// The placeholder arguments of the statement of a cursor, which are bound up front so
+/// that the cursor owns them rather than borrowing the arguments it was given.
+enum CursorArguments {
+    MySql(sqlx::mysql::MySqlArguments),
+    Postgres(sqlx::postgres::PgArguments),
+    Sqlite(sqlx::sqlite::SqliteArguments<'static>),
+    Mock(Vec<NativeType>),
+}
+
+impl CursorArguments {
+    /// Binds the given placeholder arguments, if any, for a statement of the given database
+    /// type.
+    fn bind(
+        database_type: DatabaseType,
+        mock: bool,
+        args: Option<impl PlaceholderArgumentCollection>,
+    ) -> Result<Self, Error> {
+        // The arguments are bound to a query without any text, only to take them out again.
+        let mut query = if mock {
+            Query::Mock(MockQuery::new(database_type, ""))
+        } else {
+            Query::new(database_type, "")
+        };
+        if let Some(args) = args {
+            query = args.bind_to_query(query)?;
+        }
+        Ok(match query {
+            Query::MySql(mut query) => {
+                CursorArguments::MySql(query.take_arguments().unwrap_or_default())
+            }
+            Query::Postgres(mut query) => {
+                CursorArguments::Postgres(query.take_arguments().unwrap_or_default())
+            }
+            Query::Sqlite(mut query) => {
+                CursorArguments::Sqlite(query.take_arguments().unwrap_or_default())
+            }
+            Query::Mock(query) => CursorArguments::Mock(query.arguments),
+        })
+    }
+}
+
+/// An error reading a row, which is sent to the cursor along with the rows.
+///
+/// Unlike [`Error`], it can be sent between threads.
+enum RowError {
+    Sqlx(sqlx::Error),
+    Other(ErrorCode, String),
+}
+
+impl RowError {
+    fn new(error: Error) -> Self {
+        RowError::Other(error.code(), error.message().into_owned())
+    }
+
+    fn into_error(self) -> Error {
+        match self {
+            RowError::Sqlx(error) => SqlxError::new(error),
+            RowError::Other(code, message) => DataJointError::new_with_message(&message, code),
+        }
+    }
+}
+
+/// A pooled connection owned by a cursor, which is returned to the pool once dropped
+/// unless it must be detached from the pool.
+struct CursorConnection {
+    connection: Option<SqlxConnection>,
+    // Set once the statement is interrupted, when the connection may still have results in
+    // flight.
+    detach: Arc<AtomicBool>,
+}
+
+impl Drop for CursorConnection {
+    fn drop(&mut self) {
+        if self.detach.load(Ordering::SeqCst) {
+            if let Some(connection) = self.connection.take() {
+                connection.detach();
+            }
+        }
+    }
+}
+
+/// The connection a cursor reads its rows from.
+enum RowSource<'c> {
+    Pool(Box<CursorConnection>),
+    Transaction(&'c mut SqlxTransaction),
+}
+
+/// A connection borrowed from a [`RowSource`] for running the statement of a cursor.
+enum SourceConnection<'s> {
+    MySql(&'s mut sqlx::MySqlConnection),
+    Postgres(&'s mut sqlx::PgConnection),
+    Sqlite(&'s mut sqlx::SqliteConnection),
+    Mock(&'s MockDatabase),
+}
+
+impl<'c> RowSource<'c> {
+    fn connection(&mut self) -> SourceConnection<'_> {
+        match self {
+            RowSource::Pool(owned) => match owned.connection.as_mut().unwrap() {
+                SqlxConnection::MySql(connection) => SourceConnection::MySql(connection),
+                SqlxConnection::Postgres(connection) => SourceConnection::Postgres(connection),
+                SqlxConnection::Sqlite(connection) => SourceConnection::Sqlite(connection),
+                SqlxConnection::Mock(database) => SourceConnection::Mock(database),
+            },
+            RowSource::Transaction(transaction) => match &mut **transaction {
+                SqlxTransaction::MySql(transaction) => SourceConnection::MySql(transaction),
+                SqlxTransaction::Postgres(transaction) => SourceConnection::Postgres(transaction),
+                SqlxTransaction::Sqlite(transaction) => SourceConnection::Sqlite(transaction),
+                SqlxTransaction::Mock(database) => SourceConnection::Mock(database),
+            },
+        }
+    }
+
+    /// Runs the statement over the connection, returning the stream of its rows.
+    fn fetch<'s>(
+        &'s mut self,
+        database_type: DatabaseType,
+        sql: &'s str,
+        args: CursorArguments,
+    ) -> BoxStream<'s, Result<TableRow, RowError>> {
+        fn rows<'s, R: 's>(
+            stream: BoxStream<'s, Result<R, sqlx::Error>>,
+            wrap: fn(R) -> TableRow,
+        ) -> BoxStream<'s, Result<TableRow, RowError>> {
+            stream
+                .map(move |result| match result {
+                    Err(err) => Err(RowError::Sqlx(err)),
+                    Ok(row) => Ok(wrap(row)),
+                })
+                .boxed()
+        }
+
+        match (self.connection(), args) {
+            (SourceConnection::MySql(connection), CursorArguments::MySql(args)) => rows(
+                sqlx::query_with(sql, args).fetch(connection),
+                TableRow::MySql,
+            ),
+            (SourceConnection::Postgres(connection), CursorArguments::Postgres(args)) => rows(
+                sqlx::query_with(sql, args).fetch(connection),
+                TableRow::Postgres,
+            ),
+            (SourceConnection::Sqlite(connection), CursorArguments::Sqlite(args)) => rows(
+                sqlx::query_with(sql, args).fetch(connection),
+                TableRow::Sqlite,
+            ),
+            (SourceConnection::Mock(database), CursorArguments::Mock(arguments)) => {
+                let mut query = MockQuery::new(database_type, sql);
+                query.arguments = arguments;
+                futures::stream::iter(database.cursor(query).map(|row| row.map_err(RowError::new)))
+                    .boxed()
+            }
+            _ => futures::stream::once(async {
+                Err(RowError::Other(
+                    ErrorCode::WrongDatabaseType,
+                    "prepared query is for the wrong database type".to_string(),
+                ))
+            })
+            .boxed(),
+        }
+    }
+}
+
+/// Runs the statement of a cursor, sending each of its rows to the cursor until the rows
+/// run out, the cursor stops receiving them or the pool is closed.
+///
+/// The future owns the statement, its arguments and its connection, so the stream of rows
+/// borrowing them lives inside of the future rather than alongside it in the cursor. The
+/// source is given back once the rows run out.
+async fn produce_rows<'c>(
+    mut source: RowSource<'c>,
+    database_type: DatabaseType,
+    sql: String,
+    args: CursorArguments,
+    mut sender: mpsc::Sender<Result<TableRow, RowError>>,
+    closed: BoxFuture<'static, ()>,
+) -> RowSource<'c> {
+    let mut rows = source.fetch(database_type, &sql, args);
+    let send_rows = async {
+        while let Some(row) = rows.next().await {
+            if sender.send(row).await.is_err() {
+                break;
+            }
+        }
+    };
+    let closing = tokio::select! {
+        _ = send_rows => false,
+        _ = closed => true,
+    };
+    drop(rows);
+    if closing {
+        // The connection may still have results in flight, so it is removed from the pool.
+        if let RowSource::Pool(owned) = &source {
+            owned.detach.store(true, Ordering::SeqCst);
+        }
+    }
+    source
+}
+
+/// The future running the statement of a cursor.
+enum Producer<'c> {
+    // Polled by the cursor whenever the cursor is polled.
+    Local(BoxFuture<'c, RowSource<'c>>),
+    // Runs on the runtime on its own, so that it releases its connection once the pool is
+    // closed even if the cursor is not read.
+    Spawned(SpawnedProducer),
+}
+
+/// A producer spawned on the runtime, which is aborted once dropped.
+struct SpawnedProducer(tokio::task::JoinHandle<()>);
+
+impl Drop for SpawnedProducer {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Limits on waiting for the rows of a cursor, along with the state needed to stop its
+/// statement on the server.
+struct CursorLimits {
+    // The server session running the statement, if any.
+    session_id: Option<u64>,
+    timeout: Option<Duration>,
+    // Resolves once the cursor is cancelled.
+    cancelled: BoxFuture<'static, ()>,
+    // Deadline for receiving the row currently being waited on.
+    deadline: Option<Pin<Box<Sleep>>>,
+    // Stops the interrupted statement on the server, before its error is reported.
+    stopping: Option<(BoxFuture<'static, ()>, ErrorCode)>,
+}
+
+impl CursorLimits {
+    /// Checks if the statement must be interrupted while waiting for the next row,
+    /// returning the error code to report.
+    fn poll_interrupted(&mut self, cx: &mut Context<'_>) -> Option<ErrorCode> {
+        if self.cancelled.as_mut().poll(cx).is_ready() {
+            return Some(ErrorCode::QueryCancelled);
+        }
+        if let Some(timeout) = self.timeout {
+            let deadline = self
+                .deadline
+                .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+            if deadline.as_mut().poll(cx).is_ready() {
+                return Some(ErrorCode::QueryTimedOut);
+            }
+        }
+        None
+    }
+}
+
+/// An object used to asynchronously iterate over a set of rows.
+///
+/// Implements [`Stream`], yielding rows as they are received from the database.
+///
+/// The timeout of the executor that created the cursor bounds the wait for each row,
+/// rather than the life of the whole cursor.
+///
+/// A cursor owns its statement, arguments and pooled connection, so a cursor created by an
+/// executor is `'static` and may be kept after the executor and connection are dropped. A
+/// cursor created inside of a transaction borrows the transaction instead.
+pub struct AsyncCursor<'c> {
+    // Runs the statement and sends its rows to the receiver. Returns the connection to the
+    // pool once the rows run out.
+    producer: Option<Producer<'c>>,
+    receiver: Option<mpsc::Receiver<Result<TableRow, RowError>>>,
+    // Detaches the pooled connection from the pool once the producer is dropped.
+    detach: Arc<AtomicBool>,
+    // The pool of a cursor run over a pooled connection.
+    pool: Option<Pool>,
+    // Signals a cursor run over a pooled connection that its pool is closed.
+    closed: Option<CloseSignal>,
+    // The transaction of a cursor run inside of a transaction, once the producer gives it
+    // back.
+    transaction: Option<&'c mut SqlxTransaction>,
+    // The columns of the rows, once known.
+    columns: Option<Vec<TableColumn>>,
+    // A row read ahead to find the columns, which is returned next.
+    peeked: Option<TableRow>,
+    query: String,
+    database_type: DatabaseType,
+    limits: Option<CursorLimits>,
+    // Reports the statement to the query observer once the cursor finishes.
+    observed: Option<ObservedQuery>,
+    // The number of rows returned so far.
+    rows: u64,
+}
+
+impl<'c> DatabaseTypeAgnostic for AsyncCursor<'c> {
+    fn database_type(&self) -> DatabaseType {
+        self.database_type
+    }
+}
+
+impl<'c> AsyncCursor<'c> {
+    /// Creates a new cursor that reads the rows of the given statement from the source.
+    fn new(
+        query: &str,
+        database_type: DatabaseType,
+        source: RowSource<'c>,
+        args: CursorArguments,
+        detach: Arc<AtomicBool>,
+        closed: Option<CloseSignal>,
+    ) -> Self {
+        // The producer sends each row as soon as the cursor has taken the one before it.
+        let (sender, receiver) = mpsc::channel(0);
+        let closing: BoxFuture<'static, ()> = match &closed {
+            None => Box::pin(std::future::pending()),
+            Some(closed) => Box::pin(closed.closed()),
+        };
+        AsyncCursor {
+            producer: Some(Producer::Local(Box::pin(produce_rows(
+                source,
+                database_type,
+                query.to_string(),
+                args,
+                sender,
+                closing,
+            )))),
+            receiver: Some(receiver),
+            detach,
+            pool: None,
+            closed,
+            transaction: None,
+            columns: None,
+            peeked: None,
+            query: query.to_string(),
+            database_type,
+            limits: None,
+            observed: None,
+            rows: 0,
+        }
+    }
+
+    /// Attaches the observed statement to a newly created cursor, or reports the error the
+    /// cursor failed to be created with.
+    fn with_observed(
+        query: &str,
+        observed: Option<ObservedQuery>,
+        result: Result<AsyncCursor<'c>, Error>,
+    ) -> Result<AsyncCursor<'c>, Error> {
+        match result {
+            Err(error) => {
+                if let Some(observed) = observed {
+                    observed.finish(query, 0, error.code());
+                }
+                Err(error)
+            }
+            Ok(mut cursor) => {
+                cursor.observed = observed;
+                Ok(cursor)
+            }
+        }
+    }
+
+    /// Reports the finished statement to the query observer, if it was not reported yet.
+    fn finish_observed(&mut self, error_code: ErrorCode) {
+        if let Some(observed) = self.observed.take() {
+            observed.finish(&self.query, self.rows, error_code);
+        }
+    }
+
+    /// Creates a new cursor over the rows of a statement run inside of a transaction.
+    ///
+    /// Mutably borrows the transaction until the cursor is dropped.
+    pub(crate) fn new_from_transaction(
+        query: &str,
+        transaction: &'c mut SqlxTransaction,
+        observed: Option<ObservedQuery>,
+        args: Option<impl PlaceholderArgumentCollection>,
+    ) -> Result<AsyncCursor<'c>, Error> {
+        let database_type = transaction.database_type();
+        let mock = matches!(transaction, SqlxTransaction::Mock(_));
+        let result = CursorArguments::bind(database_type, mock, args).map(move |args| {
+            AsyncCursor::new(
+                query,
+                database_type,
+                RowSource::Transaction(transaction),
+                args,
+                Arc::new(AtomicBool::new(false)),
+                None,
+            )
+        });
+        AsyncCursor::with_observed(query, observed, result)
+    }
+
+    /// Fetches up to the given number of rows.
+    ///
+    /// Panics on error.
+    pub async fn next_batch(&mut self, size: usize) -> Vec<TableRow> {
+        self.try_next_batch(size).await.unwrap()
+    }
+
+    /// Fetches up to the given number of rows, returning fewer rows only once the rows run
+    /// out.
+    ///
+    /// Returns an empty vector if there are no more rows. A size of 0 fails with
+    /// [`ErrorCode::InvalidBatchSize`], since its empty result would look like the end of
+    /// the rows.
+    pub async fn try_next_batch(&mut self, size: usize) -> Result<Vec<TableRow>, Error> {
+        if size == 0 {
+            return Err(DataJointError::new(ErrorCode::InvalidBatchSize));
+        }
+        let mut rows = Vec::with_capacity(size.min(BATCH_CAPACITY));
+        while rows.len() < size {
+            match self.next().await {
+                None => break,
+                Some(result) => rows.push(result?),
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Returns the columns of the rows of the cursor.
+    ///
+    /// Panics on error.
+    pub async fn columns(&mut self) -> Vec<TableColumn> {
+        self.try_columns().await.unwrap()
+    }
+
+    /// Returns the columns of the rows of the cursor, which are known even if the cursor
+    /// returns no rows.
+    ///
+    /// The columns are read from the next row without consuming it, or described by the
+    /// database if there are no more rows. Only described columns report their
+    /// nullability.
+    pub async fn try_columns(&mut self) -> Result<Vec<TableColumn>, Error> {
+        if self.columns.is_none() {
+            if let Some(row) = self.next().await {
+                self.peeked = Some(row?);
+            }
+        }
+        if let Some(columns) = &self.columns {
+            return Ok(columns.clone());
+        }
+        let columns = match (&mut self.transaction, &self.pool) {
+            (Some(transaction), _) => transaction.describe(&self.query).await?,
+            (None, Some(pool)) => pool.acquire().await?.describe(&self.query).await?,
+            (None, None) => Vec::new(),
+        };
+        self.columns = Some(columns.clone());
+        Ok(columns)
+    }
+
+    /// Fetches all remaining rows.
+    ///
+    /// Panics on error.
+    pub async fn rest(&mut self) -> Vec<TableRow> {
+        self.try_rest().await.unwrap()
+    }
+
+    /// Fetches all remaining rows.
+    pub async fn try_rest(&mut self) -> Result<Vec<TableRow>, Error> {
+        let mut rows = vec![];
+        while let Some(result) = self.next().await {
+            rows.push(result?);
+        }
+
+        Ok(rows)
+    }
+}
+
+impl AsyncCursor<'static> {
+    /// Creates a new cursor over the rows of a statement run over a connection of the pool.
+    ///
+    /// The statement is interrupted according to the given limits, and the connection is
+    /// released once the close signal of the pool is given.
+    pub(crate) async fn new_from_pool(
+        query: &str,
+        pool: &Pool,
+        closed: &CloseSignal,
+        limits: &StatementLimits,
+        observed: Option<ObservedQuery>,
+        args: Option<impl PlaceholderArgumentCollection>,
+    ) -> Result<AsyncCursor<'static>, Error> {
+        let result = AsyncCursor::open_from_pool(query, pool, closed, limits, args).await;
+        AsyncCursor::with_observed(query, observed, result)
+    }
+
+    async fn open_from_pool(
+        query: &str,
+        pool: &Pool,
+        closed: &CloseSignal,
+        limits: &StatementLimits,
+        args: Option<impl PlaceholderArgumentCollection>,
+    ) -> Result<AsyncCursor<'static>, Error> {
+        let database_type = pool.database_type();
+        let args = CursorArguments::bind(database_type, matches!(pool, Pool::Mock(_)), args)?;
+        let (connection, cursor_limits) = if limits.is_unlimited() {
+            (pool.acquire().await?, None)
+        } else {
+            let (connection, session_id) = pool.acquire_with_session_id().await?;
+            let cursor_limits = CursorLimits {
+                session_id,
+                timeout: limits.timeout,
+                cancelled: limits.cancelled(),
+                deadline: None,
+                stopping: None,
+            };
+            (connection, Some(cursor_limits))
+        };
+        let detach = Arc::new(AtomicBool::new(false));
+        let source = RowSource::Pool(Box::new(CursorConnection {
+            connection: Some(connection),
+            detach: detach.clone(),
+        }));
+        let mut cursor = AsyncCursor::new(
+            query,
+            database_type,
+            source,
+            args,
+            detach,
+            Some(closed.clone()),
+        );
+        // Run the statement on the runtime, if there is one, so that closing the pool does
+        // not wait for the cursor to be read again.
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            if let Some(Producer::Local(producer)) = cursor.producer.take() {
+                let handle = runtime.spawn(async move {
+                    producer.await;
+                });
+                cursor.producer = Some(Producer::Spawned(SpawnedProducer(handle)));
+            }
+        }
+        cursor.pool = Some(pool.clone());
+        cursor.limits = cursor_limits;
+        Ok(cursor)
+    }
+}
+
+impl<'c> AsyncCursor<'c> {
+    /// Checks if the pool of the cursor is closed.
+    fn is_pool_closed(&self) -> bool {
+        matches!(&self.closed, Some(closed) if closed.is_closed())
+    }
+
+    /// Stops reading rows once the pool of the cursor is closed, releasing the connection,
+    /// and returns the error to report.
+    fn release_closed(&mut self) -> Error {
+        // The connection may still have results in flight, so it is removed from the pool.
+        self.detach.store(true, Ordering::SeqCst);
+        self.receiver = None;
+        self.producer = None;
+        DataJointError::new(ErrorCode::NotConnected)
+    }
+
+    /// Polls for the next row, interrupting the statement according to the limits of the
+    /// cursor.
+    fn poll_row(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<TableRow, Error>>> {
+        if let Some(limits) = &mut self.limits {
+            if let Some((stopping, _)) = &mut limits.stopping {
+                ready!(stopping.as_mut().poll(cx));
+                let (_, code) = limits.stopping.take().unwrap();
+                return Poll::Ready(Some(Err(DataJointError::new(code))));
+            }
+        }
+
+        if self.receiver.is_some() && self.is_pool_closed() {
+            return Poll::Ready(Some(Err(self.release_closed())));
+        }
+        let receiver = match &mut self.receiver {
+            None => return Poll::Ready(None),
+            Some(receiver) => receiver,
+        };
+        let finished = match &mut self.producer {
+            None => false,
+            Some(Producer::Local(producer)) => match producer.as_mut().poll(cx) {
+                Poll::Pending => false,
+                Poll::Ready(source) => {
+                    // Return the connection to the pool as soon as the rows run out, but keep
+                    // the transaction for describing the columns.
+                    if let RowSource::Transaction(transaction) = source {
+                        self.transaction = Some(transaction);
+                    }
+                    true
+                }
+            },
+            Some(Producer::Spawned(producer)) => producer.0.poll_unpin(cx).is_ready(),
+        };
+        if finished {
+            self.producer = None;
+        }
+        let poll = receiver
+            .poll_next_unpin(cx)
+            .map(|row| row.map(|row| row.map_err(RowError::into_error)));
+
+        if let Poll::Ready(None) = poll {
+            if self.is_pool_closed() {
+                return Poll::Ready(Some(Err(self.release_closed())));
+            }
+            self.receiver = None;
+        }
+        let limits = match &mut self.limits {
+            None => return poll,
+            Some(limits) => limits,
+        };
+        if poll.is_ready() {
+            limits.deadline = None;
+            return poll;
+        }
+        let code = match limits.poll_interrupted(cx) {
+            None => return Poll::Pending,
+            Some(code) => code,
+        };
+
+        // Stop waiting for rows, and stop the statement on the server. The connection may
+        // still have results in flight, so it is removed from the pool before the producer
+        // can notice that the rows are no longer received.
+        if limits.session_id.is_some() {
+            self.detach.store(true, Ordering::SeqCst);
+        }
+        self.receiver = None;
+        self.producer = None;
+        match limits.session_id {
+            None => Poll::Ready(Some(Err(DataJointError::new(code)))),
+            Some(session_id) => {
+                let pool = self.pool.clone().unwrap();
+                limits.stopping = Some((
+                    Box::pin(async move {
+                        // The cursor already failed, so failing to stop the statement is not
+                        // reported.
+                        let _ = pool.cancel_session(session_id).await;
+                    }),
+                    code,
+                ));
+                self.poll_row(cx)
+            }
+        }
+    }
+}
+
+impl<'c> Stream for AsyncCursor<'c> {
+    type Item = Result<TableRow, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(row) = self.peeked.take() {
+            return Poll::Ready(Some(Ok(row)));
+        }
+        let poll = self.poll_row(cx);
+        match &poll {
+            Poll::Pending => {}
+            Poll::Ready(Some(Ok(row))) => {
+                self.rows += 1;
+                if self.columns.is_none() {
+                    self.columns = Some(
+                        row.columns()
+                            .iter()
+                            .map(|column| column.to_owned())
+                            .collect(),
+                    );
+                }
+            }
+            Poll::Ready(Some(Err(error))) => self.finish_observed(error.code()),
+            Poll::Ready(None) => self.finish_observed(ErrorCode::Success),
+        }
+        poll
+    }
+}
+
+impl<'c> Drop for AsyncCursor<'c> {
+    fn drop(&mut self) {
+        // A cursor dropped before its last row still reports the rows it returned.
+        self.finish_observed(ErrorCode::Success);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{ConnectionSettings, MockPool, MockRows, MockStatement, SharedPool};
+    use crate::types::DataJointType;
+    use futures::executor::block_on;
+
+    fn mock_rows() -> MockRows {
+        MockRows::new(&[("id", DataJointType::Int)])
+            .row(vec![NativeType::Int32(1)])
+            .row(vec![NativeType::Int32(2)])
+    }
+
+    #[test]
+    fn test_cursor_outlives_pool_and_arguments() {
+        let database = MockDatabase::new(DatabaseType::Sqlite);
+        database.push_rows(mock_rows());
+
+        let mut cursor: AsyncCursor<'static> = {
+            let pool = Pool::Mock(MockPool::new(database.clone()));
+            let query = String::from("select id from t where id > ?");
+            let args = vec![NativeType::Int32(0)];
+            block_on(AsyncCursor::new_from_pool(
+                &query,
+                &pool,
+                &CloseSignal::default(),
+                &StatementLimits::default(),
+                None,
+                Some(args),
+            ))
+            .unwrap()
+        };
+
+        let rows = block_on(cursor.try_rest()).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].decode(rows[1].column(0)), NativeType::Int32(2));
+        assert!(block_on(cursor.next()).is_none());
+        assert_eq!(
+            database.statements(),
+            vec![MockStatement {
+                sql: "select id from t where id > ?".to_string(),
+                arguments: vec![NativeType::Int32(0)],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_cursor_dropped_before_last_row() {
+        let database = MockDatabase::new(DatabaseType::MySql);
+        database.push_rows(mock_rows());
+        let pool = Pool::Mock(MockPool::new(database.clone()));
+
+        let mut cursor = block_on(AsyncCursor::new_from_pool(
+            "select id from t",
+            &pool,
+            &CloseSignal::default(),
+            &StatementLimits::default(),
+            None,
+            None as Option<Vec<NativeType>>,
+        ))
+        .unwrap();
+        assert_eq!(block_on(cursor.try_next_batch(1)).unwrap().len(), 1);
+        drop(cursor);
+        drop(pool);
+        assert_eq!(database.statements().len(), 1);
+    }
+
+    #[test]
+    fn test_cursor_released_once_pool_closed() {
+        let database = MockDatabase::new(DatabaseType::Postgres);
+        database.push_rows(mock_rows());
+        let shared = SharedPool::new(
+            Pool::Mock(MockPool::new(database)),
+            &ConnectionSettings::new(),
+        );
+        let (pool, closed) = shared.get_with_close_signal();
+
+        let mut cursor = block_on(AsyncCursor::new_from_pool(
+            "select id from t",
+            &pool,
+            &closed,
+            &StatementLimits::default(),
+            None,
+            None as Option<Vec<NativeType>>,
+        ))
+        .unwrap();
+        assert_eq!(block_on(cursor.try_next_batch(1)).unwrap().len(), 1);
+        block_on(shared.close());
+        assert!(pool.is_closed());
+        let error = block_on(cursor.try_next_batch(1)).err().unwrap();
+        assert_eq!(error.code(), ErrorCode::NotConnected);
+        assert!(block_on(cursor.next()).is_none());
+    }
+}
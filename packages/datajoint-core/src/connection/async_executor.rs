@@ -0,0 +1,912 @@
+use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::connection::bulk_load::{
+    copy_statement, insert_statement, write_csv_row, DelimitedReader,
+};
+use crate::connection::cache::CacheKey;
+use crate::connection::cancel::StatementLimits;
+use crate::connection::observer::{finish_observed, ObservedQuery};
+use crate::connection::reconnect::is_idempotent;
+use crate::connection::{
+    AsyncCursor, AsyncPreparedStatement, CachedFetch, CancelHandle, DelimitedFormat, ExecuteResult,
+    InterruptibleConnection, Pool, QueryObserver, ReconnectPolicy, ResultCache, ScriptResult,
+    ServerInfo, SharedPool,
+};
+use crate::error::{DataJointError, Error, ErrorCode};
+use crate::placeholders::{PlaceholderArgumentCollection, PlaceholderArgumentVector};
+use crate::query::{split_script, BatchStatement, Query};
+use crate::results::{TableColumn, TableRow};
+use std::future::Future;
+use std::io::{BufReader, Read};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The size of the chunks of data sent by a Postgres `COPY`, in bytes.
+const COPY_CHUNK_SIZE: usize = 1 << 20;
+
+/// The number of rows of delimited text inserted at once into databases without `COPY`.
+const LOAD_BATCH_ROWS: usize = 10000;
+
+/// An object used to interact with a database by executing queries asynchronously.
+///
+/// Instances of `AsyncExecutor` should not be created manually but by calling
+/// [`try_executor()`][crate::connection::AsyncConnection::try_executor] on an
+/// [`AsyncConnection`][crate::connection::AsyncConnection] instance.
+///
+/// This is the asynchronous counterpart of [`Executor`][crate::connection::Executor],
+/// which runs these same methods to completion.
+#[derive(Clone)]
+pub struct AsyncExecutor<'c> {
+    pool: &'c SharedPool,
+    reconnect: Option<ReconnectPolicy>,
+    limits: StatementLimits,
+    observer: Option<Arc<dyn QueryObserver>>,
+    cache: Option<&'c ResultCache>,
+    count_warnings: bool,
+    warnings_as_errors: bool,
+}
+
+impl<'c> DatabaseTypeAgnostic for AsyncExecutor<'c> {
+    fn database_type(&self) -> DatabaseType {
+        self.pool().database_type()
+    }
+}
+
+impl<'c> AsyncExecutor<'c> {
+    /// Creates a new executor over the given connection pool.
+    pub(crate) fn new(
+        pool: &'c SharedPool,
+        reconnect: Option<ReconnectPolicy>,
+        timeout: Option<Duration>,
+        cancel: Option<CancelHandle>,
+        observer: Option<Arc<dyn QueryObserver>>,
+        cache: Option<&'c ResultCache>,
+    ) -> Self {
+        AsyncExecutor {
+            pool,
+            reconnect,
+            limits: StatementLimits { timeout, cancel },
+            observer,
+            cache,
+            count_warnings: false,
+            warnings_as_errors: false,
+        }
+    }
+
+    /// Gets the current pool of the connection.
+    fn pool(&self) -> Pool {
+        self.pool.get()
+    }
+
+    /// Gets the maximum amount of time a statement run by the executor may take.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.limits.timeout
+    }
+
+    /// Sets the maximum amount of time a statement run by the executor may take before it
+    /// fails with [`ErrorCode::QueryTimedOut`][crate::error::ErrorCode::QueryTimedOut] and
+    /// is cancelled on the server.
+    ///
+    /// Overrides the statement timeout of the connection settings. `None` lets statements
+    /// run indefinitely.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.limits.timeout = timeout;
+    }
+
+    /// Sets the handle used to cancel the statements run by the executor, replacing the
+    /// handle of the connection.
+    pub fn set_cancel_handle(&mut self, cancel: CancelHandle) {
+        self.limits.cancel = Some(cancel);
+    }
+
+    /// Checks if the warnings produced by statements are counted.
+    pub fn count_warnings(&self) -> bool {
+        self.count_warnings
+    }
+
+    /// Sets whether the warnings produced by statements are counted and reported by
+    /// [`ExecuteResult::warnings`].
+    ///
+    /// Counting warnings takes another round-trip to the server after every statement, so
+    /// they are not counted by default. Only MySQL reports warnings.
+    pub fn set_count_warnings(&mut self, count_warnings: bool) {
+        self.count_warnings = count_warnings;
+    }
+
+    /// Checks if statements that produce warnings fail.
+    pub fn warnings_as_errors(&self) -> bool {
+        self.warnings_as_errors
+    }
+
+    /// Sets whether statements that produce warnings fail with
+    /// [`ErrorCode::StatementWarnings`][crate::error::ErrorCode::StatementWarnings], such as
+    /// MySQL inserts that silently truncate values.
+    ///
+    /// The error message holds the warnings reported by `SHOW WARNINGS`. The statement has
+    /// already run when it fails, so its changes are kept unless it runs in a transaction
+    /// that is rolled back. Warnings are counted while this is set. Only MySQL reports
+    /// warnings.
+    pub fn set_warnings_as_errors(&mut self, warnings_as_errors: bool) {
+        self.warnings_as_errors = warnings_as_errors;
+    }
+
+    /// Checks if statements run by the executor count their warnings.
+    fn counts_warnings(&self) -> bool {
+        self.count_warnings || self.warnings_as_errors
+    }
+
+    /// Fails a statement that produced warnings if the executor treats warnings as errors,
+    /// with the warnings fetched by the given function.
+    async fn check_warnings<F, Fut>(
+        &self,
+        result: Result<ExecuteResult, Error>,
+        show_warnings: F,
+    ) -> Result<ExecuteResult, Error>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<String>, Error>>,
+    {
+        match result {
+            Ok(ExecuteResult {
+                warnings: Some(count),
+                ..
+            }) if self.warnings_as_errors && count > 0 => {
+                let warnings = show_warnings().await?;
+                Err(DataJointError::new_with_message(
+                    &format!(
+                        "statement produced {} warnings: {}",
+                        count,
+                        warnings.join("; ")
+                    ),
+                    ErrorCode::StatementWarnings,
+                ))
+            }
+            result => result,
+        }
+    }
+
+    /// Executes a non-returning query, interrupting it according to the limits of the
+    /// executor.
+    async fn execute_limited(&self, query: Query<'_>) -> Result<ExecuteResult, Error> {
+        if self.limits.is_unlimited() {
+            if !self.warnings_as_errors {
+                return self.pool().try_execute(query, self.count_warnings).await;
+            }
+            let mut connection = self.pool().acquire().await?;
+            let result = connection.try_execute(query, true).await;
+            return self
+                .check_warnings(result, || connection.show_warnings())
+                .await;
+        }
+        let pool = self.pool();
+        let mut connection = InterruptibleConnection::acquire(&pool).await?;
+        let interrupted = self.limits.interrupted();
+        let result = tokio::select! {
+            result = connection.connection.try_execute(query, self.counts_warnings()) => result,
+            error = interrupted => Err(error),
+        };
+        let result = self
+            .check_warnings(result, || connection.connection.show_warnings())
+            .await;
+        connection.finish(result).await
+    }
+
+    /// Fetches one row, interrupting the query according to the limits of the executor.
+    async fn fetch_one_limited(&self, query: Query<'_>) -> Result<TableRow, Error> {
+        if self.limits.is_unlimited() {
+            return self.pool().try_fetch_one(query).await;
+        }
+        let pool = self.pool();
+        let mut connection = InterruptibleConnection::acquire(&pool).await?;
+        let interrupted = self.limits.interrupted();
+        let result = tokio::select! {
+            result = connection.connection.try_fetch_one(query) => result,
+            error = interrupted => Err(error),
+        };
+        connection.finish(result).await
+    }
+
+    /// Fetches all rows, interrupting the query according to the limits of the executor.
+    async fn fetch_all_limited(&self, query: Query<'_>) -> Result<Vec<TableRow>, Error> {
+        if self.limits.is_unlimited() {
+            return self.pool().try_fetch_all(query).await;
+        }
+        let pool = self.pool();
+        let mut connection = InterruptibleConnection::acquire(&pool).await?;
+        let interrupted = self.limits.interrupted();
+        let result = tokio::select! {
+            result = connection.connection.try_fetch_all(query) => result,
+            error = interrupted => Err(error),
+        };
+        connection.finish(result).await
+    }
+
+    /// Runs a statement, retrying it according to the reconnect policy if the connection
+    /// to the server is lost.
+    ///
+    /// The pool of the connection is replaced by a newly connected pool before retrying a
+    /// statement that failed because the pool was closed.
+    async fn run_with_reconnect<T, F, Fut>(&self, query: &str, mut run: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let policy = match &self.reconnect {
+            None => return run().await,
+            Some(policy) => policy,
+        };
+        let idempotent = is_idempotent(query);
+        let mut attempt = 0;
+        loop {
+            let generation = self.pool.generation();
+            match run().await {
+                Err(error) if policy.should_retry(attempt, &error, idempotent) => {
+                    tokio::time::sleep(policy.backoff(attempt)).await;
+                    attempt += 1;
+                    if ReconnectPolicy::should_reconnect(&error) {
+                        self.pool.reconnect(generation).await?;
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Runs a statement with the given placeholder arguments, if there are any, retrying
+    /// it according to the reconnect policy.
+    ///
+    /// Collections are consumed by binding them, so retries bind the
+    /// [`values()`][PlaceholderArgumentCollection::values] of the arguments instead.
+    /// Statements with arguments that do not report their values run only once.
+    async fn run_with_reconnect_ph<'q, T, A, F, Fut>(
+        &self,
+        query: &'q str,
+        args: Option<A>,
+        run: F,
+    ) -> Result<T, Error>
+    where
+        A: PlaceholderArgumentCollection,
+        F: Fn(Query<'q>) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let (mut args, values) = match args {
+            None => {
+                return self
+                    .run_with_reconnect(query, || run(self.pool().query(query)))
+                    .await
+            }
+            Some(args) => match args.values() {
+                None => return run(args.bind_to_query(self.pool().query(query))?).await,
+                Some(values) => (Some(args), values),
+            },
+        };
+        let run = &run;
+        self.run_with_reconnect(query, || {
+            let bound = match args.take() {
+                Some(args) => args.bind_to_query(self.pool().query(query)),
+                None => values.clone().bind_to_query(self.pool().query(query)),
+            };
+            async move { run(bound?).await }
+        })
+        .await
+    }
+
+    /// Executes the given query over the connection.
+    ///
+    /// Panics on error.
+    pub async fn execute(&self, query: &str) -> ExecuteResult {
+        self.try_execute(query).await.unwrap()
+    }
+
+    /// Executes the given query over the connection.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// Panics on error.
+    pub async fn execute_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> ExecuteResult {
+        self.try_execute_ph(query, args).await.unwrap()
+    }
+
+    /// Executes the given query over the connection.
+    ///
+    /// Returns the number of rows affected by the query, along with the id of an inserted
+    /// row and the number of warnings, if the database reports them. Warnings are only
+    /// counted if the executor [counts warnings][AsyncExecutor::set_count_warnings] or
+    /// treats them as errors.
+    pub async fn try_execute(&self, query: &str) -> Result<ExecuteResult, Error> {
+        let observed = ObservedQuery::start(&self.observer, Vec::new);
+        let result = self
+            .run_with_reconnect(query, || self.execute_limited(self.pool().query(query)))
+            .await;
+        finish_observed(observed, query, &result, |result| result.rows_affected);
+        result
+    }
+
+    /// Executes the given query over the connection.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    pub async fn try_execute_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<ExecuteResult, Error> {
+        let observed = ObservedQuery::start(&self.observer, || args.redacted());
+        let result = self
+            .run_with_reconnect_ph(query, Some(args), |query| self.execute_limited(query))
+            .await;
+        finish_observed(observed, query, &result, |result| result.rows_affected);
+        result
+    }
+
+    /// Executes the given query once for every set of placeholder arguments.
+    ///
+    /// Panics on error.
+    pub async fn execute_many(&self, query: &str, args: Vec<PlaceholderArgumentVector>) -> u64 {
+        self.try_execute_many(query, args).await.unwrap()
+    }
+
+    /// Executes the given query once for every set of placeholder arguments.
+    ///
+    /// Inserts of a single row of values, such as
+    /// `insert into tweet (text, owner_id) values (?, ?)`, are batched into statements
+    /// inserting many rows, each run in one round-trip. Batches stay within the parameter
+    /// limit of the database and the `max_allowed_packet` of MySQL servers. Other
+    /// statements run once for every set of arguments.
+    ///
+    /// Returns the total number of rows affected. Statements run separately, so rows
+    /// inserted before a failing statement are kept unless the batch runs in a transaction.
+    pub async fn try_execute_many(
+        &self,
+        query: &str,
+        args: Vec<PlaceholderArgumentVector>,
+    ) -> Result<u64, Error> {
+        let parameters = match args.first() {
+            None => return Ok(0),
+            Some(first) => first.len(),
+        };
+        if args.iter().any(|row| row.len() != parameters) {
+            return Err(DataJointError::new_with_message(
+                "every set of placeholder arguments must have the same length",
+                ErrorCode::WrongArgumentCount,
+            ));
+        }
+
+        let batch = match BatchStatement::parse(self.database_type(), query, parameters) {
+            Some(batch) => batch,
+            None => {
+                let mut rows_affected = 0;
+                for row in args {
+                    rows_affected += self.try_execute_ph(query, row).await?.rows_affected;
+                }
+                return Ok(rows_affected);
+            }
+        };
+        let chunks = batch.chunks(&args, self.pool().max_statement_size().await?);
+        let mut args = args.into_iter();
+        let mut rows_affected = 0;
+        for rows in chunks {
+            let chunk: PlaceholderArgumentVector = args.by_ref().take(rows).flatten().collect();
+            rows_affected += self
+                .try_execute_ph(&batch.sql(rows), chunk)
+                .await?
+                .rows_affected;
+        }
+        Ok(rows_affected)
+    }
+
+    /// Runs the statements of a SQL script in order.
+    ///
+    /// Panics if the script could not be run, but not if one of its statements fails.
+    pub async fn execute_script(&self, script: &str, in_transaction: bool) -> ScriptResult {
+        self.try_execute_script(script, in_transaction)
+            .await
+            .unwrap()
+    }
+
+    /// Runs the statements of a SQL script in order, stopping at the first statement that
+    /// fails.
+    ///
+    /// The script is split into statements at semicolons outside of quoted text, comments
+    /// and Postgres dollar-quoted bodies. MySQL scripts may change the delimiter with
+    /// `DELIMITER` commands. Every statement runs over the same pooled connection, so
+    /// statements such as `SET` affect the statements after them.
+    ///
+    /// If `in_transaction` is set, the script runs inside of a single transaction, which
+    /// is committed if every statement succeeds and rolled back otherwise. Like other
+    /// statements in transactions, these statements are not interrupted by the timeout or
+    /// cancel handle of the executor.
+    ///
+    /// The failing statement is reported by the returned [`ScriptResult`]. Fails if a
+    /// connection cannot be acquired or the transaction cannot be begun or committed.
+    pub async fn try_execute_script(
+        &self,
+        script: &str,
+        in_transaction: bool,
+    ) -> Result<ScriptResult, Error> {
+        let statements: Vec<String> = split_script(self.database_type(), script)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let mut result = ScriptResult::new(statements.clone(), in_transaction);
+        if in_transaction {
+            let mut transaction = self.pool().begin().await?;
+            for statement in &statements {
+                let observed = ObservedQuery::start(&self.observer, Vec::new);
+                let query = transaction.query(statement);
+                let executed = transaction.try_execute(query, self.counts_warnings()).await;
+                let executed = self
+                    .check_warnings(executed, || transaction.show_warnings())
+                    .await;
+                finish_observed(observed, statement, &executed, |result| {
+                    result.rows_affected
+                });
+                match executed {
+                    Ok(executed) => result.push_rows_affected(executed.rows_affected),
+                    Err(error) => {
+                        result.fail(error);
+                        // The statement already failed, so failing to roll back is not
+                        // reported. The server rolls back once the connection is closed.
+                        let _ = transaction.rollback().await;
+                        return Ok(result);
+                    }
+                }
+            }
+            transaction.commit().await?;
+            return Ok(result);
+        }
+
+        let pool = self.pool();
+        let mut connection = InterruptibleConnection::acquire(&pool).await?;
+        for statement in &statements {
+            let observed = ObservedQuery::start(&self.observer, Vec::new);
+            let interrupted = self.limits.interrupted();
+            let executed = tokio::select! {
+                result = connection
+                    .connection
+                    .try_execute(self.pool().query(statement), self.counts_warnings()) => result,
+                error = interrupted => Err(error),
+            };
+            let executed = self
+                .check_warnings(executed, || connection.connection.show_warnings())
+                .await;
+            finish_observed(observed, statement, &executed, |result| {
+                result.rows_affected
+            });
+            match executed {
+                Ok(executed) => result.push_rows_affected(executed.rows_affected),
+                Err(error) => {
+                    // Stops the statement on the server if it was interrupted.
+                    if let Err(error) = connection.finish::<()>(Err(error)).await {
+                        result.fail(error);
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Checks that every row being loaded into a table has a value for every column.
+    fn check_load_rows(columns: &[&str], rows: &[PlaceholderArgumentVector]) -> Result<(), Error> {
+        match rows.iter().find(|row| row.len() != columns.len()) {
+            None => Ok(()),
+            Some(row) => Err(DataJointError::new_with_message(
+                &format!("row has {} values for {} columns", row.len(), columns.len()),
+                ErrorCode::WrongArgumentCount,
+            )),
+        }
+    }
+
+    /// Runs a Postgres `COPY ... FROM STDIN` statement over a pooled connection, sending
+    /// the chunks of data given by the function and interrupting the copy according to
+    /// the limits of the executor.
+    async fn copy_in<F>(&self, statement: &str, next_chunk: F) -> Result<u64, Error>
+    where
+        F: FnMut() -> Result<Option<Vec<u8>>, Error>,
+    {
+        let observed = ObservedQuery::start(&self.observer, Vec::new);
+        let pool = self.pool();
+        let result = match InterruptibleConnection::acquire(&pool).await {
+            Err(error) => Err(error),
+            Ok(mut connection) => {
+                let interrupted = self.limits.interrupted();
+                let result = tokio::select! {
+                    result = connection.connection.copy_in(statement, next_chunk) => result,
+                    error = interrupted => Err(error),
+                };
+                match result {
+                    // The driver may leave messages of a failed copy unread, so the
+                    // connection is not reused.
+                    Err(error)
+                        if !matches!(
+                            error.code(),
+                            ErrorCode::QueryCancelled | ErrorCode::QueryTimedOut
+                        ) =>
+                    {
+                        connection.connection.detach();
+                        Err(error)
+                    }
+                    result => connection.finish(result).await,
+                }
+            }
+        };
+        finish_observed(observed, statement, &result, |rows| *rows);
+        result
+    }
+
+    /// Loads rows of values into the given columns of a table.
+    ///
+    /// Panics on error.
+    pub async fn bulk_load(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: Vec<PlaceholderArgumentVector>,
+    ) -> u64 {
+        self.try_bulk_load(table, columns, rows).await.unwrap()
+    }
+
+    /// Loads rows of values into the given columns of a table, returning the number of
+    /// rows loaded.
+    ///
+    /// Postgres streams the rows through `COPY ... FROM STDIN`, which is interrupted by the
+    /// timeout and cancel handle of the executor. MySQL and SQLite insert them with batched
+    /// inserts, like [`try_execute_many()`][AsyncExecutor::try_execute_many]. MySQL does not
+    /// use `LOAD DATA`, since the MySQL driver does not support `LOCAL INFILE`.
+    pub async fn try_bulk_load(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: Vec<PlaceholderArgumentVector>,
+    ) -> Result<u64, Error> {
+        AsyncExecutor::check_load_rows(columns, &rows)?;
+        if !matches!(self.pool(), Pool::Postgres(_)) {
+            let insert = insert_statement(self.database_type(), table, columns);
+            return self.try_execute_many(&insert, rows).await;
+        }
+        let mut rows = rows.into_iter();
+        let statement = copy_statement(table, columns, DelimitedFormat::Csv);
+        self.copy_in(&statement, || {
+            let mut data = String::new();
+            for row in rows.by_ref() {
+                write_csv_row(&mut data, &row);
+                if data.len() >= COPY_CHUNK_SIZE {
+                    break;
+                }
+            }
+            if data.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(data.into_bytes()))
+            }
+        })
+        .await
+    }
+
+    /// Loads delimited text into the given columns of a table.
+    ///
+    /// Panics on error.
+    pub async fn bulk_load_reader(
+        &self,
+        table: &str,
+        columns: &[&str],
+        reader: impl Read,
+        format: DelimitedFormat,
+    ) -> u64 {
+        self.try_bulk_load_reader(table, columns, reader, format)
+            .await
+            .unwrap()
+    }
+
+    /// Loads delimited text into the given columns of a table, returning the number of
+    /// rows loaded.
+    ///
+    /// Postgres streams the text through `COPY ... FROM STDIN`, which parses it on the
+    /// server. MySQL and SQLite parse the text and insert its rows with batched inserts,
+    /// binding every value as a string or null. The reader is read on the calling thread,
+    /// so it should not block for long.
+    pub async fn try_bulk_load_reader(
+        &self,
+        table: &str,
+        columns: &[&str],
+        mut reader: impl Read,
+        format: DelimitedFormat,
+    ) -> Result<u64, Error> {
+        if let Pool::Postgres(_) = self.pool() {
+            let statement = copy_statement(table, columns, format);
+            let mut buffer = vec![0; COPY_CHUNK_SIZE];
+            return self
+                .copy_in(&statement, || loop {
+                    match reader.read(&mut buffer) {
+                        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                        Err(err) => {
+                            return Err(DataJointError::new_with_message(
+                                &format!("failed to read delimited text: {}", err),
+                                ErrorCode::IoError,
+                            ))
+                        }
+                        Ok(0) => return Ok(None),
+                        Ok(size) => return Ok(Some(buffer[..size].to_vec())),
+                    }
+                })
+                .await;
+        }
+
+        let insert = insert_statement(self.database_type(), table, columns);
+        let mut records = DelimitedReader::new(BufReader::new(reader), format);
+        let mut rows_loaded = 0;
+        loop {
+            let rows: Vec<PlaceholderArgumentVector> = records
+                .by_ref()
+                .take(LOAD_BATCH_ROWS)
+                .collect::<Result<_, _>>()?;
+            if rows.is_empty() {
+                return Ok(rows_loaded);
+            }
+            AsyncExecutor::check_load_rows(columns, &rows)?;
+            rows_loaded += self.try_execute_many(&insert, rows).await?;
+        }
+    }
+
+    /// Returns the key of the result of a query in the result cache, if there is one.
+    fn cache_key<A>(&self, fetch: CachedFetch, query: &str, args: &Option<A>) -> Option<CacheKey>
+    where
+        A: PlaceholderArgumentCollection,
+    {
+        let values = match args {
+            None => Some(Vec::new()),
+            Some(args) => args.values(),
+        };
+        self.cache?.key(fetch, query, values)
+    }
+
+    /// Fetches one row, binding the placeholder arguments if there are any.
+    async fn fetch_one_observed<A>(&self, query: &str, args: Option<A>) -> Result<TableRow, Error>
+    where
+        A: PlaceholderArgumentCollection,
+    {
+        let observed = ObservedQuery::start(&self.observer, || match &args {
+            None => Vec::new(),
+            Some(args) => args.redacted(),
+        });
+        let result = self
+            .run_with_reconnect_ph(query, args, |query| self.fetch_one_limited(query))
+            .await;
+        finish_observed(observed, query, &result, |_| 1);
+        result
+    }
+
+    /// Fetches all rows, binding the placeholder arguments if there are any.
+    async fn fetch_all_observed<A>(
+        &self,
+        query: &str,
+        args: Option<A>,
+    ) -> Result<Vec<TableRow>, Error>
+    where
+        A: PlaceholderArgumentCollection,
+    {
+        let observed = ObservedQuery::start(&self.observer, || match &args {
+            None => Vec::new(),
+            Some(args) => args.redacted(),
+        });
+        let result = self
+            .run_with_reconnect_ph(query, args, |query| self.fetch_all_limited(query))
+            .await;
+        finish_observed(observed, query, &result, |rows| rows.len() as u64);
+        result
+    }
+
+    /// Fetches one row, reading and writing the result cache.
+    async fn fetch_one_cached<A>(&self, query: &str, args: Option<A>) -> Result<TableRow, Error>
+    where
+        A: PlaceholderArgumentCollection,
+    {
+        let key = self.cache_key(CachedFetch::One, query, &args);
+        if let (Some(cache), Some(key)) = (self.cache, &key) {
+            if let Some(row) = cache.read(key).and_then(|rows| rows.into_iter().next()) {
+                return Ok(row);
+            }
+        }
+        let result = self.fetch_one_observed(query, args).await;
+        if let (Some(cache), Some(key), Ok(row)) = (self.cache, &key, &result) {
+            cache.write(key, std::slice::from_ref(row));
+        }
+        result
+    }
+
+    /// Fetches all rows, reading and writing the result cache.
+    async fn fetch_all_cached<A>(
+        &self,
+        query: &str,
+        args: Option<A>,
+    ) -> Result<Vec<TableRow>, Error>
+    where
+        A: PlaceholderArgumentCollection,
+    {
+        let key = self.cache_key(CachedFetch::All, query, &args);
+        if let (Some(cache), Some(key)) = (self.cache, &key) {
+            if let Some(rows) = cache.read(key) {
+                return Ok(rows);
+            }
+        }
+        let result = self.fetch_all_observed(query, args).await;
+        if let (Some(cache), Some(key), Ok(rows)) = (self.cache, &key, &result) {
+            cache.write(key, rows);
+        }
+        result
+    }
+
+    /// Fetches one row using the given query.
+    ///
+    /// Panics on error.
+    pub async fn fetch_one(&self, query: &str) -> TableRow {
+        self.try_fetch_one(query).await.unwrap()
+    }
+
+    /// Fetches one row using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// Panics on error.
+    pub async fn fetch_one_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> TableRow {
+        self.try_fetch_one_ph(query, args).await.unwrap()
+    }
+
+    /// Fetches one row using the given query.
+    ///
+    /// Reads and writes the result cache if the connection has one.
+    pub async fn try_fetch_one(&self, query: &str) -> Result<TableRow, Error> {
+        self.fetch_one_cached(query, None as Option<PlaceholderArgumentVector>)
+            .await
+    }
+
+    /// Fetches one row using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution. Reads and
+    /// writes the result cache if the connection has one.
+    pub async fn try_fetch_one_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<TableRow, Error> {
+        self.fetch_one_cached(query, Some(args)).await
+    }
+
+    /// Fetches multiple rows using the given query.
+    ///
+    /// Panics on error.
+    pub async fn fetch_all(&self, query: &str) -> Vec<TableRow> {
+        self.try_fetch_all(query).await.unwrap()
+    }
+
+    /// Fetches multiple rows using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// Panics on error.
+    pub async fn fetch_all_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Vec<TableRow> {
+        self.try_fetch_all_ph(query, args).await.unwrap()
+    }
+
+    /// Fetches multiple rows using the given query.
+    ///
+    /// Reads and writes the result cache if the connection has one.
+    pub async fn try_fetch_all(&self, query: &str) -> Result<Vec<TableRow>, Error> {
+        self.fetch_all_cached(query, None as Option<PlaceholderArgumentVector>)
+            .await
+    }
+
+    /// Fetches multiple rows using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution. Reads and
+    /// writes the result cache if the connection has one.
+    pub async fn try_fetch_all_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<Vec<TableRow>, Error> {
+        self.fetch_all_cached(query, Some(args)).await
+    }
+
+    /// Checks that the database server can still be reached over the connection.
+    pub async fn ping(&self) -> Result<(), Error> {
+        self.pool().ping().await
+    }
+
+    /// Fetches the version, current user and current database of the database server.
+    ///
+    /// Panics on error.
+    pub async fn server_info(&self) -> ServerInfo {
+        self.try_server_info().await.unwrap()
+    }
+
+    /// Fetches the version, current user and current database of the database server.
+    ///
+    /// Server information is never read from the result cache.
+    pub async fn try_server_info(&self) -> Result<ServerInfo, Error> {
+        let database_type = self.database_type();
+        let row = self
+            .fetch_one_observed(
+                ServerInfo::query(database_type),
+                None as Option<PlaceholderArgumentVector>,
+            )
+            .await?;
+        ServerInfo::from_row(database_type, &row)
+    }
+
+    /// Describes the columns of the rows returned by the given query, without running it.
+    ///
+    /// Panics on error.
+    pub async fn describe(&self, query: &str) -> Vec<TableColumn> {
+        self.try_describe(query).await.unwrap()
+    }
+
+    /// Describes the columns of the rows returned by the given query, without running it.
+    ///
+    /// Unlike the columns of fetched rows, described columns are known for queries that
+    /// return no rows and report whether they may hold nulls, if the database can tell.
+    /// Non-returning statements have no columns, and mock databases describe none.
+    pub async fn try_describe(&self, query: &str) -> Result<Vec<TableColumn>, Error> {
+        let mut connection = self.pool().acquire().await?;
+        connection.describe(query).await
+    }
+
+    /// Creates a cursor for the given query, which streams rows as they are received.
+    ///
+    /// The cursor holds a pooled connection until all of its rows are read or it is dropped,
+    /// or until the connection disconnects, after which it fails with
+    /// [`ErrorCode::NotConnected`]. Rows streamed by a cursor are never retried after a lost
+    /// connection.
+    pub async fn cursor(&self, query: &str) -> Result<AsyncCursor<'static>, Error> {
+        let (pool, closed) = self.pool.get_with_close_signal();
+        AsyncCursor::new_from_pool(
+            query,
+            &pool,
+            &closed,
+            &self.limits,
+            ObservedQuery::start(&self.observer, Vec::new),
+            None as Option<PlaceholderArgumentVector>,
+        )
+        .await
+    }
+
+    /// Creates a cursor for the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    pub async fn cursor_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<AsyncCursor<'static>, Error> {
+        let observed = ObservedQuery::start(&self.observer, || args.redacted());
+        let (pool, closed) = self.pool.get_with_close_signal();
+        AsyncCursor::new_from_pool(query, &pool, &closed, &self.limits, observed, Some(args)).await
+    }
+
+    /// Prepares the given statement on the database server, returning a statement that
+    /// can be run any number of times with different placeholder arguments.
+    ///
+    /// Preparing reports the number of placeholder parameters of the statement and the
+    /// columns of the rows it returns, without running it.
+    pub async fn prepare(&self, query: &str) -> Result<AsyncPreparedStatement<'c>, Error> {
+        let mut connection = self.pool().acquire().await?;
+        let (parameter_count, columns) = connection.prepare(query).await?;
+        Ok(AsyncPreparedStatement::new(
+            self.clone(),
+            query,
+            parameter_count,
+            columns,
+        ))
+    }
+}
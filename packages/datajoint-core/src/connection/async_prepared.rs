@@ -0,0 +1,151 @@
+use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::connection::{AsyncCursor, AsyncExecutor, ExecuteResult};
+use crate::error::{DataJointError, Error, ErrorCode};
+use crate::placeholders::PlaceholderArgumentCollection;
+use crate::results::{TableColumn, TableRow};
+
+/// A statement prepared on the database server, which can be run any number of times
+/// with different placeholder arguments.
+///
+/// Instances of `AsyncPreparedStatement` should not be created manually but by calling
+/// [`prepare()`][crate::connection::AsyncExecutor::prepare] on an
+/// [`AsyncExecutor`][crate::connection::AsyncExecutor] instance.
+///
+/// The statement keeps its SQL text, parameter count and columns, rather than a handle to
+/// the server-side statement, since a server-side statement belongs to a single pooled
+/// connection. Each run goes through the executor, and reusing the server-side statement
+/// depends on the statement cache of the database driver: every pooled connection keeps
+/// up to 100 recently run statements, so a run over a connection that still has the
+/// statement skips parsing the SQL again, while a connection that never ran it, or
+/// evicted it, prepares it again. Statements are run with the timeout, cancel handle,
+/// reconnect policy, observer and result cache of the executor that prepared them.
+///
+/// This is the asynchronous counterpart of
+/// [`PreparedStatement`][crate::connection::PreparedStatement], which runs these same
+/// methods to completion.
+pub struct AsyncPreparedStatement<'c> {
+    executor: AsyncExecutor<'c>,
+    sql: String,
+    parameter_count: Option<usize>,
+    columns: Vec<TableColumn>,
+}
+
+impl<'c> DatabaseTypeAgnostic for AsyncPreparedStatement<'c> {
+    fn database_type(&self) -> DatabaseType {
+        self.executor.database_type()
+    }
+}
+
+impl<'c> AsyncPreparedStatement<'c> {
+    /// Creates a new prepared statement run by the given executor.
+    pub(crate) fn new(
+        executor: AsyncExecutor<'c>,
+        sql: &str,
+        parameter_count: Option<usize>,
+        columns: Vec<TableColumn>,
+    ) -> Self {
+        AsyncPreparedStatement {
+            executor,
+            sql: sql.to_string(),
+            parameter_count,
+            columns,
+        }
+    }
+
+    /// Returns the SQL text of the statement.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Returns the number of placeholder parameters of the statement, or `None` if the
+    /// database does not report it.
+    pub fn parameter_count(&self) -> Option<usize> {
+        self.parameter_count
+    }
+
+    /// Returns the columns of the rows returned by the statement, which is empty for
+    /// non-returning statements.
+    ///
+    /// SQLite only declares the types of table columns, so columns of expressions have
+    /// the [`Unknown`][crate::types::DataJointType::Unknown] type.
+    pub fn columns(&self) -> &[TableColumn] {
+        &self.columns
+    }
+
+    /// Checks that the number of placeholder arguments matches the parameters of the
+    /// statement.
+    fn check_arguments(&self, args: &impl PlaceholderArgumentCollection) -> Result<(), Error> {
+        if let (Some(expected), Some(given)) = (self.parameter_count, args.argument_count()) {
+            if expected != given {
+                return Err(DataJointError::new_with_message(
+                    &format!(
+                        "prepared statement expects {} placeholder arguments, but {} were given",
+                        expected, given
+                    ),
+                    ErrorCode::WrongArgumentCount,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes the statement, binding the placeholder arguments.
+    ///
+    /// Panics on error.
+    pub async fn execute(&self, args: impl PlaceholderArgumentCollection) -> ExecuteResult {
+        self.try_execute(args).await.unwrap()
+    }
+
+    /// Executes the statement, binding the placeholder arguments.
+    ///
+    /// Returns the number of rows affected by the statement, along with the id of an
+    /// inserted row and the number of warnings, if the database reports them.
+    pub async fn try_execute(
+        &self,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<ExecuteResult, Error> {
+        self.check_arguments(&args)?;
+        self.executor.try_execute_ph(&self.sql, args).await
+    }
+
+    /// Fetches one row using the statement, binding the placeholder arguments.
+    ///
+    /// Panics on error.
+    pub async fn fetch_one(&self, args: impl PlaceholderArgumentCollection) -> TableRow {
+        self.try_fetch_one(args).await.unwrap()
+    }
+
+    /// Fetches one row using the statement, binding the placeholder arguments.
+    pub async fn try_fetch_one(
+        &self,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<TableRow, Error> {
+        self.check_arguments(&args)?;
+        self.executor.try_fetch_one_ph(&self.sql, args).await
+    }
+
+    /// Fetches multiple rows using the statement, binding the placeholder arguments.
+    ///
+    /// Panics on error.
+    pub async fn fetch_all(&self, args: impl PlaceholderArgumentCollection) -> Vec<TableRow> {
+        self.try_fetch_all(args).await.unwrap()
+    }
+
+    /// Fetches multiple rows using the statement, binding the placeholder arguments.
+    pub async fn try_fetch_all(
+        &self,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<Vec<TableRow>, Error> {
+        self.check_arguments(&args)?;
+        self.executor.try_fetch_all_ph(&self.sql, args).await
+    }
+
+    /// Creates a cursor for the statement, binding the placeholder arguments.
+    pub async fn cursor(
+        &self,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<AsyncCursor<'static>, Error> {
+        self.check_arguments(&args)?;
+        self.executor.cursor_ph(&self.sql, args).await
+    }
+}
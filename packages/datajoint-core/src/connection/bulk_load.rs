@@ -0,0 +1,349 @@
+use crate::common::DatabaseType;
+use crate::error::{DataJointError, Error, ErrorCode};
+use crate::placeholders::PlaceholderArgumentVector;
+use crate::types::NativeType;
+use std::fmt::Write;
+use std::io::BufRead;
+
+/// The format of delimited text loaded into a table.
+///
+/// Neither format has a header line, so every line holds the values of the given columns
+/// in order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, FromPrimitive)]
+#[repr(i32)]
+pub enum DelimitedFormat {
+    /// Comma-separated values, where a field may be quoted with `"` to hold commas, quotes
+    /// and line breaks, and a doubled `""` stands for a quote inside of a quoted field.
+    ///
+    /// An empty unquoted field is null, while `""` is an empty string.
+    Csv,
+    /// Tab-separated values in the text format of Postgres `COPY`, where `\N` is null and
+    /// `\t`, `\n`, `\r` and `\\` stand for a tab, line break, carriage return and backslash.
+    Tsv,
+}
+
+/// Quotes an identifier, such as a table or column name, for the given database type.
+///
+/// Names qualified by a schema, such as `lab.session`, are quoted part by part.
+pub(crate) fn quote_identifier(database_type: DatabaseType, name: &str) -> String {
+    let quote = match database_type {
+        DatabaseType::MySql => '`',
+        _ => '"',
+    };
+    name.split('.')
+        .map(|part| {
+            let escaped = part.replace(quote, &format!("{}{}", quote, quote));
+            format!("{}{}{}", quote, escaped, quote)
+        })
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+/// Quotes a table name and its columns as the target of a load, such as
+/// `"session" ("id", "name")`.
+fn quote_target(database_type: DatabaseType, table: &str, columns: &[&str]) -> String {
+    let columns: Vec<String> = columns
+        .iter()
+        .map(|column| quote_identifier(database_type, column))
+        .collect();
+    format!(
+        "{} ({})",
+        quote_identifier(database_type, table),
+        columns.join(", ")
+    )
+}
+
+/// Creates the Postgres `COPY` statement that loads delimited text into the columns of a
+/// table.
+pub(crate) fn copy_statement(table: &str, columns: &[&str], format: DelimitedFormat) -> String {
+    let format = match format {
+        DelimitedFormat::Csv => "csv",
+        DelimitedFormat::Tsv => "text",
+    };
+    format!(
+        "COPY {} FROM STDIN WITH (FORMAT {})",
+        quote_target(DatabaseType::Postgres, table, columns),
+        format
+    )
+}
+
+/// Creates an insert of a single row into the columns of a table, which is run with many
+/// sets of arguments to load rows into databases without `COPY`.
+pub(crate) fn insert_statement(
+    database_type: DatabaseType,
+    table: &str,
+    columns: &[&str],
+) -> String {
+    let placeholders: Vec<String> = (1..=columns.len())
+        .map(|number| match database_type {
+            DatabaseType::Postgres => format!("${}", number),
+            _ => "?".to_string(),
+        })
+        .collect();
+    format!(
+        "INSERT INTO {} VALUES ({})",
+        quote_target(database_type, table, columns),
+        placeholders.join(", ")
+    )
+}
+
+/// Appends a row of values to CSV data in the format read by Postgres `COPY`.
+///
+/// Nulls are empty unquoted fields, strings are always quoted so that empty strings are
+/// kept, and bytes are written in the hex format of `bytea`.
+pub(crate) fn write_csv_row(data: &mut String, row: &[NativeType]) {
+    for (index, value) in row.iter().enumerate() {
+        if index > 0 {
+            data.push(',');
+        }
+        match value {
+            NativeType::None => (),
+            NativeType::String(string) => {
+                data.push('"');
+                data.push_str(&string.replace('"', "\"\""));
+                data.push('"');
+            }
+            NativeType::Bytes(bytes) => {
+                data.push_str("\\x");
+                for byte in bytes {
+                    write!(data, "{:02x}", byte).unwrap();
+                }
+            }
+            value => write!(data, "{}", value).unwrap(),
+        }
+    }
+    data.push('\n');
+}
+
+/// Reads the records of delimited text as rows of string and null values.
+pub(crate) struct DelimitedReader<R> {
+    reader: R,
+    format: DelimitedFormat,
+    // The number of the line being read, for reporting malformed text.
+    line: usize,
+}
+
+impl<R> DelimitedReader<R>
+where
+    R: BufRead,
+{
+    /// Creates a reader of delimited text in the given format.
+    pub fn new(reader: R, format: DelimitedFormat) -> Self {
+        DelimitedReader {
+            reader,
+            format,
+            line: 0,
+        }
+    }
+
+    fn malformed(&self, message: &str) -> Error {
+        DataJointError::new_with_message(
+            &format!("{} on line {}", message, self.line),
+            ErrorCode::MalformedDelimitedText,
+        )
+    }
+
+    /// Reads the next line, without its line break, or `None` at the end of the text.
+    fn read_line(&mut self) -> Result<Option<String>, Error> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Err(err) if err.kind() == std::io::ErrorKind::InvalidData => {
+                Err(DataJointError::new(ErrorCode::InvalidUtf8String))
+            }
+            Err(err) => Err(DataJointError::new_with_message(
+                &format!("failed to read delimited text: {}", err),
+                ErrorCode::IoError,
+            )),
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                self.line += 1;
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Ok(Some(line))
+            }
+        }
+    }
+
+    /// Reads the fields of the next CSV record, which may span several lines.
+    fn read_csv_record(&mut self, mut line: String) -> Result<PlaceholderArgumentVector, Error> {
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        loop {
+            let mut chars = line.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if in_quotes => {
+                        if chars.peek() == Some(&'"') {
+                            chars.next();
+                            field.push('"');
+                        } else {
+                            in_quotes = false;
+                        }
+                    }
+                    '"' if field.is_empty() && !quoted => {
+                        quoted = true;
+                        in_quotes = true;
+                    }
+                    ',' if !in_quotes => {
+                        row.push(DelimitedReader::<R>::csv_value(&mut field, quoted));
+                        quoted = false;
+                    }
+                    c => field.push(c),
+                }
+            }
+            if !in_quotes {
+                row.push(DelimitedReader::<R>::csv_value(&mut field, quoted));
+                return Ok(row);
+            }
+            // A quoted field holds a line break, so the record continues on the next line.
+            field.push('\n');
+            line = match self.read_line()? {
+                None => return Err(self.malformed("unterminated quoted field")),
+                Some(line) => line,
+            };
+        }
+    }
+
+    fn csv_value(field: &mut String, quoted: bool) -> NativeType {
+        let field = std::mem::take(field);
+        if field.is_empty() && !quoted {
+            NativeType::None
+        } else {
+            NativeType::String(field)
+        }
+    }
+
+    /// Reads the fields of a line of text in the format of Postgres `COPY`.
+    fn read_tsv_record(&self, line: &str) -> Result<PlaceholderArgumentVector, Error> {
+        let mut row = Vec::new();
+        for field in line.split('\t') {
+            if field == "\\N" {
+                row.push(NativeType::None);
+                continue;
+            }
+            let mut value = String::with_capacity(field.len());
+            let mut chars = field.chars();
+            while let Some(c) = chars.next() {
+                if c != '\\' {
+                    value.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    None => return Err(self.malformed("unterminated escape sequence")),
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some('v') => value.push('\u{b}'),
+                    Some(c) => value.push(c),
+                }
+            }
+            row.push(NativeType::String(value));
+        }
+        Ok(row)
+    }
+}
+
+impl<R> Iterator for DelimitedReader<R>
+where
+    R: BufRead,
+{
+    type Item = Result<PlaceholderArgumentVector, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match self.read_line() {
+            Err(err) => return Some(Err(err)),
+            Ok(line) => line?,
+        };
+        Some(match self.format {
+            DelimitedFormat::Csv => self.read_csv_record(line),
+            DelimitedFormat::Tsv => self.read_tsv_record(&line),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(value: &str) -> NativeType {
+        NativeType::String(value.to_string())
+    }
+
+    #[test]
+    fn test_statements() {
+        assert_eq!(
+            copy_statement("lab.spike", &["unit", "time"], DelimitedFormat::Tsv),
+            "COPY \"lab\".\"spike\" (\"unit\", \"time\") FROM STDIN WITH (FORMAT text)"
+        );
+        assert_eq!(
+            insert_statement(DatabaseType::MySql, "spike", &["unit", "odd`name"]),
+            "INSERT INTO `spike` (`unit`, `odd``name`) VALUES (?, ?)"
+        );
+        assert_eq!(
+            insert_statement(DatabaseType::Postgres, "spike", &["unit", "time"]),
+            "INSERT INTO \"spike\" (\"unit\", \"time\") VALUES ($1, $2)"
+        );
+    }
+
+    #[test]
+    fn test_write_csv_row() {
+        let mut data = String::new();
+        write_csv_row(
+            &mut data,
+            &[
+                NativeType::Int32(7),
+                NativeType::None,
+                string(""),
+                string("say \"hi\",\nbye"),
+                NativeType::Bytes(vec![0, 255]),
+            ],
+        );
+        assert_eq!(data, "7,,\"\",\"say \"\"hi\"\",\nbye\",\\x00ff\n");
+    }
+
+    #[test]
+    fn test_read_csv() {
+        let text = "1,,\"\"\r\n2,\"a,\"\"b\"\"\nc\",plain\n";
+        let rows: Vec<PlaceholderArgumentVector> =
+            DelimitedReader::new(text.as_bytes(), DelimitedFormat::Csv)
+                .collect::<Result<_, _>>()
+                .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec![string("1"), NativeType::None, string("")],
+                vec![string("2"), string("a,\"b\"\nc"), string("plain")],
+            ]
+        );
+
+        let mut reader = DelimitedReader::new("1,\"open\n2".as_bytes(), DelimitedFormat::Csv);
+        match reader.next().unwrap() {
+            Ok(_) => panic!("Unterminated quoted field was read."),
+            Err(err) => assert_eq!(err.code(), ErrorCode::MalformedDelimitedText),
+        }
+    }
+
+    #[test]
+    fn test_read_tsv() {
+        let text = "1\t\\N\ta\\tb\\\\c\n2\t\t\\n\n";
+        let rows: Vec<PlaceholderArgumentVector> =
+            DelimitedReader::new(text.as_bytes(), DelimitedFormat::Tsv)
+                .collect::<Result<_, _>>()
+                .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec![string("1"), NativeType::None, string("a\tb\\c")],
+                vec![string("2"), string(""), string("\n")],
+            ]
+        );
+    }
+}
@@ -0,0 +1,425 @@
+use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::connection::ConnectionSettings;
+use crate::error::{DataJointError, Error, ErrorCode};
+use crate::hash::Hash;
+use crate::placeholders::PlaceholderArgument;
+use crate::results::{OwnedRow, TableColumn, TableRow};
+use crate::types::NativeType;
+use crate::util::IntegerEnum;
+use serde_json::{json, Map, Value};
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Version of the format of cache files.
+///
+/// Files written in any other version are treated as cache misses, and are overwritten
+/// when results are written to the cache.
+const CACHE_FORMAT_VERSION: u64 = 1;
+
+/// Counter for naming the temporary files that cache files are written to.
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Enum type for representing how a connection uses the result cache.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, FromPrimitive)]
+#[repr(i32)]
+pub enum CacheMode {
+    /// Never read or write cached results.
+    #[default]
+    Off,
+    /// Return cached results when present, and cache the results of queries that miss.
+    ReadWrite,
+    /// Return cached results when present, without caching any new results.
+    ReadOnly,
+}
+
+/// Settings for caching the results of read queries on disk.
+///
+/// Applies to [`Executor::try_fetch_one()`][crate::connection::Executor::try_fetch_one],
+/// [`Executor::try_fetch_all()`][crate::connection::Executor::try_fetch_all] and their
+/// placeholder variants. Results are keyed by the SQL text, the placeholder argument
+/// values and the database, so cached results are only valid for as long as the data
+/// they were read from does not change.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CacheSettings {
+    /// How the result cache is used.
+    pub mode: CacheMode,
+    /// Directory holding the cache files, which is required unless the cache is off.
+    pub path: Option<PathBuf>,
+}
+
+impl CacheSettings {
+    /// Creates settings for a result cache in the given directory.
+    pub fn new<P: Into<PathBuf>>(mode: CacheMode, path: P) -> Self {
+        CacheSettings {
+            mode,
+            path: Some(path.into()),
+        }
+    }
+}
+
+/// The kind of fetch a result is cached for.
+///
+/// Fetching one row keeps only the first row of a result, so its results are cached
+/// separately from fetching all rows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum CachedFetch {
+    One,
+    All,
+}
+
+/// The key of a cached result, along with the file it is stored in.
+pub(crate) struct CacheKey {
+    key: Value,
+    path: PathBuf,
+}
+
+/// A cache of query results, stored as one JSON file per result in a directory.
+pub(crate) struct ResultCache {
+    mode: CacheMode,
+    directory: PathBuf,
+    database_type: DatabaseType,
+    database: Value,
+}
+
+impl ResultCache {
+    /// Creates the result cache for the given settings, or `None` if the cache is off.
+    ///
+    /// Creates the cache directory if results are written to the cache.
+    pub fn new(settings: &ConnectionSettings) -> Result<Option<Self>, Error> {
+        let directory = match (settings.cache.mode, &settings.cache.path) {
+            (CacheMode::Off, _) => return Ok(None),
+            (_, None) => {
+                return Err(DataJointError::new_with_message(
+                    "the result cache requires a directory",
+                    ErrorCode::ConfigurationError,
+                ))
+            }
+            (_, Some(path)) => path.clone(),
+        };
+        if settings.cache.mode == CacheMode::ReadWrite {
+            if let Err(err) = std::fs::create_dir_all(&directory) {
+                return Err(DataJointError::new_with_message(
+                    &format!(
+                        "failed to create cache directory {}: {}",
+                        directory.display(),
+                        err
+                    ),
+                    ErrorCode::IoError,
+                ));
+            }
+        }
+        // The password is left out, so that it is never written to disk.
+        let database = json!([
+            settings.database_type as i32,
+            settings.username,
+            settings.hostname,
+            settings.port,
+            settings
+                .socket_path
+                .as_ref()
+                .map(|path| path.to_string_lossy()),
+            settings.database_name,
+        ]);
+        Ok(Some(ResultCache {
+            mode: settings.cache.mode,
+            directory,
+            database_type: settings.database_type,
+            database,
+        }))
+    }
+
+    /// Returns the key of the result of the given query.
+    ///
+    /// Returns `None` if the placeholder arguments of the query cannot be cached.
+    pub fn key(
+        &self,
+        fetch: CachedFetch,
+        query: &str,
+        arguments: Option<Vec<PlaceholderArgument>>,
+    ) -> Option<CacheKey> {
+        let arguments: Vec<Value> = arguments?.iter().map(ResultCache::encode_value).collect();
+        let fetch = match fetch {
+            CachedFetch::One => "one",
+            CachedFetch::All => "all",
+        };
+        let key = json!([self.database, fetch, query, arguments]);
+        let name = Hash::uuid_from_buffer(key.to_string().as_bytes());
+        Some(CacheKey {
+            key,
+            path: self.directory.join(format!("{}.json", name)),
+        })
+    }
+
+    /// Reads the cached result for the given key.
+    ///
+    /// Missing, unreadable and outdated cache files are cache misses.
+    pub fn read(&self, key: &CacheKey) -> Option<Vec<TableRow>> {
+        let contents = std::fs::read(&key.path).ok()?;
+        let file = match serde_json::from_slice::<Value>(&contents).ok()? {
+            Value::Object(file) => file,
+            _ => return None,
+        };
+        if file.get("version")?.as_u64()? != CACHE_FORMAT_VERSION || file.get("key")? != &key.key {
+            return None;
+        }
+        let database_type =
+            DatabaseType::from_int(i32::try_from(file.get("database_type")?.as_i64()?).ok()?)?;
+
+        let mut columns = Vec::new();
+        for (ordinal, column) in file.get("columns")?.as_array()?.iter().enumerate() {
+            columns.push(TableColumn {
+                database_type,
+                ordinal,
+                name: column.get("name")?.as_str()?.to_string(),
+                type_name: IntegerEnum::from_int(
+                    i32::try_from(column.get("type")?.as_i64()?).ok()?,
+                )?,
+                nullable: None,
+            });
+        }
+        let columns: Arc<[TableColumn]> = columns.into();
+
+        let mut rows = Vec::new();
+        for row in file.get("rows")?.as_array()? {
+            let row = row.as_array()?;
+            if row.len() != columns.len() {
+                return None;
+            }
+            let values = row
+                .iter()
+                .map(ResultCache::decode_value)
+                .collect::<Option<Vec<NativeType>>>()?;
+            rows.push(TableRow::Owned(OwnedRow::new(
+                database_type,
+                columns.clone(),
+                values,
+            )));
+        }
+        Some(rows)
+    }
+
+    /// Caches the result for the given key, if results are written to the cache.
+    ///
+    /// Results with values that cannot be decoded to native types are not cached. Failing
+    /// to write the cache file does not fail the query, which already has its result.
+    pub fn write(&self, key: &CacheKey, rows: &[TableRow]) {
+        if self.mode != CacheMode::ReadWrite {
+            return;
+        }
+        if let Some(file) = self.encode(key, rows) {
+            let _ = ResultCache::write_file(&key.path, file.to_string().as_bytes());
+        }
+    }
+
+    /// Encodes a result as the contents of a cache file.
+    fn encode(&self, key: &CacheKey, rows: &[TableRow]) -> Option<Value> {
+        let columns = match rows.first() {
+            None => Vec::new(),
+            Some(row) => row.columns(),
+        };
+        let mut encoded_rows = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut values = Vec::with_capacity(columns.len());
+            for column in row.columns() {
+                values.push(match row.try_decode_optional(column).ok()? {
+                    None => Value::Null,
+                    Some(value) => ResultCache::encode_value(&value),
+                });
+            }
+            encoded_rows.push(Value::Array(values));
+        }
+
+        let mut file = Map::new();
+        file.insert("version".to_string(), json!(CACHE_FORMAT_VERSION));
+        file.insert("key".to_string(), key.key.clone());
+        file.insert(
+            "database_type".to_string(),
+            json!(rows
+                .first()
+                .map_or(self.database_type, |row| row.database_type()) as i32),
+        );
+        file.insert(
+            "columns".to_string(),
+            Value::Array(
+                columns
+                    .iter()
+                    .map(|column| json!({"name": column.name(), "type": column.type_name() as i32}))
+                    .collect(),
+            ),
+        );
+        file.insert("rows".to_string(), Value::Array(encoded_rows));
+        Some(Value::Object(file))
+    }
+
+    /// Writes a cache file through a temporary file, so that readers never see a partially
+    /// written file.
+    fn write_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        let temp_path = path.with_extension(format!(
+            "{}.{}.tmp",
+            std::process::id(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&temp_path, contents)?;
+        let result = std::fs::rename(&temp_path, path);
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    /// Encodes a native value as a pair of its type name and its value.
+    ///
+    /// Floats are encoded as strings so that infinite and NaN values survive, and bytes are
+    /// encoded as hexadecimal strings.
+    fn encode_value(value: &NativeType) -> Value {
+        let type_name = value.type_name();
+        match value {
+            NativeType::None => Value::Null,
+            NativeType::Bool(val) => json!([type_name, val]),
+            NativeType::Int8(val) => json!([type_name, val]),
+            NativeType::UInt8(val) => json!([type_name, val]),
+            NativeType::Int16(val) => json!([type_name, val]),
+            NativeType::UInt16(val) => json!([type_name, val]),
+            NativeType::Int32(val) => json!([type_name, val]),
+            NativeType::UInt32(val) => json!([type_name, val]),
+            NativeType::Int64(val) => json!([type_name, val]),
+            NativeType::UInt64(val) => json!([type_name, val]),
+            NativeType::String(val) => json!([type_name, val]),
+            NativeType::Float32(val) => json!([type_name, val.to_string()]),
+            NativeType::Float64(val) => json!([type_name, val.to_string()]),
+            NativeType::Bytes(val) => json!([type_name, hex::encode(val)]),
+        }
+    }
+
+    /// Decodes a native value encoded by [`encode_value()`][ResultCache::encode_value].
+    fn decode_value(value: &Value) -> Option<NativeType> {
+        let (type_name, value) = match value {
+            Value::Null => return Some(NativeType::None),
+            Value::Array(pair) if pair.len() == 2 => (pair[0].as_str()?, &pair[1]),
+            _ => return None,
+        };
+        Some(match type_name {
+            "bool" => NativeType::Bool(value.as_bool()?),
+            "int8" => NativeType::Int8(i8::try_from(value.as_i64()?).ok()?),
+            "uint8" => NativeType::UInt8(u8::try_from(value.as_u64()?).ok()?),
+            "int16" => NativeType::Int16(i16::try_from(value.as_i64()?).ok()?),
+            "uint16" => NativeType::UInt16(u16::try_from(value.as_u64()?).ok()?),
+            "int32" => NativeType::Int32(i32::try_from(value.as_i64()?).ok()?),
+            "uint32" => NativeType::UInt32(u32::try_from(value.as_u64()?).ok()?),
+            "int64" => NativeType::Int64(value.as_i64()?),
+            "uint64" => NativeType::UInt64(value.as_u64()?),
+            "string" => NativeType::String(value.as_str()?.to_string()),
+            "float32" => NativeType::Float32(value.as_str()?.parse().ok()?),
+            "float64" => NativeType::Float64(value.as_str()?.parse().ok()?),
+            "bytes" => NativeType::Bytes(hex::decode(value.as_str()?).ok()?),
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_and_decode_values() {
+        let values = vec![
+            NativeType::None,
+            NativeType::Bool(true),
+            NativeType::Int8(-8),
+            NativeType::UInt8(8),
+            NativeType::Int16(-16),
+            NativeType::UInt16(16),
+            NativeType::Int32(-32),
+            NativeType::UInt32(32),
+            NativeType::Int64(i64::MIN),
+            NativeType::UInt64(u64::MAX),
+            NativeType::String("Hello, \"world\"".to_string()),
+            NativeType::Float32(1.5),
+            NativeType::Float64(f64::INFINITY),
+            NativeType::Bytes(vec![0, 1, 254, 255]),
+        ];
+        for value in values {
+            let encoded = ResultCache::encode_value(&value);
+            assert_eq!(ResultCache::decode_value(&encoded), Some(value));
+        }
+        assert_eq!(ResultCache::decode_value(&json!(["int8", 1000])), None);
+        assert_eq!(ResultCache::decode_value(&json!(["unknown", 1])), None);
+    }
+
+    #[test]
+    fn test_cache_keys() {
+        let mut settings = ConnectionSettings::new();
+        settings.database_type = DatabaseType::Sqlite;
+        settings.database_name = "results.db".to_string();
+        settings.password = "secret".to_string();
+        settings.cache = CacheSettings::new(CacheMode::ReadOnly, "/nonexistent/cache");
+        let cache = ResultCache::new(&settings).unwrap().unwrap();
+
+        let key = cache
+            .key(
+                CachedFetch::All,
+                "select ?",
+                Some(vec![NativeType::Int32(1)]),
+            )
+            .unwrap();
+        assert!(key.path.starts_with("/nonexistent/cache"));
+        assert!(!key.key.to_string().contains("secret"));
+        let same = cache
+            .key(
+                CachedFetch::All,
+                "select ?",
+                Some(vec![NativeType::Int32(1)]),
+            )
+            .unwrap();
+        assert_eq!(key.path, same.path);
+
+        let other_keys = [
+            cache.key(
+                CachedFetch::One,
+                "select ?",
+                Some(vec![NativeType::Int32(1)]),
+            ),
+            cache.key(
+                CachedFetch::All,
+                "select ?",
+                Some(vec![NativeType::Int32(2)]),
+            ),
+            cache.key(
+                CachedFetch::All,
+                "select ?",
+                Some(vec![NativeType::Int64(1)]),
+            ),
+            cache.key(
+                CachedFetch::All,
+                "select  ?",
+                Some(vec![NativeType::Int32(1)]),
+            ),
+        ];
+        for other in other_keys.iter() {
+            assert_ne!(other.as_ref().unwrap().path, key.path);
+        }
+        assert!(cache.key(CachedFetch::All, "select ?", None).is_none());
+
+        settings.database_name = "other.db".to_string();
+        let other_cache = ResultCache::new(&settings).unwrap().unwrap();
+        let other = other_cache
+            .key(
+                CachedFetch::All,
+                "select ?",
+                Some(vec![NativeType::Int32(1)]),
+            )
+            .unwrap();
+        assert_ne!(other.path, key.path);
+
+        settings.cache.path = None;
+        assert_eq!(
+            ResultCache::new(&settings).err().unwrap().code(),
+            ErrorCode::ConfigurationError
+        );
+        settings.cache.mode = CacheMode::Off;
+        assert!(ResultCache::new(&settings).unwrap().is_none());
+    }
+}
@@ -0,0 +1,149 @@
+use crate::error::{DataJointError, Error, ErrorCode};
+use futures::future::BoxFuture;
+use std::future::{pending, Future};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+struct CancelState {
+    // Incremented by every call to `cancel()`, so that a statement can tell whether it
+    // was cancelled after it started.
+    generation: AtomicU64,
+    notify: Notify,
+}
+
+/// A handle for cancelling running statements, possibly from another thread.
+///
+/// Clones of a handle share the same state, so cancelling any clone cancels every
+/// statement running under the handle. Statements started after a call to
+/// [`cancel()`][CancelHandle::cancel] are unaffected by it.
+///
+/// A cancelled MySQL or Postgres statement is stopped on the server with `KILL QUERY` or
+/// `pg_cancel_backend()`, and fails with [`ErrorCode::QueryCancelled`].
+///
+/// Interrupting a SQLite statement, by cancelling it or by a timeout, only detaches the
+/// caller from it and does not stop it. The call fails right away, but the statement keeps
+/// running to completion in the background, so its changes may still be written, and its
+/// connection serves no other statement until it finishes.
+#[derive(Clone)]
+pub struct CancelHandle {
+    state: Arc<CancelState>,
+}
+
+impl Default for CancelHandle {
+    fn default() -> Self {
+        CancelHandle::new()
+    }
+}
+
+impl CancelHandle {
+    /// Creates a new cancel handle.
+    pub fn new() -> Self {
+        CancelHandle {
+            state: Arc::new(CancelState {
+                generation: AtomicU64::new(0),
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    /// Cancels every statement currently running under this handle.
+    pub fn cancel(&self) {
+        self.state.generation.fetch_add(1, Ordering::SeqCst);
+        self.state.notify.notify_waiters();
+    }
+
+    /// Returns a future that resolves once the handle is cancelled after this call.
+    pub(crate) fn cancelled(&self) -> impl Future<Output = ()> + Send + 'static {
+        let state = self.state.clone();
+        let generation = state.generation.load(Ordering::SeqCst);
+        async move {
+            loop {
+                // Register for the notification before checking the generation, so that
+                // a cancellation between the two is not missed.
+                let notified = state.notify.notified();
+                if state.generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                notified.await;
+            }
+        }
+    }
+}
+
+/// Limits placed on running statements by an executor.
+#[derive(Clone, Default)]
+pub(crate) struct StatementLimits {
+    pub timeout: Option<Duration>,
+    pub cancel: Option<CancelHandle>,
+}
+
+impl StatementLimits {
+    /// Checks if statements run without a timeout or cancel handle.
+    pub fn is_unlimited(&self) -> bool {
+        self.timeout.is_none() && self.cancel.is_none()
+    }
+
+    /// Returns a future that resolves once the cancel handle is cancelled, or never if
+    /// there is no cancel handle.
+    pub fn cancelled(&self) -> BoxFuture<'static, ()> {
+        match &self.cancel {
+            None => Box::pin(pending()),
+            Some(cancel) => Box::pin(cancel.cancelled()),
+        }
+    }
+
+    /// Returns a future that resolves with the error to report once a statement starting
+    /// now must be interrupted.
+    pub fn interrupted(&self) -> BoxFuture<'static, Error> {
+        let timeout = self.timeout;
+        let cancelled = self.cancelled();
+        Box::pin(async move {
+            let timed_out = async move {
+                match timeout {
+                    None => pending().await,
+                    Some(timeout) => tokio::time::sleep(timeout).await,
+                }
+            };
+            tokio::select! {
+                _ = timed_out => DataJointError::new(ErrorCode::QueryTimedOut) as Error,
+                _ = cancelled => DataJointError::new(ErrorCode::QueryCancelled),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+
+    #[tokio::test]
+    async fn test_cancel_handle() {
+        let handle = CancelHandle::new();
+
+        // Cancellations before a statement starts do not affect it.
+        handle.cancel();
+        let mut cancelled = Box::pin(handle.cancelled());
+        assert!((&mut cancelled).now_or_never().is_none());
+
+        handle.clone().cancel();
+        assert!(cancelled.now_or_never().is_some());
+
+        let limits = StatementLimits {
+            timeout: Some(Duration::from_millis(10)),
+            cancel: None,
+        };
+        assert_eq!(limits.interrupted().await.code(), ErrorCode::QueryTimedOut);
+
+        let limits = StatementLimits {
+            timeout: Some(Duration::from_secs(60)),
+            cancel: Some(handle.clone()),
+        };
+        let interrupted = limits.interrupted();
+        handle.cancel();
+        assert_eq!(interrupted.await.code(), ErrorCode::QueryCancelled);
+        assert!(StatementLimits::default().is_unlimited());
+    }
+}
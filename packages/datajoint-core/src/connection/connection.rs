@@ -1,10 +1,20 @@
 use crate::common::{DatabaseType, DatabaseTypeAgnostic};
-use crate::connection::Pool;
-use crate::connection::{ConnectionSettings, Cursor, Executor};
-use crate::error::{DataJointError, Error, ErrorCode, SqlxError};
+use crate::connection::runtime::shared_runtime;
+use crate::connection::{
+    AsyncExecutor, CancelHandle, ConnectionSettings, Cursor, DelimitedFormat, Executor,
+    MockDatabase, MockPool, QueryObserver, ResultCache, ServerInfo, Transaction,
+};
+use crate::connection::{Pool, SharedPool};
+use crate::error::{DataJointError, Error, ErrorCode};
 use crate::placeholders::{PlaceholderArgumentCollection, PlaceholderArgumentVector};
+use std::io::Read;
+use std::sync::{Arc, OnceLock};
 
 /// A single connection instance to an arbitrary SQL database.
+///
+/// A connection is `Send` and `Sync`, so a single connected instance can be shared by
+/// reference across threads. Queries from every thread are served concurrently by the
+/// connection pool.
 pub struct Connection {
     /// The settings for the database connection.
     ///
@@ -12,15 +22,20 @@ pub struct Connection {
     /// the client should call [`.disconnect()`][Connection::disconnect] and then connect
     /// again to use the updated settings.
     pub settings: ConnectionSettings,
-    pool: Option<Pool>,
-    runtime: tokio::runtime::Runtime,
+    pool: Option<SharedPool>,
+    cancel: OnceLock<CancelHandle>,
+    observer: Option<Arc<dyn QueryObserver>>,
+    cache: Option<ResultCache>,
+    // The mock database the connection runs its statements over, if any.
+    mock: Option<MockDatabase>,
+    runtime: &'static tokio::runtime::Runtime,
 }
 
 impl DatabaseTypeAgnostic for Connection {
     fn database_type(&self) -> DatabaseType {
         match &self.pool {
             None => self.settings.database_type,
-            Some(pool) => pool.database_type(),
+            Some(pool) => pool.get().database_type(),
         }
     }
 }
@@ -34,22 +49,38 @@ impl Connection {
         Connection {
             settings,
             pool: None,
-            runtime: tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .ok()
-                .unwrap(),
+            cancel: OnceLock::new(),
+            observer: None,
+            cache: None,
+            mock: None,
+            runtime: shared_runtime(),
         }
     }
 
+    /// Creates a new connection that runs its statements over a mock database rather
+    /// than a database server, for testing code that runs queries.
+    ///
+    /// The database type of the settings is set to the type emulated by the mock database.
+    /// The connection is not usable until [`.connect()`][Connection::connect] is called, which
+    /// never fails for a mock database unless the result cache cannot be opened.
+    pub fn new_mock(database: MockDatabase) -> Self {
+        let mut settings = ConnectionSettings::new();
+        settings.database_type = database.database_type();
+        let mut connection = Connection::new(settings);
+        connection.mock = Some(database);
+        connection
+    }
+
     /// Starts the connection to the SQL database according to the settings the connection
     /// was initialized with.
     pub fn connect(&mut self) -> Result<(), Error> {
-        self.pool = Some(Connection::get_pool(
-            &self.runtime,
-            self.settings.database_type,
-            &*self.settings.uri(),
-        )?);
+        let cache = ResultCache::new(&self.settings)?;
+        let pool = match &self.mock {
+            None => self.runtime.block_on(Pool::connect(&self.settings))?,
+            Some(database) => Pool::Mock(MockPool::new(database.clone())),
+        };
+        self.pool = Some(SharedPool::new(pool, &self.settings));
+        self.cache = cache;
         return Ok(());
     }
 
@@ -57,11 +88,11 @@ impl Connection {
         DataJointError::new(ErrorCode::NotConnected)
     }
 
-    fn get_connected_pool(&self) -> Result<&Pool, Error> {
+    fn get_connected_pool(&self) -> Result<&SharedPool, Error> {
         match &self.pool {
             None => Err(Connection::not_connected_error()),
             Some(pool) => {
-                if pool.is_closed() {
+                if pool.get().is_closed() {
                     Err(Connection::not_connected_error())
                 } else {
                     Ok(pool)
@@ -84,47 +115,19 @@ impl Connection {
     /// is a no-op.
     ///
     /// The connection can be restarted if desired.
+    /// Cursors still reading rows release their connections, and fail with
+    /// [`ErrorCode::NotConnected`] when they are read next.
+    /// Executors created before disconnecting that have a
+    /// [`ReconnectPolicy`][crate::connection::ReconnectPolicy] connect a new pool when they
+    /// run their next statement.
     pub fn disconnect(&self) {
         if let Some(pool) = &self.pool {
-            if !pool.is_closed() {
+            if !pool.get().is_closed() {
                 self.runtime.block_on(pool.close());
             }
         }
     }
 
-    fn get_pool(
-        runtime: &tokio::runtime::Runtime,
-        database_type: DatabaseType,
-        uri: &str,
-    ) -> Result<Pool, Error> {
-        runtime.block_on(Connection::get_pool_async(database_type, uri))
-    }
-
-    async fn get_pool_async(database_type: DatabaseType, uri: &str) -> Result<Pool, Error> {
-        match database_type {
-            DatabaseType::MySql => {
-                match sqlx::mysql::MySqlPoolOptions::new()
-                    .max_connections(1)
-                    .connect(uri)
-                    .await
-                {
-                    Err(err) => Err(SqlxError::new(err)),
-                    Ok(pool) => Ok(Pool::MySql(pool)),
-                }
-            }
-            DatabaseType::Postgres => {
-                match sqlx::postgres::PgPoolOptions::new()
-                    .max_connections(1)
-                    .connect(uri)
-                    .await
-                {
-                    Err(err) => Err(SqlxError::new(err)),
-                    Ok(pool) => Ok(Pool::Postgres(pool)),
-                }
-            }
-        }
-    }
-
     /// Creates an executor to interact with the database over this connection.
     ///
     /// Panics on error.
@@ -134,7 +137,74 @@ impl Connection {
 
     /// Creates an executor to interact with the database over this connection.
     pub fn try_executor<'c>(&'c self) -> Result<Executor<'c>, Error> {
-        Ok(Executor::new(self.get_connected_pool()?, &self.runtime))
+        let executor = AsyncExecutor::new(
+            self.get_connected_pool()?,
+            self.settings.reconnect,
+            self.settings.statement_timeout,
+            self.cancel.get().cloned(),
+            self.observer.clone(),
+            self.cache.as_ref(),
+        );
+        Ok(Executor::new(executor, self.runtime))
+    }
+
+    /// Sets the observer that is notified of every statement run over this connection,
+    /// including statements in transactions and cursors.
+    ///
+    /// `None` removes the current observer. Executors and transactions that already exist
+    /// keep the observer they were created with.
+    pub fn set_query_observer(&mut self, observer: Option<Arc<dyn QueryObserver>>) {
+        self.observer = observer;
+    }
+
+    /// Gets the handle for cancelling statements running over this connection, which may
+    /// be used from any thread.
+    ///
+    /// Once the handle has been requested, every executor, cursor and query method of the
+    /// connection runs its statements under it, so that
+    /// [`cancel()`][CancelHandle::cancel] interrupts them all. Statements in transactions
+    /// are not cancelled.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.get_or_init(CancelHandle::new).clone()
+    }
+
+    /// Begins a transaction, which runs all of its queries on a single connection.
+    ///
+    /// Panics on error.
+    pub fn begin(&self) -> Transaction<'_> {
+        self.try_begin().unwrap()
+    }
+
+    /// Begins a transaction, which runs all of its queries on a single connection.
+    pub fn try_begin(&self) -> Result<Transaction<'_>, Error> {
+        let pool = self.get_connected_pool()?;
+        Ok(Transaction::new(
+            self.runtime.block_on(pool.get().begin())?,
+            self.runtime,
+            self.observer.clone(),
+        ))
+    }
+
+    /// Checks that the database server can still be reached.
+    ///
+    /// Unlike [`is_connected()`][Connection::is_connected], this makes a round trip to the
+    /// server, so it detects sessions that were dropped by the server or the network.
+    pub fn ping(&self) -> Result<(), Error> {
+        self.try_executor()?.ping()
+    }
+
+    /// Fetches the database type, version, current user and current database of the
+    /// database server.
+    ///
+    /// Panics on error.
+    pub fn server_info(&self) -> ServerInfo {
+        self.try_server_info().unwrap()
+    }
+
+    /// Fetches the database type, version, current user and current database of the
+    /// database server.
+    pub fn try_server_info(&self) -> Result<ServerInfo, Error> {
+        self.try_executor()?.try_server_info()
     }
 
     /// Executes the given non-returning query, returning the number of rows affected.
@@ -155,7 +225,7 @@ impl Connection {
 
     /// Executes the given non-returning query, returning the number of rows affected.
     pub fn try_execute_query(&self, query: &str) -> Result<u64, Error> {
-        Ok(self.try_executor()?.try_execute(query)?)
+        Ok(self.try_executor()?.try_execute(query)?.rows_affected)
     }
 
     /// Executes the given non-returning query, returning the number of rows affected.
@@ -166,13 +236,72 @@ impl Connection {
         query: &str,
         args: impl PlaceholderArgumentCollection,
     ) -> Result<u64, Error> {
-        Ok(self.try_executor()?.try_execute_ph(query, args)?)
+        Ok(self
+            .try_executor()?
+            .try_execute_ph(query, args)?
+            .rows_affected)
+    }
+
+    /// Loads rows of values into the given columns of a table, returning the number of
+    /// rows loaded.
+    ///
+    /// Panics on error.
+    pub fn bulk_load(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: Vec<PlaceholderArgumentVector>,
+    ) -> u64 {
+        self.try_bulk_load(table, columns, rows).unwrap()
+    }
+
+    /// Loads rows of values into the given columns of a table, returning the number of
+    /// rows loaded.
+    ///
+    /// Postgres streams the rows through `COPY ... FROM STDIN`, which is much faster than
+    /// inserts. MySQL and SQLite insert them with batched inserts, since the MySQL driver
+    /// does not support `LOAD DATA LOCAL INFILE`.
+    pub fn try_bulk_load(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: Vec<PlaceholderArgumentVector>,
+    ) -> Result<u64, Error> {
+        self.try_executor()?.try_bulk_load(table, columns, rows)
+    }
+
+    /// Loads delimited text into the given columns of a table, returning the number of
+    /// rows loaded.
+    ///
+    /// Panics on error.
+    pub fn bulk_load_reader(
+        &self,
+        table: &str,
+        columns: &[&str],
+        reader: impl Read,
+        format: DelimitedFormat,
+    ) -> u64 {
+        self.try_bulk_load_reader(table, columns, reader, format)
+            .unwrap()
+    }
+
+    /// Loads delimited text into the given columns of a table, returning the number of
+    /// rows loaded.
+    pub fn try_bulk_load_reader(
+        &self,
+        table: &str,
+        columns: &[&str],
+        reader: impl Read,
+        format: DelimitedFormat,
+    ) -> Result<u64, Error> {
+        self.try_executor()?
+            .try_bulk_load_reader(table, columns, reader, format)
     }
 
     /// Creates a cursor for iterating over the results of the given returning query.
     ///
     /// Panics on error.
-    pub fn fetch_query<'c>(&'c self, query: &str) -> Cursor<'c> {
+    pub fn fetch_query(&self, query: &str) -> Cursor<'static> {
         self.try_fetch_query(query).unwrap()
     }
 
@@ -181,31 +310,27 @@ impl Connection {
     /// Uses placeholder arguments, binding them to the query prior to execution.
     ///
     /// Panics on error.
-    pub fn fetch_query_ph<'c>(
-        &'c self,
-        query: &'c str,
+    pub fn fetch_query_ph(
+        &self,
+        query: &str,
         args: impl PlaceholderArgumentCollection,
-    ) -> Cursor {
+    ) -> Cursor<'static> {
         self.try_fetch_query_ph(query, args).unwrap()
     }
 
     /// Creates a cursor for iterating over the results of the given returning query.
-    pub fn try_fetch_query<'c>(&'c self, query: &str) -> Result<Cursor<'c>, Error> {
-        Cursor::new_from_executor(
-            query,
-            self.try_executor()?,
-            None as Option<PlaceholderArgumentVector>,
-        )
+    pub fn try_fetch_query(&self, query: &str) -> Result<Cursor<'static>, Error> {
+        self.try_executor()?.cursor(query)
     }
 
     /// Creates a cursor for iterating over the results of the given returning query.
     ///
     /// Uses placeholder arguments, binding them to the query prior to execution.
-    pub fn try_fetch_query_ph<'c>(
-        &'c self,
-        query: &'c str,
+    pub fn try_fetch_query_ph(
+        &self,
+        query: &str,
         args: impl PlaceholderArgumentCollection,
-    ) -> Result<Cursor, Error> {
-        Cursor::new_from_executor(query, self.try_executor()?, Some(args))
+    ) -> Result<Cursor<'static>, Error> {
+        self.try_executor()?.cursor_ph(query, args)
     }
 }
@@ -0,0 +1,305 @@
+use crate::connection::ConnectionSettings;
+use crate::error::{DataJointError, Error, ErrorCode};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A function that returns the password to log in with, given the settings of the
+/// connection that is connecting.
+pub type CredentialCallback =
+    Arc<dyn Fn(&ConnectionSettings) -> Result<String, Error> + Send + Sync>;
+
+/// The source of the password used to log in to the database server.
+///
+/// Credentials are resolved once per connection pool, when the connection connects, rather
+/// than when the settings are created. Every physical connection the pool opens logs in
+/// with that password. Once the server rejects the login of a new physical connection, a
+/// connection with a [`ReconnectPolicy`][crate::connection::ReconnectPolicy] replaces its
+/// pool, resolving the credentials again, so a callback can hand out short-lived tokens,
+/// such as AWS RDS IAM authentication tokens. Without a policy, tokens are refreshed by
+/// disconnecting and connecting again.
+#[derive(Clone, Default)]
+pub enum CredentialProvider {
+    /// Uses the [`password`][ConnectionSettings::password] of the settings.
+    #[default]
+    Static,
+    /// Reads the password from a file, ignoring a trailing newline.
+    PasswordFile(PathBuf),
+    /// Looks up the password in a Postgres password file, by the hostname, port, database
+    /// name and username of the settings.
+    ///
+    /// `None` reads the file named by the `PGPASSFILE` environment variable, or
+    /// `~/.pgpass`. No password is used if no entry matches.
+    PgPass(Option<PathBuf>),
+    /// Reads the password of the `[client]` group of a MySQL option file.
+    ///
+    /// `None` reads `~/.my.cnf`. No password is used if the group has no password.
+    MyCnf(Option<PathBuf>),
+    /// Calls a function for the password.
+    Callback(CredentialCallback),
+}
+
+impl fmt::Debug for CredentialProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Static => write!(f, "Static"),
+            Self::PasswordFile(path) => f.debug_tuple("PasswordFile").field(path).finish(),
+            Self::PgPass(path) => f.debug_tuple("PgPass").field(path).finish(),
+            Self::MyCnf(path) => f.debug_tuple("MyCnf").field(path).finish(),
+            Self::Callback(_) => write!(f, "Callback(..)"),
+        }
+    }
+}
+
+impl CredentialProvider {
+    /// Creates a provider that calls the given function for the password.
+    pub fn callback<F>(callback: F) -> Self
+    where
+        F: Fn(&ConnectionSettings) -> Result<String, Error> + Send + Sync + 'static,
+    {
+        Self::Callback(Arc::new(callback))
+    }
+
+    /// Resolves the password to log in with for the given settings.
+    pub fn resolve(&self, settings: &ConnectionSettings) -> Result<String, Error> {
+        match self {
+            Self::Static => Ok(settings.password.clone()),
+            Self::PasswordFile(path) => {
+                let contents = CredentialProvider::read_file(path)?;
+                let password = contents.strip_suffix('\n').unwrap_or(&contents);
+                Ok(password.strip_suffix('\r').unwrap_or(password).to_string())
+            }
+            Self::PgPass(path) => {
+                let contents = match path {
+                    Some(path) => CredentialProvider::read_file(path)?,
+                    None => match CredentialProvider::default_pgpass_path() {
+                        Some(path) if path.is_file() => CredentialProvider::read_file(&path)?,
+                        _ => return Ok(String::new()),
+                    },
+                };
+                Ok(CredentialProvider::pgpass_password(&contents, settings).unwrap_or_default())
+            }
+            Self::MyCnf(path) => {
+                let contents = match path {
+                    Some(path) => CredentialProvider::read_file(path)?,
+                    None => match CredentialProvider::home_dir() {
+                        Some(home) if home.join(".my.cnf").is_file() => {
+                            CredentialProvider::read_file(&home.join(".my.cnf"))?
+                        }
+                        _ => return Ok(String::new()),
+                    },
+                };
+                Ok(CredentialProvider::mycnf_password(&contents).unwrap_or_default())
+            }
+            Self::Callback(callback) => callback(settings),
+        }
+    }
+
+    /// Checks if the provider may supply a password without resolving it.
+    pub(crate) fn has_password(&self, settings: &ConnectionSettings) -> bool {
+        match self {
+            Self::Static => !settings.password.trim().is_empty(),
+            _ => true,
+        }
+    }
+
+    fn home_dir() -> Option<PathBuf> {
+        std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(PathBuf::from)
+    }
+
+    fn default_pgpass_path() -> Option<PathBuf> {
+        match std::env::var_os("PGPASSFILE") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => CredentialProvider::home_dir().map(|home| home.join(".pgpass")),
+        }
+    }
+
+    /// Reads a credential file.
+    ///
+    /// Errors name the file but never include its contents.
+    fn read_file(path: &Path) -> Result<String, Error> {
+        match std::fs::read_to_string(path) {
+            Err(err) => Err(DataJointError::new_with_message(
+                &format!("failed to read credential file {}: {}", path.display(), err),
+                ErrorCode::ConfigurationError,
+            )),
+            Ok(contents) => Ok(contents),
+        }
+    }
+
+    /// Splits a line of a Postgres password file into its fields, unescaping `\:` and
+    /// `\\`.
+    fn pgpass_fields(line: &str) -> Vec<String> {
+        let mut fields = vec![String::new()];
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        fields.last_mut().unwrap().push(escaped);
+                    }
+                }
+                ':' => fields.push(String::new()),
+                c => fields.last_mut().unwrap().push(c),
+            }
+        }
+        fields
+    }
+
+    /// Finds the password of the first entry of a Postgres password file that matches
+    /// the settings.
+    ///
+    /// Connections through a socket match the `localhost` hostname, like libpq.
+    fn pgpass_password(contents: &str, settings: &ConnectionSettings) -> Option<String> {
+        let hostname = match settings.socket_path {
+            Some(_) => "localhost",
+            None => &settings.hostname,
+        };
+        let port = settings.port.to_string();
+        let wanted = [hostname, &port, &settings.database_name, &settings.username];
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(CredentialProvider::pgpass_fields)
+            .filter(|fields| fields.len() == 5)
+            .find(|fields| {
+                fields[..4]
+                    .iter()
+                    .zip(wanted.iter())
+                    .all(|(field, wanted)| field == "*" || field == wanted)
+            })
+            .map(|mut fields| fields.pop().unwrap())
+    }
+
+    /// Finds the password of the `[client]` group of a MySQL option file.
+    ///
+    /// Later options override earlier ones, and quotes around the value are removed.
+    fn mycnf_password(contents: &str) -> Option<String> {
+        let mut in_client = false;
+        let mut password = None;
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                in_client = line[1..line.len() - 1].trim() == "client";
+                continue;
+            }
+            if !in_client {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "password" {
+                    let value = value.trim();
+                    let unquoted = ['"', '\''].iter().find_map(|quote| {
+                        value
+                            .strip_prefix(*quote)
+                            .and_then(|value| value.strip_suffix(*quote))
+                    });
+                    password = Some(unquoted.unwrap_or(value).to_string());
+                }
+            }
+        }
+        password
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pgpass_password() {
+        let contents = "\
+# hostname:port:database:username:password
+db.example.com:5432:lab:alice:first
+*:5432:*:alice:second\\:with\\\\escapes
+localhost:*:*:*:third
+";
+        let mut settings = ConnectionSettings::new();
+        settings.hostname = "db.example.com".to_string();
+        settings.port = 5432;
+        settings.database_name = "lab".to_string();
+        settings.username = "alice".to_string();
+        assert_eq!(
+            CredentialProvider::pgpass_password(contents, &settings),
+            Some("first".to_string())
+        );
+
+        settings.database_name = "other".to_string();
+        assert_eq!(
+            CredentialProvider::pgpass_password(contents, &settings),
+            Some("second:with\\escapes".to_string())
+        );
+
+        settings.username = "bob".to_string();
+        assert_eq!(
+            CredentialProvider::pgpass_password(contents, &settings),
+            None
+        );
+
+        settings.socket_path = Some(PathBuf::from("/var/run/postgresql"));
+        assert_eq!(
+            CredentialProvider::pgpass_password(contents, &settings),
+            Some("third".to_string())
+        );
+    }
+
+    #[test]
+    fn test_mycnf_password() {
+        let contents = "\
+[mysqld]
+password = server
+
+[client]
+user = alice
+password = \"first\"
+# password = commented
+password = 'second'
+
+[mysql]
+password = other
+";
+        assert_eq!(
+            CredentialProvider::mycnf_password(contents),
+            Some("second".to_string())
+        );
+        assert_eq!(
+            CredentialProvider::mycnf_password("[client]\nuser = alice\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_credentials() {
+        let mut settings = ConnectionSettings::new();
+        settings.password = "static".to_string();
+        assert_eq!(
+            CredentialProvider::Static.resolve(&settings).unwrap(),
+            "static"
+        );
+
+        let path = std::env::temp_dir().join("datajoint_core_test_password_file");
+        std::fs::write(&path, "from file\r\n").unwrap();
+        assert_eq!(
+            CredentialProvider::PasswordFile(path.clone())
+                .resolve(&settings)
+                .unwrap(),
+            "from file"
+        );
+        std::fs::remove_file(&path).unwrap();
+        match CredentialProvider::PasswordFile(path).resolve(&settings) {
+            Ok(_) => panic!("Missing password file did not fail."),
+            Err(err) => assert_eq!(err.code(), ErrorCode::ConfigurationError),
+        }
+
+        let provider = CredentialProvider::callback(|settings: &ConnectionSettings| {
+            Ok(format!("token for {}", settings.hostname))
+        });
+        assert_eq!(provider.resolve(&settings).unwrap(), "token for localhost");
+        assert_eq!(format!("{:?}", provider), "Callback(..)");
+    }
+}
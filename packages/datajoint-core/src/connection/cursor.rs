@@ -1,179 +1,71 @@
 use crate::common::{DatabaseType, DatabaseTypeAgnostic};
-use crate::connection::{Executor, Pool};
-use crate::error::{DataJointError, Error, ErrorCode, SqlxError};
-use crate::placeholders::PlaceholderArgumentCollection;
-use crate::query::Query;
-use crate::results::TableRow;
+use crate::connection::AsyncCursor;
+use crate::error::{DataJointError, Error, ErrorCode};
+use crate::results::{TableColumn, TableRow};
 use futures::stream::StreamExt;
-use futures_core::stream::BoxStream;
-use std::pin::Pin;
-use std::ptr::NonNull;
-
-/// A wrapper around a stream of rows from SQLx, which basically represents a cursor.
-enum SqlxCursor<'c> {
-    MySql(BoxStream<'c, Result<sqlx::mysql::MySqlRow, sqlx::Error>>),
-    Postgres(BoxStream<'c, Result<sqlx::postgres::PgRow, sqlx::Error>>),
-}
 
 /// An object used to iterate over a set of rows.
+///
+/// A cursor is an [`Iterator`] of `Result<TableRow, Error>`, which ends once the rows run
+/// out.
 pub struct Cursor<'c> {
-    // The owned query string.
-    query: Pin<Box<String>>,
+    cursor: AsyncCursor<'c>,
     // The asynchronous runtime.
     runtime: &'c tokio::runtime::Runtime,
-    // The stream of rows, which references the owned query string.
-    stream: Option<SqlxCursor<'c>>,
 }
 
 impl<'c> DatabaseTypeAgnostic for Cursor<'c> {
     fn database_type(&self) -> DatabaseType {
-        match self.stream.as_ref().unwrap() {
-            SqlxCursor::MySql(_) => DatabaseType::MySql,
-            SqlxCursor::Postgres(_) => DatabaseType::Postgres,
-        }
+        self.cursor.database_type()
     }
 }
 
 impl<'c> Cursor<'c> {
-    fn wrong_database_type_error() -> Error {
-        DataJointError::new_with_message(
-            "prepared query is for the wrong database type",
-            ErrorCode::WrongDatabaseType,
-        )
+    /// Creates a new cursor that runs the given asynchronous cursor to completion.
+    pub(crate) fn new(cursor: AsyncCursor<'c>, runtime: &'c tokio::runtime::Runtime) -> Self {
+        Cursor { cursor, runtime }
     }
 
-    /// Creates a new cursor over a stream of SQLx rows.
+    /// Fetches the next row.
     ///
-    /// Consumes the input executor.
-    pub(crate) fn new_from_executor(
-        query: &str,
-        executor: Executor<'c>,
-        args: Option<impl PlaceholderArgumentCollection>,
-    ) -> Result<Cursor<'c>, Error> {
-        // self.stream needs to reference self.query in order to work properly.
-        // This is because SQLx expects its query string to live as long as the query itself,
-        // but we can't make that guarantee when using this wrapper model.
-        //
-        // Thus, we need to both own the query string and refer to it in the same struct.
-        // This is not the best for Rust, so we have to implement a work around here.
-        //
-        // We pin the query string in memory so it is guaranteed to not move so that the stream's
-        // reference to the stored query string is always valid.
-
-        // Create the cursor.
-        let mut new_cursor = Cursor {
-            query: Box::pin(query.to_string()),
-            runtime: executor.runtime,
-            stream: None,
-        };
-
-        // Create a reference to the owned string.
-        let slice = NonNull::from(&*new_cursor.query);
-
-        // We know this is safe because modifying a single field does not move the whole struct.
-        unsafe {
-            // Create the stream with the reference to the query.
-            new_cursor.stream = match executor.executor {
-                Pool::MySql(pool) => {
-                    let mut query = Query::new(DatabaseType::MySql, slice.as_ref());
-                    if let Some(args) = args {
-                        query = args.bind_to_query(query)?;
-                    }
-                    if let Query::MySql(query) = query {
-                        Some(SqlxCursor::MySql(query.fetch(pool)))
-                    } else {
-                        return Err(Cursor::wrong_database_type_error());
-                    }
-                }
-                Pool::Postgres(pool) => {
-                    let mut query = Query::new(DatabaseType::Postgres, slice.as_ref());
-                    if let Some(args) = args {
-                        query = args.bind_to_query(query)?;
-                    }
-                    if let Query::Postgres(query) = query {
-                        Some(SqlxCursor::Postgres(query.fetch(pool)))
-                    } else {
-                        return Err(Cursor::wrong_database_type_error());
-                    }
-                }
-            }
+    /// Fails with [`ErrorCode::NoMoreRows`] once the rows run out.
+    pub fn try_next(&mut self) -> Result<TableRow, Error> {
+        match self.runtime.block_on(self.cursor.next()) {
+            None => Err(DataJointError::new(ErrorCode::NoMoreRows)),
+            Some(result) => result,
         }
-
-        return Ok(new_cursor);
     }
 
-    /// Creates a new cursor over a stream of SQLx rows.
+    /// Fetches up to the given number of rows.
     ///
-    /// Keeps the executor reference simply by borrowing out of it.
-    pub(crate) fn new_from_executor_ref(
-        query: &str,
-        executor: &'c Executor,
-        args: Option<impl PlaceholderArgumentCollection>,
-    ) -> Result<Cursor<'c>, Error> {
-        // See the above function for what this is doing and why.
-
-        let mut new_cursor = Cursor {
-            query: Box::pin(query.to_string()),
-            runtime: executor.runtime,
-            stream: None,
-        };
-        let slice = NonNull::from(&*new_cursor.query);
-
-        unsafe {
-            new_cursor.stream = match executor.executor {
-                Pool::MySql(pool) => {
-                    let mut query = Query::new(DatabaseType::MySql, slice.as_ref());
-                    if let Some(args) = args {
-                        query = args.bind_to_query(query)?;
-                    }
-                    if let Query::MySql(query) = query {
-                        Some(SqlxCursor::MySql(query.fetch(pool)))
-                    } else {
-                        return Err(Cursor::wrong_database_type_error());
-                    }
-                }
-                Pool::Postgres(pool) => {
-                    let mut query = Query::new(DatabaseType::Postgres, slice.as_ref());
-                    if let Some(args) = args {
-                        query = args.bind_to_query(query)?;
-                    }
-                    if let Query::Postgres(query) = query {
-                        Some(SqlxCursor::Postgres(query.fetch(pool)))
-                    } else {
-                        return Err(Cursor::wrong_database_type_error());
-                    }
-                }
-            }
-        }
+    /// Panics on error.
+    pub fn next_batch(&mut self, size: usize) -> Vec<TableRow> {
+        self.try_next_batch(size).unwrap()
+    }
 
-        return Ok(new_cursor);
+    /// Fetches up to the given number of rows, returning fewer rows only once the rows run
+    /// out.
+    ///
+    /// Returns an empty vector if there are no more rows. A size of 0 fails with
+    /// [`ErrorCode::InvalidBatchSize`][crate::error::ErrorCode::InvalidBatchSize].
+    pub fn try_next_batch(&mut self, size: usize) -> Result<Vec<TableRow>, Error> {
+        self.runtime.block_on(self.cursor.try_next_batch(size))
     }
 
-    /// Fetches the next row.
+    /// Returns the columns of the rows of the cursor.
     ///
     /// Panics on error.
-    pub fn next(&mut self) -> TableRow {
-        self.try_next().unwrap()
+    pub fn columns(&mut self) -> Vec<TableColumn> {
+        self.try_columns().unwrap()
     }
 
-    /// Fetches the next row.
-    pub fn try_next(&mut self) -> Result<TableRow, Error> {
-        match self.stream.as_mut().unwrap() {
-            SqlxCursor::MySql(stream) => match self.runtime.block_on(stream.next()) {
-                None => Err(DataJointError::new(ErrorCode::NoMoreRows)),
-                Some(result) => match result {
-                    Err(err) => Err(SqlxError::new(err)),
-                    Ok(row) => Ok(TableRow::MySql(row)),
-                },
-            },
-            SqlxCursor::Postgres(stream) => match self.runtime.block_on(stream.next()) {
-                None => Err(DataJointError::new(ErrorCode::NoMoreRows)),
-                Some(result) => match result {
-                    Err(err) => Err(SqlxError::new(err)),
-                    Ok(row) => Ok(TableRow::Postgres(row)),
-                },
-            },
-        }
+    /// Returns the columns of the rows of the cursor, which are known even if the cursor
+    /// returns no rows.
+    ///
+    /// The columns are read from the next row without consuming it, or described by the
+    /// database if there are no more rows.
+    pub fn try_columns(&mut self) -> Result<Vec<TableColumn>, Error> {
+        self.runtime.block_on(self.cursor.try_columns())
     }
 
     /// Fetches all remaining rows.
@@ -185,15 +77,14 @@ impl<'c> Cursor<'c> {
 
     /// Fetches all remaining rows.
     pub fn try_rest(&mut self) -> Result<Vec<TableRow>, Error> {
-        let mut rows = vec![];
-        loop {
-            match self.try_next() {
-                Ok(row) => rows.push(row),
-                Err(err) if err.code() == ErrorCode::NoMoreRows => break,
-                Err(err) => return Err(err),
-            }
-        }
+        self.runtime.block_on(self.cursor.try_rest())
+    }
+}
+
+impl<'c> Iterator for Cursor<'c> {
+    type Item = Result<TableRow, Error>;
 
-        Ok(rows)
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.cursor.next())
     }
 }
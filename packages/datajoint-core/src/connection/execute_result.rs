@@ -0,0 +1,119 @@
+use crate::common::DatabaseType;
+use crate::query::top_level_words;
+
+/// The outcome of running a non-returning statement.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ExecuteResult {
+    /// The number of rows affected by the statement.
+    pub rows_affected: u64,
+    /// The id generated for a row inserted by the statement, such as the value of an
+    /// `AUTO_INCREMENT` column, if any.
+    ///
+    /// MySQL reports the id generated for the first row of a multi-row insert, and SQLite
+    /// reports the rowid of the last row. Postgres reports the first column returned for the
+    /// last row by an insert with a `RETURNING` clause, such as
+    /// `insert into tweet (text) values ('hi') returning id`, if it is an integer. Inserts
+    /// after a `WITH` clause are included.
+    pub last_insert_id: Option<i64>,
+    /// The number of warnings produced by the statement, if they were counted.
+    ///
+    /// Counting warnings takes another round-trip to the server, so they are only counted
+    /// by executors that [count warnings][crate::connection::Executor::set_count_warnings]
+    /// or treat them as errors. Only MySQL reports warnings, so this is always `None` for
+    /// other databases.
+    pub warnings: Option<u64>,
+}
+
+impl ExecuteResult {
+    /// Creates the result of a statement that only reports the rows it affected.
+    pub(crate) fn with_rows_affected(rows_affected: u64) -> Self {
+        ExecuteResult {
+            rows_affected,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the words of a statement outside of parentheses, starting at its main
+    /// keyword, so a leading `WITH` clause is skipped.
+    fn statement_words(database_type: DatabaseType, sql: &str) -> Vec<String> {
+        let words = top_level_words(database_type, sql);
+        match words.first() {
+            Some(word) if word.eq_ignore_ascii_case("with") => {
+                let main = words.iter().position(|word| {
+                    ["insert", "replace", "select", "update", "delete", "values"]
+                        .iter()
+                        .any(|keyword| word.eq_ignore_ascii_case(keyword))
+                });
+                match main {
+                    None => Vec::new(),
+                    Some(main) => words[main..].to_vec(),
+                }
+            }
+            _ => words,
+        }
+    }
+
+    /// Checks if a statement inserts rows, so the id of an inserted row is reported.
+    pub(crate) fn is_insert(database_type: DatabaseType, sql: &str) -> bool {
+        match ExecuteResult::statement_words(database_type, sql).first() {
+            None => false,
+            Some(word) => {
+                word.eq_ignore_ascii_case("insert") || word.eq_ignore_ascii_case("replace")
+            }
+        }
+    }
+
+    /// Checks if a Postgres statement inserts rows and returns values of them with a
+    /// `RETURNING` clause, such as `with ... insert into ... returning id`.
+    pub(crate) fn is_insert_returning(sql: &str) -> bool {
+        let words = ExecuteResult::statement_words(DatabaseType::Postgres, sql);
+        match words.first() {
+            Some(word) if word.eq_ignore_ascii_case("insert") => words
+                .iter()
+                .any(|word| word.eq_ignore_ascii_case("returning")),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_statements() {
+        assert!(ExecuteResult::is_insert(
+            DatabaseType::Sqlite,
+            "-- Add a tweet.\nINSERT INTO tweet (text) VALUES ('hi')"
+        ));
+        assert!(ExecuteResult::is_insert(
+            DatabaseType::MySql,
+            "replace into tweet values (1, 'hi')"
+        ));
+        assert!(!ExecuteResult::is_insert(
+            DatabaseType::Sqlite,
+            "update tweet set text = 'insert'"
+        ));
+        assert!(ExecuteResult::is_insert_returning(
+            "insert into tweet (text) values ($1) returning id"
+        ));
+        assert!(!ExecuteResult::is_insert_returning(
+            "insert into tweet (text) values ('returning')"
+        ));
+        assert!(!ExecuteResult::is_insert_returning(
+            "delete from tweet returning id"
+        ));
+        assert!(ExecuteResult::is_insert_returning(
+            "with owner as (select $1::bigint as id) \
+             insert into tweet (owner_id) select id from owner returning id"
+        ));
+        assert!(!ExecuteResult::is_insert_returning(
+            "with added as (insert into tweet (text) values ('hi') returning id) \
+             select id from added"
+        ));
+        assert!(ExecuteResult::is_insert(
+            DatabaseType::Sqlite,
+            "with recursive n(i) as (select 1) insert into tweet (id) select i from n"
+        ));
+    }
+}
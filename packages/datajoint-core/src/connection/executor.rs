@@ -1,9 +1,13 @@
 use crate::common::{DatabaseType, DatabaseTypeAgnostic};
-use crate::connection::{Cursor, Pool};
+use crate::connection::{
+    AsyncExecutor, CancelHandle, Cursor, DelimitedFormat, ExecuteResult, PreparedStatement,
+    ScriptResult, ServerInfo,
+};
 use crate::error::Error;
 use crate::placeholders::{PlaceholderArgumentCollection, PlaceholderArgumentVector};
-use crate::query::Query;
-use crate::results::TableRow;
+use crate::results::{TableColumn, TableRow};
+use std::io::Read;
+use std::time::Duration;
 
 /// An object used to interact with a database by executing queries.
 ///
@@ -13,8 +17,8 @@ use crate::results::TableRow;
 pub struct Executor<'c> {
     // TODO(jackson-nestelroad): Somehow wrap sqlx::Executor so that pools,
     // connections, and transactions can all use this API.
-    pub(crate) executor: &'c Pool,
-    pub(crate) runtime: &'c tokio::runtime::Runtime,
+    executor: AsyncExecutor<'c>,
+    runtime: &'static tokio::runtime::Runtime,
 }
 
 impl<'c> DatabaseTypeAgnostic for Executor<'c> {
@@ -24,34 +28,92 @@ impl<'c> DatabaseTypeAgnostic for Executor<'c> {
 }
 
 impl<'c> Executor<'c> {
-    /// Creates a new executor over the given SQLx executor.
-    pub(crate) fn new(executor: &'c Pool, runtime: &'c tokio::runtime::Runtime) -> Self {
-        Executor {
-            executor: executor,
-            runtime: runtime,
-        }
+    /// Creates a new executor that runs the given asynchronous executor to completion.
+    pub(crate) fn new(
+        executor: AsyncExecutor<'c>,
+        runtime: &'static tokio::runtime::Runtime,
+    ) -> Self {
+        Executor { executor, runtime }
+    }
+
+    /// Gets the maximum amount of time a statement run by the executor may take.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.executor.timeout()
+    }
+
+    /// Sets the maximum amount of time a statement run by the executor may take before it
+    /// fails with [`ErrorCode::QueryTimedOut`][crate::error::ErrorCode::QueryTimedOut] and
+    /// is cancelled on the server.
+    ///
+    /// Overrides the statement timeout of the connection settings. `None` lets statements
+    /// run indefinitely.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.executor.set_timeout(timeout);
+    }
+
+    /// Sets the handle used to cancel the statements run by the executor, replacing the
+    /// handle of the connection.
+    pub fn set_cancel_handle(&mut self, cancel: CancelHandle) {
+        self.executor.set_cancel_handle(cancel);
+    }
+
+    /// Checks if the warnings produced by statements are counted.
+    pub fn count_warnings(&self) -> bool {
+        self.executor.count_warnings()
+    }
+
+    /// Sets whether the warnings produced by statements are counted and reported by
+    /// [`ExecuteResult::warnings`].
+    ///
+    /// Counting warnings takes another round-trip to the server after every statement, so
+    /// they are not counted by default. Only MySQL reports warnings.
+    pub fn set_count_warnings(&mut self, count_warnings: bool) {
+        self.executor.set_count_warnings(count_warnings);
+    }
+
+    /// Checks if statements that produce warnings fail.
+    pub fn warnings_as_errors(&self) -> bool {
+        self.executor.warnings_as_errors()
+    }
+
+    /// Sets whether statements that produce warnings fail with
+    /// [`ErrorCode::StatementWarnings`][crate::error::ErrorCode::StatementWarnings], such as
+    /// MySQL inserts that silently truncate values.
+    ///
+    /// The error message holds the warnings reported by `SHOW WARNINGS`. The statement has
+    /// already run when it fails, so its changes are kept unless it runs in a transaction
+    /// that is rolled back. Warnings are counted while this is set. Only MySQL reports
+    /// warnings.
+    pub fn set_warnings_as_errors(&mut self, warnings_as_errors: bool) {
+        self.executor.set_warnings_as_errors(warnings_as_errors);
     }
 
     /// Executes the given query over the connection.
     ///
     /// Panics on error.
-    pub fn execute(&self, query: &str) -> u64 {
+    pub fn execute(&self, query: &str) -> ExecuteResult {
         self.try_execute(query).unwrap()
     }
 
     /// Executes the given query over the connection.
     ///
     /// Uses placeholder arguments, binding them to the query prior to execution.
-    pub fn execute_ph(&self, query: &str, args: impl PlaceholderArgumentCollection) -> u64 {
+    pub fn execute_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> ExecuteResult {
         self.try_execute_ph(query, args).unwrap()
     }
 
     /// Executes the given query over the connection.
-    pub fn try_execute(&self, query: &str) -> Result<u64, Error> {
-        self.runtime.block_on(
-            self.executor
-                .try_execute(Query::new(self.database_type(), query)),
-        )
+    ///
+    /// Returns the number of rows affected by the query, along with the id of an inserted
+    /// row and the number of warnings, if the database reports them. Warnings are only
+    /// counted if the executor [counts warnings][Executor::set_count_warnings] or treats
+    /// them as errors.
+    pub fn try_execute(&self, query: &str) -> Result<ExecuteResult, Error> {
+        self.runtime.block_on(self.executor.try_execute(query))
     }
 
     /// Executes the given query over the connection.
@@ -61,10 +123,119 @@ impl<'c> Executor<'c> {
         &self,
         query: &str,
         args: impl PlaceholderArgumentCollection,
+    ) -> Result<ExecuteResult, Error> {
+        self.runtime
+            .block_on(self.executor.try_execute_ph(query, args))
+    }
+
+    /// Executes the given query once for every set of placeholder arguments.
+    ///
+    /// Panics on error.
+    pub fn execute_many(&self, query: &str, args: Vec<PlaceholderArgumentVector>) -> u64 {
+        self.try_execute_many(query, args).unwrap()
+    }
+
+    /// Executes the given query once for every set of placeholder arguments.
+    ///
+    /// Inserts of a single row of values, such as
+    /// `insert into tweet (text, owner_id) values (?, ?)`, are batched into statements
+    /// inserting many rows, each run in one round-trip, within the parameter and packet
+    /// limits of the server. Other statements run once for every set of arguments.
+    ///
+    /// Returns the total number of rows affected.
+    pub fn try_execute_many(
+        &self,
+        query: &str,
+        args: Vec<PlaceholderArgumentVector>,
+    ) -> Result<u64, Error> {
+        self.runtime
+            .block_on(self.executor.try_execute_many(query, args))
+    }
+
+    /// Runs the statements of a SQL script in order.
+    ///
+    /// Panics if the script could not be run, but not if one of its statements fails.
+    pub fn execute_script(&self, script: &str, in_transaction: bool) -> ScriptResult {
+        self.try_execute_script(script, in_transaction).unwrap()
+    }
+
+    /// Runs the statements of a SQL script in order, stopping at the first statement that
+    /// fails.
+    ///
+    /// The script is split into statements at semicolons outside of quoted text, comments
+    /// and Postgres dollar-quoted bodies. MySQL scripts may change the delimiter with
+    /// `DELIMITER` commands. Every statement runs over the same pooled connection.
+    ///
+    /// If `in_transaction` is set, the script runs inside of a single transaction, which
+    /// is committed if every statement succeeds and rolled back otherwise.
+    ///
+    /// The failing statement is reported by the returned [`ScriptResult`]. Fails if a
+    /// connection cannot be acquired or the transaction cannot be begun or committed.
+    pub fn try_execute_script(
+        &self,
+        script: &str,
+        in_transaction: bool,
+    ) -> Result<ScriptResult, Error> {
+        self.runtime
+            .block_on(self.executor.try_execute_script(script, in_transaction))
+    }
+
+    /// Loads rows of values into the given columns of a table.
+    ///
+    /// Panics on error.
+    pub fn bulk_load(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: Vec<PlaceholderArgumentVector>,
+    ) -> u64 {
+        self.try_bulk_load(table, columns, rows).unwrap()
+    }
+
+    /// Loads rows of values into the given columns of a table, returning the number of
+    /// rows loaded.
+    ///
+    /// Postgres streams the rows through `COPY ... FROM STDIN`. MySQL and SQLite insert
+    /// them with batched inserts, like [`try_execute_many()`][Executor::try_execute_many].
+    pub fn try_bulk_load(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: Vec<PlaceholderArgumentVector>,
+    ) -> Result<u64, Error> {
+        self.runtime
+            .block_on(self.executor.try_bulk_load(table, columns, rows))
+    }
+
+    /// Loads delimited text into the given columns of a table.
+    ///
+    /// Panics on error.
+    pub fn bulk_load_reader(
+        &self,
+        table: &str,
+        columns: &[&str],
+        reader: impl Read,
+        format: DelimitedFormat,
+    ) -> u64 {
+        self.try_bulk_load_reader(table, columns, reader, format)
+            .unwrap()
+    }
+
+    /// Loads delimited text into the given columns of a table, returning the number of
+    /// rows loaded.
+    ///
+    /// Postgres parses the text on the server through `COPY ... FROM STDIN`. MySQL and
+    /// SQLite parse the text and insert its rows with batched inserts.
+    pub fn try_bulk_load_reader(
+        &self,
+        table: &str,
+        columns: &[&str],
+        reader: impl Read,
+        format: DelimitedFormat,
     ) -> Result<u64, Error> {
         self.runtime.block_on(
             self.executor
-                .try_execute(args.bind_to_query(Query::new(self.database_type(), query))?),
+                .try_bulk_load_reader(table, columns, reader, format),
         )
     }
 
@@ -76,11 +247,32 @@ impl<'c> Executor<'c> {
     }
 
     /// Fetches one row using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// Panics on error.
+    pub fn fetch_one_ph(&self, query: &str, args: impl PlaceholderArgumentCollection) -> TableRow {
+        self.try_fetch_one_ph(query, args).unwrap()
+    }
+
+    /// Fetches one row using the given query.
+    ///
+    /// Reads and writes the result cache if the connection has one.
     pub fn try_fetch_one(&self, query: &str) -> Result<TableRow, Error> {
-        self.runtime.block_on(
-            self.executor
-                .try_fetch_one(Query::new(self.database_type(), query)),
-        )
+        self.runtime.block_on(self.executor.try_fetch_one(query))
+    }
+
+    /// Fetches one row using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution. Reads and
+    /// writes the result cache if the connection has one.
+    pub fn try_fetch_one_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<TableRow, Error> {
+        self.runtime
+            .block_on(self.executor.try_fetch_one_ph(query, args))
     }
 
     /// Fetches multiple rows using the given query.
@@ -91,26 +283,116 @@ impl<'c> Executor<'c> {
     }
 
     /// Fetches multiple rows using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// Panics on error.
+    pub fn fetch_all_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Vec<TableRow> {
+        self.try_fetch_all_ph(query, args).unwrap()
+    }
+
+    /// Fetches multiple rows using the given query.
+    ///
+    /// Reads and writes the result cache if the connection has one.
     pub fn try_fetch_all(&self, query: &str) -> Result<Vec<TableRow>, Error> {
-        self.runtime.block_on(
-            self.executor
-                .try_fetch_all(Query::new(self.database_type(), query)),
-        )
+        self.runtime.block_on(self.executor.try_fetch_all(query))
+    }
+
+    /// Fetches multiple rows using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution. Reads and
+    /// writes the result cache if the connection has one.
+    pub fn try_fetch_all_ph(
+        &self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<Vec<TableRow>, Error> {
+        self.runtime
+            .block_on(self.executor.try_fetch_all_ph(query, args))
+    }
+
+    /// Checks that the database server can still be reached over the connection.
+    pub fn ping(&self) -> Result<(), Error> {
+        self.runtime.block_on(self.executor.ping())
+    }
+
+    /// Fetches the version, current user and current database of the database server.
+    ///
+    /// Panics on error.
+    pub fn server_info(&self) -> ServerInfo {
+        self.try_server_info().unwrap()
+    }
+
+    /// Fetches the version, current user and current database of the database server.
+    pub fn try_server_info(&self) -> Result<ServerInfo, Error> {
+        self.runtime.block_on(self.executor.try_server_info())
+    }
+
+    /// Describes the columns of the rows returned by the given query, without running it.
+    ///
+    /// Panics on error.
+    pub fn describe(&self, query: &str) -> Vec<TableColumn> {
+        self.try_describe(query).unwrap()
+    }
+
+    /// Describes the columns of the rows returned by the given query, without running it.
+    ///
+    /// Unlike the columns of fetched rows, described columns are known for queries that
+    /// return no rows and report whether they may hold nulls, if the database can tell.
+    /// Non-returning statements have no columns, and mock databases describe none.
+    pub fn try_describe(&self, query: &str) -> Result<Vec<TableColumn>, Error> {
+        self.runtime.block_on(self.executor.try_describe(query))
     }
 
     /// Creates a cursor for the given query.
-    pub fn cursor(&'c self, query: &str) -> Result<Cursor<'c>, Error> {
-        Cursor::new_from_executor_ref(query, &self, None as Option<PlaceholderArgumentVector>)
+    ///
+    /// The cursor holds a pooled connection until all of its rows are read or it is dropped,
+    /// or until the connection disconnects, after which it fails with
+    /// [`ErrorCode::NotConnected`][crate::error::ErrorCode::NotConnected]. Rows streamed by a
+    /// cursor are never retried after a lost connection.
+    pub fn cursor(&self, query: &str) -> Result<Cursor<'static>, Error> {
+        Ok(Cursor::new(
+            self.runtime.block_on(self.executor.cursor(query))?,
+            self.runtime,
+        ))
     }
 
     /// Creates a cursor for the given query.
     ///
     /// Uses placeholder arguments, binding them to the query prior to execution.
     pub fn cursor_ph(
-        &'c self,
+        &self,
         query: &str,
         args: impl PlaceholderArgumentCollection,
-    ) -> Result<Cursor<'c>, Error> {
-        Cursor::new_from_executor_ref(query, self, Some(args))
+    ) -> Result<Cursor<'static>, Error> {
+        Ok(Cursor::new(
+            self.runtime
+                .block_on(self.executor.cursor_ph(query, args))?,
+            self.runtime,
+        ))
+    }
+
+    /// Prepares the given statement on the database server, returning a statement that
+    /// can be run any number of times with different placeholder arguments.
+    ///
+    /// Panics on error.
+    pub fn prepare(&self, query: &str) -> PreparedStatement<'c> {
+        self.try_prepare(query).unwrap()
+    }
+
+    /// Prepares the given statement on the database server, returning a statement that
+    /// can be run any number of times with different placeholder arguments.
+    ///
+    /// Preparing reports the number of placeholder parameters of the statement and the
+    /// columns of the rows it returns, without running it.
+    pub fn try_prepare(&self, query: &str) -> Result<PreparedStatement<'c>, Error> {
+        Ok(PreparedStatement::new(
+            self.runtime.block_on(self.executor.prepare(query))?,
+            self.runtime,
+        ))
     }
 }
@@ -0,0 +1,331 @@
+use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::connection::ExecuteResult;
+use crate::error::{DataJointError, Error, ErrorCode};
+use crate::query::MockQuery;
+use crate::results::{OwnedRow, TableColumn, TableRow};
+use crate::types::{DataJointType, NativeType};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A statement recorded by a [`MockDatabase`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockStatement {
+    /// The SQL text of the statement.
+    pub sql: String,
+    /// The placeholder arguments bound to the statement, in order.
+    pub arguments: Vec<NativeType>,
+}
+
+/// Rows scripted as the result of a statement run over a [`MockDatabase`].
+#[derive(Debug, Clone)]
+pub struct MockRows {
+    columns: Vec<(String, DataJointType)>,
+    rows: Vec<Vec<NativeType>>,
+}
+
+impl MockRows {
+    /// Creates an empty result with the given column names and types.
+    pub fn new(columns: &[(&str, DataJointType)]) -> Self {
+        MockRows {
+            columns: columns
+                .iter()
+                .map(|(name, type_name)| (name.to_string(), *type_name))
+                .collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row holding one value per column, with null values given as
+    /// [`NativeType::None`].
+    ///
+    /// Panics if the number of values does not match the number of columns.
+    pub fn row(mut self, values: Vec<NativeType>) -> Self {
+        assert_eq!(
+            values.len(),
+            self.columns.len(),
+            "mock row has {} values for {} columns",
+            values.len(),
+            self.columns.len()
+        );
+        self.rows.push(values);
+        self
+    }
+
+    /// Returns the number of rows.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Checks if there are no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Converts the scripted rows to owned table rows of the given database type.
+    fn into_table_rows(self, database_type: DatabaseType) -> Vec<TableRow> {
+        let columns: Arc<[TableColumn]> = self
+            .columns
+            .into_iter()
+            .enumerate()
+            .map(|(ordinal, (name, type_name))| TableColumn {
+                database_type,
+                ordinal,
+                name,
+                type_name,
+                nullable: None,
+            })
+            .collect();
+        self.rows
+            .into_iter()
+            .map(|values| TableRow::Owned(OwnedRow::new(database_type, columns.clone(), values)))
+            .collect()
+    }
+}
+
+/// A result scripted for a statement run over a [`MockDatabase`].
+#[derive(Debug, Clone)]
+pub enum MockResult {
+    /// Rows returned by a returning statement.
+    ///
+    /// A non-returning statement reports the number of rows as the number of rows affected.
+    Rows(MockRows),
+    /// The number of rows affected by a non-returning statement.
+    ///
+    /// A returning statement returns no rows.
+    RowsAffected(u64),
+    /// The result of a non-returning statement, such as one that inserted a row with a
+    /// generated id or produced warnings.
+    ///
+    /// The warnings are only reported if the statement was run with warnings counted. A
+    /// returning statement returns no rows.
+    Executed(ExecuteResult),
+    /// An error the statement fails with.
+    Error(ErrorCode, String),
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    statements: Vec<MockStatement>,
+    results: VecDeque<MockResult>,
+}
+
+/// An in-memory stand-in for a database server, used to test code that runs queries
+/// without connecting to a real database.
+///
+/// A mock database records every statement run over it, along with its placeholder
+/// arguments, and answers statements with scripted results in the order they were
+/// pushed. Once the scripted results run out, non-returning statements affect no rows
+/// and returning statements return no rows.
+///
+/// Rows are returned as owned rows, which are read with
+/// [`try_decode()`][crate::results::TableRow::try_decode] and its variants. Placeholder
+/// arguments are checked as the emulated database type would bind them.
+///
+/// Clones of a mock database share their statements and results, so a clone can be
+/// kept for inspection after another is given to
+/// [`Connection::new_mock()`][crate::connection::Connection::new_mock].
+#[derive(Debug, Clone)]
+pub struct MockDatabase {
+    database_type: DatabaseType,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl DatabaseTypeAgnostic for MockDatabase {
+    fn database_type(&self) -> DatabaseType {
+        self.database_type
+    }
+}
+
+impl MockDatabase {
+    /// Creates a new mock database that emulates the given database type.
+    pub fn new(database_type: DatabaseType) -> Self {
+        MockDatabase {
+            database_type,
+            state: Arc::new(Mutex::new(MockState::default())),
+        }
+    }
+
+    /// Scripts the result of the next statement that has no result yet.
+    pub fn push_result(&self, result: MockResult) {
+        self.state.lock().unwrap().results.push_back(result);
+    }
+
+    /// Scripts rows to be returned by the next statement that has no result yet.
+    pub fn push_rows(&self, rows: MockRows) {
+        self.push_result(MockResult::Rows(rows));
+    }
+
+    /// Scripts the number of rows affected by the next statement that has no result yet.
+    pub fn push_rows_affected(&self, rows: u64) {
+        self.push_result(MockResult::RowsAffected(rows));
+    }
+
+    /// Scripts the result of the next non-returning statement that has no result yet.
+    pub fn push_execute_result(&self, result: ExecuteResult) {
+        self.push_result(MockResult::Executed(result));
+    }
+
+    /// Scripts an error for the next statement that has no result yet to fail with.
+    pub fn push_error(&self, code: ErrorCode, message: &str) {
+        self.push_result(MockResult::Error(code, message.to_string()));
+    }
+
+    /// Returns the number of scripted results that have not been used yet.
+    pub fn pending_results(&self) -> usize {
+        self.state.lock().unwrap().results.len()
+    }
+
+    /// Returns every statement run so far, in order.
+    ///
+    /// Beginning, committing and rolling back a transaction are recorded as `BEGIN`,
+    /// `COMMIT` and `ROLLBACK` statements, which do not use scripted results.
+    pub fn statements(&self) -> Vec<MockStatement> {
+        self.state.lock().unwrap().statements.clone()
+    }
+
+    /// Forgets every statement run so far.
+    pub fn clear_statements(&self) {
+        self.state.lock().unwrap().statements.clear();
+    }
+
+    /// Records a statement that does not use a scripted result.
+    pub(crate) fn record(&self, sql: &str) {
+        self.state.lock().unwrap().statements.push(MockStatement {
+            sql: sql.to_string(),
+            arguments: Vec::new(),
+        });
+    }
+
+    /// Records a statement and takes its scripted result, if any.
+    fn run(&self, query: MockQuery) -> Option<MockResult> {
+        let mut state = self.state.lock().unwrap();
+        state.statements.push(MockStatement {
+            sql: query.sql.to_string(),
+            arguments: query.arguments,
+        });
+        state.results.pop_front()
+    }
+
+    /// Runs a non-returning statement, dropping its scripted warnings unless
+    /// `count_warnings` is set.
+    pub(crate) fn execute(
+        &self,
+        query: MockQuery,
+        count_warnings: bool,
+    ) -> Result<ExecuteResult, Error> {
+        match self.run(query) {
+            None => Ok(ExecuteResult::default()),
+            Some(MockResult::Rows(rows)) => {
+                Ok(ExecuteResult::with_rows_affected(rows.len() as u64))
+            }
+            Some(MockResult::RowsAffected(rows)) => Ok(ExecuteResult::with_rows_affected(rows)),
+            Some(MockResult::Executed(result)) if count_warnings => Ok(result),
+            Some(MockResult::Executed(result)) => Ok(ExecuteResult {
+                warnings: None,
+                ..result
+            }),
+            Some(MockResult::Error(code, message)) => {
+                Err(DataJointError::new_with_message(&message, code))
+            }
+        }
+    }
+
+    /// Runs a returning statement, returning all of its rows.
+    pub(crate) fn fetch_all(&self, query: MockQuery) -> Result<Vec<TableRow>, Error> {
+        match self.run(query) {
+            None | Some(MockResult::RowsAffected(_)) | Some(MockResult::Executed(_)) => {
+                Ok(Vec::new())
+            }
+            Some(MockResult::Rows(rows)) => Ok(rows.into_table_rows(self.database_type)),
+            Some(MockResult::Error(code, message)) => {
+                Err(DataJointError::new_with_message(&message, code))
+            }
+        }
+    }
+
+    /// Runs a returning statement, returning its first row.
+    pub(crate) fn fetch_one(&self, query: MockQuery) -> Result<TableRow, Error> {
+        match self.fetch_all(query)?.into_iter().next() {
+            None => Err(DataJointError::new(ErrorCode::RowNotFound)),
+            Some(row) => Ok(row),
+        }
+    }
+
+    /// Runs a returning statement, returning a cursor over its rows.
+    pub(crate) fn cursor(&self, query: MockQuery) -> MockCursor {
+        match self.run(query) {
+            None | Some(MockResult::RowsAffected(_)) | Some(MockResult::Executed(_)) => {
+                MockCursor {
+                    rows: Vec::new().into_iter(),
+                    error: None,
+                }
+            }
+            Some(MockResult::Rows(rows)) => MockCursor {
+                rows: rows.into_table_rows(self.database_type).into_iter(),
+                error: None,
+            },
+            Some(MockResult::Error(code, message)) => MockCursor {
+                rows: Vec::new().into_iter(),
+                error: Some((code, message)),
+            },
+        }
+    }
+}
+
+/// The rows of a statement run over a mock database, read by a cursor.
+pub(crate) struct MockCursor {
+    rows: std::vec::IntoIter<TableRow>,
+    // The scripted error, which is reported when the first row is read.
+    error: Option<(ErrorCode, String)>,
+}
+
+impl Iterator for MockCursor {
+    type Item = Result<TableRow, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((code, message)) = self.error.take() {
+            return Some(Err(DataJointError::new_with_message(&message, code)));
+        }
+        self.rows.next().map(Ok)
+    }
+}
+
+/// A connection pool over a mock database.
+///
+/// Clones of a pool share the same database and closed state.
+#[derive(Clone)]
+pub(crate) struct MockPool {
+    pub database: MockDatabase,
+    closed: Arc<AtomicBool>,
+}
+
+impl MockPool {
+    /// Creates a new open pool over the mock database.
+    pub fn new(database: MockDatabase) -> Self {
+        MockPool {
+            database,
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Checks if the pool has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Closes the pool.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// Fails with [`ErrorCode::PoolClosed`] once the pool has been closed, like the pool of
+    /// a database server.
+    pub fn check_open(&self) -> Result<(), Error> {
+        if self.is_closed() {
+            Err(DataJointError::new(ErrorCode::PoolClosed))
+        } else {
+            Ok(())
+        }
+    }
+}
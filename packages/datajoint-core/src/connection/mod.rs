@@ -1,11 +1,68 @@
+#[cfg(feature = "async")]
+mod async_connection;
+// Without the feature, only the blocking wrappers use the asynchronous types.
+#[cfg_attr(not(feature = "async"), allow(dead_code))]
+mod async_cursor;
+#[cfg_attr(not(feature = "async"), allow(dead_code))]
+mod async_executor;
+#[cfg_attr(not(feature = "async"), allow(dead_code))]
+mod async_prepared;
+mod bulk_load;
+mod cache;
+mod cancel;
 mod connection;
+mod credentials;
 mod cursor;
+mod execute_result;
 mod executor;
+mod mock;
+mod observer;
 mod pool;
+mod pool_connection;
+mod prepared;
+mod reconnect;
+mod runtime;
+mod script;
+mod server_info;
+mod session;
 mod settings;
+mod tls;
+mod transaction;
 
+#[cfg(feature = "async")]
+pub use async_connection::AsyncConnection;
+#[cfg(feature = "async")]
+pub use async_cursor::AsyncCursor;
+#[cfg(not(feature = "async"))]
+pub(crate) use async_cursor::AsyncCursor;
+#[cfg(feature = "async")]
+pub use async_executor::AsyncExecutor;
+#[cfg(not(feature = "async"))]
+pub(crate) use async_executor::AsyncExecutor;
+#[cfg(feature = "async")]
+pub use async_prepared::AsyncPreparedStatement;
+#[cfg(not(feature = "async"))]
+pub(crate) use async_prepared::AsyncPreparedStatement;
+pub use bulk_load::DelimitedFormat;
+pub use cache::{CacheMode, CacheSettings};
+pub(crate) use cache::{CachedFetch, ResultCache};
+pub use cancel::CancelHandle;
 pub use connection::Connection;
+pub use credentials::{CredentialCallback, CredentialProvider};
 pub use cursor::Cursor;
+pub use execute_result::ExecuteResult;
 pub use executor::Executor;
-pub(crate) use pool::Pool;
+pub(crate) use mock::MockPool;
+pub use mock::{MockDatabase, MockResult, MockRows, MockStatement};
+pub use observer::{QueryEvent, QueryObserver};
+pub(crate) use pool::{CloseSignal, Pool, SharedPool};
+pub(crate) use pool_connection::{InterruptibleConnection, SqlxConnection};
+pub use prepared::PreparedStatement;
+pub use reconnect::ReconnectPolicy;
+pub use script::ScriptResult;
+pub use server_info::ServerInfo;
+pub use session::SessionSettings;
 pub use settings::ConnectionSettings;
+pub use tls::{TlsMode, TlsSettings};
+pub(crate) use transaction::SqlxTransaction;
+pub use transaction::Transaction;
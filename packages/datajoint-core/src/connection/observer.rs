@@ -0,0 +1,82 @@
+use crate::error::{Error, ErrorCode};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A description of a finished statement, passed to a [`QueryObserver`].
+pub struct QueryEvent<'a> {
+    /// The SQL text of the statement.
+    pub query: &'a str,
+    /// The placeholder arguments bound to the statement, with their values redacted to
+    /// their types.
+    pub arguments: &'a [String],
+    /// The number of rows affected by a non-returning statement, or returned by a
+    /// returning one.
+    pub rows: u64,
+    /// How long the statement took, including waiting for a pooled connection and any
+    /// retries. For cursors, this spans from creating the cursor to its last row.
+    pub duration: Duration,
+    /// [`ErrorCode::Success`], or the code of the error the statement failed with.
+    pub error_code: ErrorCode,
+}
+
+/// An observer of every statement run over a connection, for logging and tracing.
+///
+/// Observers are called synchronously on the thread that ran the statement, so they
+/// should return quickly.
+pub trait QueryObserver: Send + Sync {
+    /// Called once a statement finishes, successfully or not.
+    fn on_query(&self, event: &QueryEvent);
+}
+
+/// A statement that is being timed for an observer.
+pub(crate) struct ObservedQuery {
+    observer: Arc<dyn QueryObserver>,
+    arguments: Vec<String>,
+    start: Instant,
+}
+
+impl ObservedQuery {
+    /// Starts timing a statement if there is an observer.
+    ///
+    /// `arguments` produces the redacted placeholder arguments of the statement.
+    pub fn start<F>(observer: &Option<Arc<dyn QueryObserver>>, arguments: F) -> Option<Self>
+    where
+        F: FnOnce() -> Vec<String>,
+    {
+        observer.as_ref().map(|observer| ObservedQuery {
+            observer: observer.clone(),
+            arguments: arguments(),
+            start: Instant::now(),
+        })
+    }
+
+    /// Reports the finished statement to the observer.
+    pub fn finish(self, query: &str, rows: u64, error_code: ErrorCode) {
+        self.observer.on_query(&QueryEvent {
+            query,
+            arguments: &self.arguments,
+            rows,
+            duration: self.start.elapsed(),
+            error_code,
+        });
+    }
+}
+
+/// Reports the result of a statement to its observer, if any.
+///
+/// `rows` counts the rows affected or returned by a successful statement.
+pub(crate) fn finish_observed<T, F>(
+    observed: Option<ObservedQuery>,
+    query: &str,
+    result: &Result<T, Error>,
+    rows: F,
+) where
+    F: FnOnce(&T) -> u64,
+{
+    if let Some(observed) = observed {
+        match result {
+            Err(error) => observed.finish(query, 0, error.code()),
+            Ok(value) => observed.finish(query, rows(value), ErrorCode::Success),
+        }
+    }
+}
@@ -1,8 +1,32 @@
 use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::connection::{
+    ConnectionSettings, ExecuteResult, MockPool, SqlxConnection, SqlxTransaction,
+};
 use crate::error::{DataJointError, Error, ErrorCode, SqlxError};
-use crate::query::Query;
+use crate::query::{MockQuery, Query};
 use crate::results::TableRow;
-use sqlx::Executor;
+use futures::future::BoxFuture;
+use sqlx::mysql::MySqlConnection;
+use sqlx::pool::PoolOptions;
+use sqlx::postgres::PgConnection;
+use sqlx::{Connection, Executor};
+use std::cell::Cell;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// A function that fetches the identifier of the server session behind a connection.
+type SessionIdQuery<DB> = for<'c> fn(
+    &'c mut <DB as sqlx::Database>::Connection,
+) -> BoxFuture<'c, Result<u64, sqlx::Error>>;
+
+tokio::task_local! {
+    // The server session of the connection being acquired by
+    // `Pool::acquire_with_session_id()`, which is fetched while the pool checks or opens
+    // the connection.
+    static ACQUIRED_SESSION_ID: Cell<Option<u64>>;
+}
 
 // TODO(jackson-nestelroad): Somehow wrap `sqlx::Executor` instead of `sqlx::Pool` (which
 // implements `sqlx::Executor`) to make this type more generic, allowing more types to use
@@ -14,9 +38,15 @@ use sqlx::Executor;
 ///
 /// Different from [`Executor`][crate::connection::Executor] in that
 /// it does not rely on a runtime for asynchronous operations.
+///
+/// Clones of a pool share the same connections, so a clone keeps the pool usable for as
+/// long as it is held.
+#[derive(Clone)]
 pub(crate) enum Pool {
     MySql(sqlx::Pool<sqlx::MySql>),
     Postgres(sqlx::Pool<sqlx::Postgres>),
+    Sqlite(sqlx::Pool<sqlx::Sqlite>),
+    Mock(MockPool),
 }
 
 impl DatabaseTypeAgnostic for Pool {
@@ -24,16 +54,207 @@ impl DatabaseTypeAgnostic for Pool {
         match self {
             Self::MySql(_) => DatabaseType::MySql,
             Self::Postgres(_) => DatabaseType::Postgres,
+            Self::Sqlite(_) => DatabaseType::Sqlite,
+            Self::Mock(pool) => pool.database.database_type(),
         }
     }
 }
 
 impl Pool {
+    /// Creates the pool options shared by every database type from the connection settings.
+    ///
+    /// The session statements of the settings run on every connection the pool opens. For
+    /// databases with a server, the session id is fetched while a connection is acquired
+    /// for [`acquire_with_session_id()`][Pool::acquire_with_session_id], standing in for
+    /// the liveness check of idle connections.
+    fn pool_options<DB>(
+        settings: &ConnectionSettings,
+        session_id: Option<SessionIdQuery<DB>>,
+    ) -> Result<PoolOptions<DB>, Error>
+    where
+        DB: sqlx::Database,
+        for<'c> &'c mut DB::Connection: sqlx::Executor<'c, Database = DB>,
+    {
+        if settings.max_connections == 0 {
+            return Err(DataJointError::new_with_message(
+                "max_connections must be greater than zero",
+                ErrorCode::ConfigurationError,
+            ));
+        }
+        if settings.min_connections > settings.max_connections {
+            return Err(DataJointError::new_with_message(
+                "min_connections must not be greater than max_connections",
+                ErrorCode::ConfigurationError,
+            ));
+        }
+        let mut options = PoolOptions::<DB>::new()
+            .max_connections(settings.max_connections)
+            .min_connections(settings.min_connections)
+            .connect_timeout(settings.acquire_timeout)
+            .idle_timeout(settings.idle_timeout)
+            .max_lifetime(settings.max_lifetime);
+
+        if let Some(session_id) = session_id {
+            // The session id query round trips to the server just like a ping, so it is
+            // only run in place of one.
+            options = options
+                .test_before_acquire(false)
+                .before_acquire(move |connection| {
+                    Box::pin(async move {
+                        if ACQUIRED_SESSION_ID.try_with(|_| ()).is_err() {
+                            connection.ping().await?;
+                        } else {
+                            let id = session_id(connection).await?;
+                            ACQUIRED_SESSION_ID.with(|acquired| acquired.set(Some(id)));
+                        }
+                        Ok(true)
+                    })
+                });
+        }
+
+        let statements = Arc::new(settings.session.statements(settings.database_type)?);
+        if statements.is_empty() && session_id.is_none() {
+            return Ok(options);
+        }
+        Ok(options.after_connect(move |connection| {
+            let statements = statements.clone();
+            Box::pin(async move {
+                for statement in statements.iter() {
+                    connection.execute(statement.as_str()).await?;
+                }
+                if let (Some(session_id), Ok(())) =
+                    (session_id, ACQUIRED_SESSION_ID.try_with(|_| ()))
+                {
+                    let id = session_id(connection).await?;
+                    ACQUIRED_SESSION_ID.with(|acquired| acquired.set(Some(id)));
+                }
+                Ok(())
+            })
+        }))
+    }
+
+    /// Fetches the identifier of the server session behind a MySQL connection.
+    pub fn mysql_session_id(
+        connection: &mut MySqlConnection,
+    ) -> BoxFuture<'_, Result<u64, sqlx::Error>> {
+        Box::pin(sqlx::query_scalar("SELECT CONNECTION_ID()").fetch_one(connection))
+    }
+
+    /// Fetches the identifier of the server session behind a Postgres connection.
+    pub fn postgres_session_id(
+        connection: &mut PgConnection,
+    ) -> BoxFuture<'_, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_scalar::<_, i32>("SELECT pg_backend_pid()")
+                .fetch_one(connection)
+                .await
+                .map(|pid| pid as u64)
+        })
+    }
+
+    /// Opens a new connection pool according to the given settings.
+    pub async fn connect(settings: &ConnectionSettings) -> Result<Pool, Error> {
+        if settings.database_type == DatabaseType::Sqlite && settings.socket_path.is_some() {
+            return Err(DataJointError::new_with_message(
+                "socket_path is not supported for SQLite databases",
+                ErrorCode::ConfigurationError,
+            ));
+        }
+        let uri = settings.connect_uri()?;
+        match settings.database_type {
+            DatabaseType::MySql => {
+                match Pool::pool_options::<sqlx::MySql>(settings, Some(Pool::mysql_session_id))?
+                    .connect(&uri)
+                    .await
+                {
+                    Err(err) => Err(SqlxError::new(err)),
+                    Ok(pool) => Ok(Pool::MySql(pool)),
+                }
+            }
+            DatabaseType::Postgres => {
+                match Pool::pool_options::<sqlx::Postgres>(
+                    settings,
+                    Some(Pool::postgres_session_id),
+                )?
+                .connect(&uri)
+                .await
+                {
+                    Err(err) => Err(SqlxError::new(err)),
+                    Ok(pool) => Ok(Pool::Postgres(pool)),
+                }
+            }
+            DatabaseType::Sqlite => {
+                let mut options = Pool::pool_options::<sqlx::Sqlite>(settings, None)?;
+                if settings.is_sqlite_in_memory() {
+                    // An in-memory database is dropped with its last connection, so keep
+                    // one connection open for the lifetime of the pool.
+                    options = options
+                        .min_connections(settings.min_connections.max(1))
+                        .idle_timeout(None)
+                        .max_lifetime(None);
+                }
+                match options.connect(&uri).await {
+                    Err(err) => Err(SqlxError::new(err)),
+                    Ok(pool) => Ok(Pool::Sqlite(pool)),
+                }
+            }
+        }
+    }
+
+    /// Creates a query with the given string for the database behind the pool.
+    pub fn query<'q>(&self, query: &'q str) -> Query<'q> {
+        match self {
+            Self::Mock(_) => Query::Mock(MockQuery::new(self.database_type(), query)),
+            _ => Query::new(self.database_type(), query),
+        }
+    }
+
+    /// Checks that a pooled connection can still reach the database server.
+    pub async fn ping(&self) -> Result<(), Error> {
+        let result = match self {
+            Self::MySql(pool) => match pool.acquire().await {
+                Err(error) => Err(error),
+                Ok(mut connection) => connection.ping().await,
+            },
+            Self::Postgres(pool) => match pool.acquire().await {
+                Err(error) => Err(error),
+                Ok(mut connection) => connection.ping().await,
+            },
+            Self::Sqlite(pool) => match pool.acquire().await {
+                Err(error) => Err(error),
+                Ok(mut connection) => connection.ping().await,
+            },
+            Self::Mock(pool) => return pool.check_open(),
+        };
+        result.map_err(|error| SqlxError::new(error) as Error)
+    }
+
+    /// Fetches the size, in bytes, of the largest statement the database server accepts,
+    /// or `None` if the server has no limit that matters in practice.
+    ///
+    /// For MySQL, this is the `max_allowed_packet` of the session.
+    pub async fn max_statement_size(&self) -> Result<Option<usize>, Error> {
+        match self {
+            Self::MySql(pool) => {
+                match sqlx::query_scalar::<_, u64>("SELECT CAST(@@max_allowed_packet AS UNSIGNED)")
+                    .fetch_one(pool)
+                    .await
+                {
+                    Err(error) => Err(SqlxError::new(error)),
+                    Ok(size) => Ok(Some(size as usize)),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
     /// Checks if the connection pool has been closed.
     pub fn is_closed(&self) -> bool {
         match self {
             Self::MySql(pool) => pool.is_closed(),
             Self::Postgres(pool) => pool.is_closed(),
+            Self::Sqlite(pool) => pool.is_closed(),
+            Self::Mock(pool) => pool.is_closed(),
         }
     }
 
@@ -42,9 +263,86 @@ impl Pool {
         match self {
             Self::MySql(pool) => pool.close().await,
             Self::Postgres(pool) => pool.close().await,
+            Self::Sqlite(pool) => pool.close().await,
+            Self::Mock(pool) => pool.close(),
         }
     }
 
+    /// Acquires a connection from the pool and begins a transaction on it.
+    pub async fn begin(&self) -> Result<SqlxTransaction, Error> {
+        match self {
+            Self::MySql(pool) => match pool.begin().await {
+                Err(error) => Err(SqlxError::new(error)),
+                Ok(transaction) => Ok(SqlxTransaction::MySql(transaction)),
+            },
+            Self::Postgres(pool) => match pool.begin().await {
+                Err(error) => Err(SqlxError::new(error)),
+                Ok(transaction) => Ok(SqlxTransaction::Postgres(transaction)),
+            },
+            Self::Sqlite(pool) => match pool.begin().await {
+                Err(error) => Err(SqlxError::new(error)),
+                Ok(transaction) => Ok(SqlxTransaction::Sqlite(transaction)),
+            },
+            Self::Mock(pool) => {
+                pool.check_open()?;
+                pool.database.record("BEGIN");
+                Ok(SqlxTransaction::Mock(pool.database.clone()))
+            }
+        }
+    }
+
+    /// Acquires a single connection from the pool.
+    pub async fn acquire(&self) -> Result<SqlxConnection, Error> {
+        let result = match self {
+            Self::MySql(pool) => pool.acquire().await.map(SqlxConnection::MySql),
+            Self::Postgres(pool) => pool.acquire().await.map(SqlxConnection::Postgres),
+            Self::Sqlite(pool) => pool.acquire().await.map(SqlxConnection::Sqlite),
+            Self::Mock(pool) => {
+                pool.check_open()?;
+                return Ok(SqlxConnection::Mock(pool.database.clone()));
+            }
+        };
+        result.map_err(|error| SqlxError::new(error) as Error)
+    }
+
+    /// Acquires a connection from the pool along with the identifier of its server session,
+    /// which is used to cancel its statements from another connection.
+    ///
+    /// The session id is fetched while the pool checks that an idle connection is still
+    /// alive, or right after the pool opens a new connection, so acquiring takes no extra
+    /// round trip to the server. Returns no session for SQLite and mock databases.
+    pub async fn acquire_with_session_id(&self) -> Result<(SqlxConnection, Option<u64>), Error> {
+        if let Self::Sqlite(_) | Self::Mock(_) = self {
+            return Ok((self.acquire().await?, None));
+        }
+        ACQUIRED_SESSION_ID
+            .scope(Cell::new(None), async {
+                let mut connection = self.acquire().await?;
+                let session_id = match ACQUIRED_SESSION_ID.with(Cell::get) {
+                    Some(session_id) => Some(session_id),
+                    // The pool hands out connections it has just checked or opened, but
+                    // the session is looked up directly should that ever change.
+                    None => connection.session_id().await?,
+                };
+                Ok((connection, session_id))
+            })
+            .await
+    }
+
+    /// Cancels the statement running in the given server session, as reported by
+    /// [`acquire_with_session_id()`][Pool::acquire_with_session_id], from another pooled
+    /// connection.
+    pub async fn cancel_session(&self, session_id: u64) -> Result<(), Error> {
+        let statement = match self {
+            Self::MySql(_) => format!("KILL QUERY {}", session_id),
+            Self::Postgres(_) => format!("SELECT pg_cancel_backend({})", session_id),
+            Self::Sqlite(_) | Self::Mock(_) => return Ok(()),
+        };
+        self.try_execute(self.query(&statement), false)
+            .await
+            .map(|_| ())
+    }
+
     fn wrong_database_type_error() -> Error {
         DataJointError::new_with_message(
             "prepared query is for the wrong database type",
@@ -54,30 +352,16 @@ impl Pool {
 
     /// Attempts to execute a non-returning query over the connection.
     ///
-    /// Returns the number of rows affected by the query.
-    pub async fn try_execute<'q>(&self, query: Query<'q>) -> Result<u64, Error> {
-        match self {
-            Self::MySql(pool) => {
-                if let Query::MySql(query) = query {
-                    match pool.execute(query).await {
-                        Err(error) => Err(SqlxError::new(error)),
-                        Ok(result) => Ok(result.rows_affected()),
-                    }
-                } else {
-                    Err(Pool::wrong_database_type_error())
-                }
-            }
-            Self::Postgres(pool) => {
-                if let Query::Postgres(query) = query {
-                    match pool.execute(query).await {
-                        Err(error) => Err(SqlxError::new(error)),
-                        Ok(result) => Ok(result.rows_affected()),
-                    }
-                } else {
-                    Err(Pool::wrong_database_type_error())
-                }
-            }
-        }
+    /// Returns the number of rows affected by the query, along with the id of an inserted
+    /// row, if the database reports them. If `count_warnings` is set, MySQL warnings are
+    /// counted over the same pooled connection as the query.
+    pub async fn try_execute<'q>(
+        &self,
+        query: Query<'q>,
+        count_warnings: bool,
+    ) -> Result<ExecuteResult, Error> {
+        let mut connection = self.acquire().await?;
+        connection.try_execute(query, count_warnings).await
     }
 
     /// Attempts to execute a returning query over the connection.
@@ -108,6 +392,24 @@ impl Pool {
                     ))
                 }
             }
+            Self::Sqlite(pool) => {
+                if let Query::Sqlite(query) = query {
+                    match pool.fetch_one(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(row) => Ok(TableRow::Sqlite(row)),
+                    }
+                } else {
+                    Err(Pool::wrong_database_type_error())
+                }
+            }
+            Self::Mock(pool) => {
+                pool.check_open()?;
+                if let Query::Mock(query) = query {
+                    pool.database.fetch_one(query)
+                } else {
+                    Err(Pool::wrong_database_type_error())
+                }
+            }
         }
     }
 
@@ -136,6 +438,161 @@ impl Pool {
                     Err(Pool::wrong_database_type_error())
                 }
             }
+            Self::Sqlite(pool) => {
+                if let Query::Sqlite(query) = query {
+                    match pool.fetch_all(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(rows) => Ok(rows.into_iter().map(TableRow::Sqlite).collect()),
+                    }
+                } else {
+                    Err(Pool::wrong_database_type_error())
+                }
+            }
+            Self::Mock(pool) => {
+                pool.check_open()?;
+                if let Query::Mock(query) = query {
+                    pool.database.fetch_all(query)
+                } else {
+                    Err(Pool::wrong_database_type_error())
+                }
+            }
+        }
+    }
+}
+
+struct CloseState {
+    closed: AtomicBool,
+    notify: Notify,
+}
+
+/// Signals the cursors reading from a pool that the pool is being closed, so that they
+/// release their connections rather than keep the pool from closing.
+#[derive(Clone)]
+pub(crate) struct CloseSignal {
+    state: Arc<CloseState>,
+}
+
+impl Default for CloseSignal {
+    fn default() -> Self {
+        CloseSignal {
+            state: Arc::new(CloseState {
+                closed: AtomicBool::new(false),
+                notify: Notify::new(),
+            }),
+        }
+    }
+}
+
+impl CloseSignal {
+    /// Signals that the pool is being closed.
+    pub fn close(&self) {
+        self.state.closed.store(true, Ordering::SeqCst);
+        self.state.notify.notify_waiters();
+    }
+
+    /// Checks if the pool is being closed.
+    pub fn is_closed(&self) -> bool {
+        self.state.closed.load(Ordering::SeqCst)
+    }
+
+    /// Returns a future that resolves once the pool is being closed.
+    pub fn closed(&self) -> impl Future<Output = ()> + Send + 'static {
+        let state = self.state.clone();
+        async move {
+            loop {
+                // Register for the notification before checking the flag, so that a close
+                // between the two is not missed.
+                let notified = state.notify.notified();
+                if state.closed.load(Ordering::SeqCst) {
+                    return;
+                }
+                notified.await;
+            }
         }
     }
 }
+
+/// The pool currently used by a [`SharedPool`], numbered by how many times it has been
+/// replaced.
+struct CurrentPool {
+    pool: Pool,
+    // Signals the cursors of the pool once it is closed.
+    closed: CloseSignal,
+    generation: u64,
+}
+
+/// A connection pool shared by a connection and its executors, which executors replace
+/// with a newly connected pool when the connection has to be re-established.
+///
+/// Clones of a shared pool always use the same current pool.
+#[derive(Clone)]
+pub(crate) struct SharedPool {
+    current: Arc<Mutex<CurrentPool>>,
+    settings: Arc<ConnectionSettings>,
+}
+
+impl SharedPool {
+    /// Creates a new shared pool, which is reconnected with the given settings.
+    pub fn new(pool: Pool, settings: &ConnectionSettings) -> Self {
+        SharedPool {
+            current: Arc::new(Mutex::new(CurrentPool {
+                pool,
+                closed: CloseSignal::default(),
+                generation: 0,
+            })),
+            settings: Arc::new(settings.clone()),
+        }
+    }
+
+    /// Gets the current pool.
+    pub fn get(&self) -> Pool {
+        self.current.lock().unwrap().pool.clone()
+    }
+
+    /// Gets the current pool, along with the signal its cursors release their connections
+    /// on once it is closed.
+    pub fn get_with_close_signal(&self) -> (Pool, CloseSignal) {
+        let current = self.current.lock().unwrap();
+        (current.pool.clone(), current.closed.clone())
+    }
+
+    /// Gets the number of times the pool has been replaced.
+    pub fn generation(&self) -> u64 {
+        self.current.lock().unwrap().generation
+    }
+
+    /// Replaces the pool of the given generation with a newly connected pool, resolving
+    /// the credentials of the settings again.
+    ///
+    /// Does nothing if the pool has already been replaced since, so that executors that
+    /// fail at the same time connect only one new pool between them. The old pool is
+    /// closed in the background once its connections that are still in use are released.
+    pub async fn reconnect(&self, generation: u64) -> Result<(), Error> {
+        let pool = match self.get() {
+            Pool::Mock(pool) => Pool::Mock(MockPool::new(pool.database)),
+            _ => Pool::connect(&self.settings).await?,
+        };
+        let unused = {
+            let mut current = self.current.lock().unwrap();
+            if current.generation == generation {
+                current.generation += 1;
+                current.closed = CloseSignal::default();
+                std::mem::replace(&mut current.pool, pool)
+            } else {
+                pool
+            }
+        };
+        tokio::spawn(async move { unused.close().await });
+        Ok(())
+    }
+
+    /// Closes the current pool.
+    ///
+    /// The cursors of the pool are signalled to release their connections first, since
+    /// closing waits for every connection in use to be released.
+    pub async fn close(&self) {
+        let (pool, closed) = self.get_with_close_signal();
+        closed.close();
+        pool.close().await;
+    }
+}
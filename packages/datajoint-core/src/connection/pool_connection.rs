@@ -0,0 +1,493 @@
+use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::connection::{ExecuteResult, MockDatabase, Pool};
+use crate::error::{DataJointError, Error, ErrorCode, SqlxError};
+use crate::query::{MockQuery, Query};
+use crate::results::{TableColumn, TableColumnRef, TableRow};
+use sqlx::pool::PoolConnection;
+use sqlx::{Describe, Either, Execute, Executor, Statement};
+use std::convert::TryFrom;
+
+/// A wrapper around a single connection acquired from a SQLx connection pool.
+///
+/// The connection returns to the pool when it is dropped.
+pub(crate) enum SqlxConnection {
+    MySql(PoolConnection<sqlx::MySql>),
+    Postgres(PoolConnection<sqlx::Postgres>),
+    Sqlite(PoolConnection<sqlx::Sqlite>),
+    Mock(MockDatabase),
+}
+
+impl DatabaseTypeAgnostic for SqlxConnection {
+    fn database_type(&self) -> DatabaseType {
+        match self {
+            Self::MySql(_) => DatabaseType::MySql,
+            Self::Postgres(_) => DatabaseType::Postgres,
+            Self::Sqlite(_) => DatabaseType::Sqlite,
+            Self::Mock(database) => database.database_type(),
+        }
+    }
+}
+
+impl SqlxConnection {
+    fn wrong_database_type_error() -> Error {
+        DataJointError::new_with_message(
+            "prepared query is for the wrong database type",
+            ErrorCode::WrongDatabaseType,
+        )
+    }
+
+    /// Fetches the identifier of the server session behind the connection, which is
+    /// used to cancel its statements from another connection.
+    ///
+    /// Returns `None` for SQLite and mock databases, which have no server.
+    pub async fn session_id(&mut self) -> Result<Option<u64>, Error> {
+        let result = match self {
+            Self::MySql(connection) => Pool::mysql_session_id(connection).await.map(Some),
+            Self::Postgres(connection) => Pool::postgres_session_id(connection).await.map(Some),
+            Self::Sqlite(_) | Self::Mock(_) => Ok(None),
+        };
+        result.map_err(|error| SqlxError::new(error) as Error)
+    }
+
+    /// Reads the parameter count and result columns of a statement prepared on the server.
+    fn statement_metadata<'q, S, F>(statement: &S, column: F) -> (Option<usize>, Vec<TableColumn>)
+    where
+        S: Statement<'q>,
+        F: Fn(&<S::Database as sqlx::Database>::Column) -> TableColumn,
+    {
+        let parameter_count = statement.parameters().map(|parameters| match parameters {
+            Either::Left(types) => types.len(),
+            Either::Right(count) => count,
+        });
+        (
+            parameter_count,
+            statement.columns().iter().map(column).collect(),
+        )
+    }
+
+    /// Prepares the given query on the server, without running it.
+    ///
+    /// Returns the number of placeholder parameters of the statement, if the database
+    /// reports it, and the columns of the rows it returns. The connection keeps the
+    /// statement prepared, so later queries with the same SQL text reuse it.
+    ///
+    /// Mock databases prepare nothing and report neither.
+    pub async fn prepare(
+        &mut self,
+        query: &str,
+    ) -> Result<(Option<usize>, Vec<TableColumn>), Error> {
+        let result = match self {
+            Self::MySql(connection) => connection.prepare(query).await.map(|statement| {
+                SqlxConnection::statement_metadata(&statement, |column| {
+                    TableColumnRef::MySql(column).to_owned()
+                })
+            }),
+            Self::Postgres(connection) => connection.prepare(query).await.map(|statement| {
+                SqlxConnection::statement_metadata(&statement, |column| {
+                    TableColumnRef::Postgres(column).to_owned()
+                })
+            }),
+            Self::Sqlite(connection) => connection.prepare(query).await.map(|statement| {
+                SqlxConnection::statement_metadata(&statement, |column| {
+                    TableColumnRef::Sqlite(column).to_owned()
+                })
+            }),
+            Self::Mock(_) => Ok((None, Vec::new())),
+        };
+        result.map_err(|error| SqlxError::new(error) as Error)
+    }
+
+    /// Reads the result columns of a described statement, along with their nullability.
+    pub(crate) fn described_columns<DB, F>(describe: &Describe<DB>, column: F) -> Vec<TableColumn>
+    where
+        DB: sqlx::Database,
+        F: Fn(&DB::Column) -> TableColumn,
+    {
+        describe
+            .columns()
+            .iter()
+            .enumerate()
+            .map(|(ordinal, described)| TableColumn {
+                nullable: describe.nullable(ordinal),
+                ..column(described)
+            })
+            .collect()
+    }
+
+    /// Describes the result columns of the given query on the server, without running it.
+    ///
+    /// Mock databases describe no columns.
+    pub async fn describe(&mut self, query: &str) -> Result<Vec<TableColumn>, Error> {
+        let result = match self {
+            Self::MySql(connection) => connection.describe(query).await.map(|describe| {
+                SqlxConnection::described_columns(&describe, |column| {
+                    TableColumnRef::MySql(column).to_owned()
+                })
+            }),
+            Self::Postgres(connection) => connection.describe(query).await.map(|describe| {
+                SqlxConnection::described_columns(&describe, |column| {
+                    TableColumnRef::Postgres(column).to_owned()
+                })
+            }),
+            Self::Sqlite(connection) => connection.describe(query).await.map(|describe| {
+                SqlxConnection::described_columns(&describe, |column| {
+                    TableColumnRef::Sqlite(column).to_owned()
+                })
+            }),
+            Self::Mock(_) => Ok(Vec::new()),
+        };
+        result.map_err(|error| SqlxError::new(error) as Error)
+    }
+
+    /// Runs a Postgres `COPY ... FROM STDIN` statement, sending the chunks of data given by
+    /// the function until it returns `None`.
+    ///
+    /// Returns the number of rows copied. The copy is aborted if the function fails.
+    pub async fn copy_in<F>(&mut self, statement: &str, mut next_chunk: F) -> Result<u64, Error>
+    where
+        F: FnMut() -> Result<Option<Vec<u8>>, Error>,
+    {
+        let connection = match self {
+            Self::Postgres(connection) => connection,
+            _ => {
+                return Err(DataJointError::new_with_message(
+                    "COPY is only supported for Postgres databases",
+                    ErrorCode::WrongDatabaseType,
+                ))
+            }
+        };
+        let mut copy = match connection.copy_in_raw(statement).await {
+            Err(error) => return Err(SqlxError::new(error)),
+            Ok(copy) => copy,
+        };
+        loop {
+            match next_chunk() {
+                Err(error) => {
+                    // The error of the data is reported rather than any error aborting.
+                    let _ = copy.abort(error.message()).await;
+                    return Err(error);
+                }
+                Ok(None) => break,
+                Ok(Some(chunk)) => {
+                    if let Err(error) = copy.send(chunk).await {
+                        return Err(SqlxError::new(error));
+                    }
+                }
+            }
+        }
+        copy.finish()
+            .await
+            .map_err(|error| SqlxError::new(error) as Error)
+    }
+
+    /// Removes the connection from the pool, closing it once it is dropped.
+    ///
+    /// Used for connections whose statement was interrupted, which may still have
+    /// results in flight.
+    pub fn detach(self) {
+        match self {
+            Self::MySql(connection) => drop(connection.detach()),
+            Self::Postgres(connection) => drop(connection.detach()),
+            Self::Sqlite(connection) => drop(connection.detach()),
+            Self::Mock(_) => {}
+        }
+    }
+
+    /// Runs a MySQL statement, then counts the warnings it produced over the same
+    /// connection if `count_warnings` is set.
+    pub(crate) async fn execute_mysql<'q>(
+        connection: &mut sqlx::MySqlConnection,
+        query: sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>,
+        count_warnings: bool,
+    ) -> Result<ExecuteResult, Error> {
+        let result = match connection.execute(query).await {
+            Err(error) => return Err(SqlxError::new(error)),
+            Ok(result) => result,
+        };
+        let warnings = if count_warnings {
+            // Unlike `SELECT @@warning_count`, this diagnostic statement keeps the warnings,
+            // so they can still be shown.
+            let row = match connection.fetch_one("SHOW COUNT(*) WARNINGS").await {
+                Err(error) => return Err(SqlxError::new(error)),
+                Ok(row) => TableRow::MySql(row),
+            };
+            let warnings = row.try_decode(row.try_column(0)?)?.as_i64().unwrap_or(0);
+            Some(warnings.max(0) as u64)
+        } else {
+            None
+        };
+        Ok(ExecuteResult {
+            rows_affected: result.rows_affected(),
+            last_insert_id: match result.last_insert_id() {
+                0 => None,
+                id => i64::try_from(id).ok(),
+            },
+            warnings,
+        })
+    }
+
+    /// Runs a Postgres statement.
+    ///
+    /// The rows returned by an insert with a `RETURNING` clause are fetched to read the
+    /// id of the last inserted row from their first column.
+    pub(crate) async fn execute_postgres<'q>(
+        connection: &mut sqlx::PgConnection,
+        query: sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>,
+    ) -> Result<ExecuteResult, Error> {
+        if !ExecuteResult::is_insert_returning(query.sql()) {
+            return match connection.execute(query).await {
+                Err(error) => Err(SqlxError::new(error)),
+                Ok(result) => Ok(ExecuteResult::with_rows_affected(result.rows_affected())),
+            };
+        }
+        let rows = match connection.fetch_all(query).await {
+            Err(error) => return Err(SqlxError::new(error)),
+            Ok(rows) => rows,
+        };
+        let rows_affected = rows.len() as u64;
+        let last_insert_id = rows.into_iter().last().and_then(|row| {
+            let row = TableRow::Postgres(row);
+            let column = row.try_column(0).ok()?;
+            row.try_decode_optional(column).ok()??.as_i64()
+        });
+        Ok(ExecuteResult {
+            rows_affected,
+            last_insert_id,
+            warnings: None,
+        })
+    }
+
+    /// Runs a SQLite statement, reporting the rowid of the last inserted row for inserts.
+    pub(crate) async fn execute_sqlite<'q>(
+        connection: &mut sqlx::SqliteConnection,
+        query: sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    ) -> Result<ExecuteResult, Error> {
+        // The last inserted rowid is kept by the connection, so it is only reported for
+        // statements that inserted rows.
+        let is_insert = ExecuteResult::is_insert(DatabaseType::Sqlite, query.sql());
+        match connection.execute(query).await {
+            Err(error) => Err(SqlxError::new(error)),
+            Ok(result) => Ok(ExecuteResult {
+                rows_affected: result.rows_affected(),
+                last_insert_id: if is_insert && result.rows_affected() > 0 {
+                    Some(result.last_insert_rowid())
+                } else {
+                    None
+                },
+                warnings: None,
+            }),
+        }
+    }
+
+    /// Fetches the warnings produced by the last statement run over a MySQL connection,
+    /// such as `Warning 1265: Data truncated for column 'name' at row 1`.
+    pub(crate) async fn show_mysql_warnings(
+        connection: &mut sqlx::MySqlConnection,
+    ) -> Result<Vec<String>, Error> {
+        match connection.fetch_all("SHOW WARNINGS").await {
+            Err(error) => Err(SqlxError::new(error)),
+            Ok(rows) => rows
+                .into_iter()
+                .map(|row| SqlxConnection::warning_message(&TableRow::MySql(row)))
+                .collect(),
+        }
+    }
+
+    /// Formats a row returned by `SHOW WARNINGS`, which holds the level, code and message
+    /// of a warning.
+    pub(crate) fn warning_message(row: &TableRow) -> Result<String, Error> {
+        Ok(format!(
+            "{} {}: {}",
+            row.try_decode(row.try_column(0)?)?,
+            row.try_decode(row.try_column(1)?)?,
+            row.try_decode(row.try_column(2)?)?
+        ))
+    }
+
+    /// Fetches the warnings produced by the last statement run over the connection.
+    ///
+    /// Mock databases answer `SHOW WARNINGS` with scripted rows. Other databases do not
+    /// report warnings.
+    pub async fn show_warnings(&mut self) -> Result<Vec<String>, Error> {
+        match self {
+            Self::MySql(connection) => SqlxConnection::show_mysql_warnings(connection).await,
+            Self::Postgres(_) | Self::Sqlite(_) => Ok(Vec::new()),
+            Self::Mock(database) => database
+                .fetch_all(MockQuery::new(database.database_type(), "SHOW WARNINGS"))?
+                .iter()
+                .map(SqlxConnection::warning_message)
+                .collect(),
+        }
+    }
+
+    /// Attempts to execute a non-returning query over the connection.
+    ///
+    /// Returns the number of rows affected by the query, along with the id of an inserted
+    /// row, if the database reports them. MySQL warnings are only counted if
+    /// `count_warnings` is set, which takes another round-trip to the server.
+    pub async fn try_execute<'q>(
+        &mut self,
+        query: Query<'q>,
+        count_warnings: bool,
+    ) -> Result<ExecuteResult, Error> {
+        match self {
+            Self::MySql(connection) => {
+                if let Query::MySql(query) = query {
+                    SqlxConnection::execute_mysql(connection, query, count_warnings).await
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+            Self::Postgres(connection) => {
+                if let Query::Postgres(query) = query {
+                    SqlxConnection::execute_postgres(connection, query).await
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+            Self::Sqlite(connection) => {
+                if let Query::Sqlite(query) = query {
+                    SqlxConnection::execute_sqlite(connection, query).await
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+            Self::Mock(database) => {
+                if let Query::Mock(query) = query {
+                    database.execute(query, count_warnings)
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+        }
+    }
+
+    /// Attempts to execute a returning query over the connection.
+    ///
+    /// Returns a single row returned by the query.
+    pub async fn try_fetch_one<'q>(&mut self, query: Query<'q>) -> Result<TableRow, Error> {
+        match self {
+            Self::MySql(connection) => {
+                if let Query::MySql(query) = query {
+                    match connection.fetch_one(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(row) => Ok(TableRow::MySql(row)),
+                    }
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+            Self::Postgres(connection) => {
+                if let Query::Postgres(query) = query {
+                    match connection.fetch_one(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(row) => Ok(TableRow::Postgres(row)),
+                    }
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+            Self::Sqlite(connection) => {
+                if let Query::Sqlite(query) = query {
+                    match connection.fetch_one(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(row) => Ok(TableRow::Sqlite(row)),
+                    }
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+            Self::Mock(database) => {
+                if let Query::Mock(query) = query {
+                    database.fetch_one(query)
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+        }
+    }
+
+    /// Attempts to execute a returning query over the connection.
+    ///
+    /// Returns all rows returned by the query.
+    pub async fn try_fetch_all<'q>(&mut self, query: Query<'q>) -> Result<Vec<TableRow>, Error> {
+        match self {
+            Self::MySql(connection) => {
+                if let Query::MySql(query) = query {
+                    match connection.fetch_all(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(rows) => Ok(rows.into_iter().map(TableRow::MySql).collect()),
+                    }
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+            Self::Postgres(connection) => {
+                if let Query::Postgres(query) = query {
+                    match connection.fetch_all(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(rows) => Ok(rows.into_iter().map(TableRow::Postgres).collect()),
+                    }
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+            Self::Sqlite(connection) => {
+                if let Query::Sqlite(query) = query {
+                    match connection.fetch_all(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(rows) => Ok(rows.into_iter().map(TableRow::Sqlite).collect()),
+                    }
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+            Self::Mock(database) => {
+                if let Query::Mock(query) = query {
+                    database.fetch_all(query)
+                } else {
+                    Err(SqlxConnection::wrong_database_type_error())
+                }
+            }
+        }
+    }
+}
+
+/// A pooled connection whose statements can be interrupted and stopped on the server.
+pub(crate) struct InterruptibleConnection<'p> {
+    pool: &'p Pool,
+    pub connection: SqlxConnection,
+    // The server session behind the connection, if any.
+    session_id: Option<u64>,
+}
+
+impl<'p> InterruptibleConnection<'p> {
+    /// Acquires a connection from the pool along with its server session.
+    pub async fn acquire(pool: &'p Pool) -> Result<InterruptibleConnection<'p>, Error> {
+        let (connection, session_id) = pool.acquire_with_session_id().await?;
+        Ok(InterruptibleConnection {
+            pool,
+            connection,
+            session_id,
+        })
+    }
+
+    /// Finishes a statement run over the connection, stopping it on the server if it was
+    /// interrupted by a timeout or cancellation.
+    pub async fn finish<T>(self, result: Result<T, Error>) -> Result<T, Error> {
+        let interrupted = match &result {
+            Err(error) => matches!(
+                error.code(),
+                ErrorCode::QueryCancelled | ErrorCode::QueryTimedOut
+            ),
+            Ok(_) => false,
+        };
+        if let (true, Some(session_id)) = (interrupted, self.session_id) {
+            // The connection may still have results in flight, so it is removed from the pool.
+            self.connection.detach();
+            // The statement already failed, so failing to stop it is not reported.
+            let _ = self.pool.cancel_session(session_id).await;
+        }
+        result
+    }
+}
@@ -0,0 +1,114 @@
+use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::connection::{AsyncPreparedStatement, Cursor, ExecuteResult};
+use crate::error::Error;
+use crate::placeholders::PlaceholderArgumentCollection;
+use crate::results::{TableColumn, TableRow};
+
+/// A statement prepared on the database server, which can be run any number of times
+/// with different placeholder arguments.
+///
+/// Instances of `PreparedStatement` should not be created manually but by calling
+/// [`prepare()`][crate::connection::Executor::prepare] on an
+/// [`Executor`][crate::connection::Executor] instance.
+///
+/// A server-side statement belongs to a single pooled connection, so reusing it depends
+/// on the statement cache of the database driver, which keeps up to 100 recently run
+/// statements for every pooled connection. Connections that never ran the statement, or
+/// evicted it, prepare it again.
+pub struct PreparedStatement<'c> {
+    statement: AsyncPreparedStatement<'c>,
+    runtime: &'static tokio::runtime::Runtime,
+}
+
+impl<'c> DatabaseTypeAgnostic for PreparedStatement<'c> {
+    fn database_type(&self) -> DatabaseType {
+        self.statement.database_type()
+    }
+}
+
+impl<'c> PreparedStatement<'c> {
+    /// Creates a new prepared statement that runs the given asynchronous statement to
+    /// completion.
+    pub(crate) fn new(
+        statement: AsyncPreparedStatement<'c>,
+        runtime: &'static tokio::runtime::Runtime,
+    ) -> Self {
+        PreparedStatement { statement, runtime }
+    }
+
+    /// Returns the SQL text of the statement.
+    pub fn sql(&self) -> &str {
+        self.statement.sql()
+    }
+
+    /// Returns the number of placeholder parameters of the statement, or `None` if the
+    /// database does not report it.
+    pub fn parameter_count(&self) -> Option<usize> {
+        self.statement.parameter_count()
+    }
+
+    /// Returns the columns of the rows returned by the statement, which is empty for
+    /// non-returning statements.
+    pub fn columns(&self) -> &[TableColumn] {
+        self.statement.columns()
+    }
+
+    /// Executes the statement, binding the placeholder arguments.
+    ///
+    /// Panics on error.
+    pub fn execute(&self, args: impl PlaceholderArgumentCollection) -> ExecuteResult {
+        self.try_execute(args).unwrap()
+    }
+
+    /// Executes the statement, binding the placeholder arguments.
+    ///
+    /// Returns the number of rows affected by the statement, along with the id of an
+    /// inserted row and the number of warnings, if the database reports them.
+    pub fn try_execute(
+        &self,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<ExecuteResult, Error> {
+        self.runtime.block_on(self.statement.try_execute(args))
+    }
+
+    /// Fetches one row using the statement, binding the placeholder arguments.
+    ///
+    /// Panics on error.
+    pub fn fetch_one(&self, args: impl PlaceholderArgumentCollection) -> TableRow {
+        self.try_fetch_one(args).unwrap()
+    }
+
+    /// Fetches one row using the statement, binding the placeholder arguments.
+    pub fn try_fetch_one(
+        &self,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<TableRow, Error> {
+        self.runtime.block_on(self.statement.try_fetch_one(args))
+    }
+
+    /// Fetches multiple rows using the statement, binding the placeholder arguments.
+    ///
+    /// Panics on error.
+    pub fn fetch_all(&self, args: impl PlaceholderArgumentCollection) -> Vec<TableRow> {
+        self.try_fetch_all(args).unwrap()
+    }
+
+    /// Fetches multiple rows using the statement, binding the placeholder arguments.
+    pub fn try_fetch_all(
+        &self,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<Vec<TableRow>, Error> {
+        self.runtime.block_on(self.statement.try_fetch_all(args))
+    }
+
+    /// Creates a cursor for the statement, binding the placeholder arguments.
+    pub fn cursor(
+        &self,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<Cursor<'static>, Error> {
+        Ok(Cursor::new(
+            self.runtime.block_on(self.statement.cursor(args))?,
+            self.runtime,
+        ))
+    }
+}
@@ -0,0 +1,187 @@
+use crate::error::{Error, ErrorCode};
+use std::time::Duration;
+
+/// Policy for transparently recovering from a lost database connection.
+///
+/// When a policy is set on [`ConnectionSettings`][crate::connection::ConnectionSettings],
+/// executors retry statements that fail because the connection to the server was lost.
+/// The connection pool replaces broken connections with new ones, so a retried statement
+/// runs on a freshly established connection. Statements that fail because the pool was
+/// closed, or because the server rejected the login of a new connection, are retried on a
+/// newly connected pool, which the connection keeps using. The new pool resolves the
+/// [`credentials`][crate::connection::ConnectionSettings::credentials] again.
+///
+/// Statements that time out waiting for a pooled connection are not retried, since every
+/// connection of the pool is busy rather than lost.
+///
+/// Only statements that are safe to run twice are retried after they may have reached
+/// the server. Statements inside of a [`Transaction`][crate::connection::Transaction]
+/// are never retried.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Maximum number of times a single statement is retried.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between retries, which doubles after every attempt.
+    pub max_backoff: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Returns the delay to wait before the given retry attempt, starting at zero.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.checked_pow(attempt).unwrap_or(u32::MAX);
+        match self.initial_backoff.checked_mul(factor) {
+            None => self.max_backoff,
+            Some(backoff) => backoff.min(self.max_backoff),
+        }
+    }
+
+    /// Checks if a statement that failed with the given error should be retried.
+    ///
+    /// `idempotent` tells if the statement can safely run more than once.
+    pub(crate) fn should_retry(&self, attempt: u32, error: &Error, idempotent: bool) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+        match error.code() {
+            // The statement never left the client, so any statement can be retried.
+            ErrorCode::PoolClosed => true,
+            ErrorCode::IoError | ErrorCode::WorkerCrashed => idempotent,
+            // Logging in fails before the statement is sent, so any statement can be
+            // retried once the pool has been replaced.
+            ErrorCode::UnknownDatabaseError => {
+                is_authentication_failure(error) || (idempotent && is_connection_lost_state(error))
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks if the pool has to be replaced with a newly connected pool before a
+    /// statement that failed with the given error is retried.
+    ///
+    /// A rejected login may come from an expired password, so the new pool resolves the
+    /// credentials of the settings again.
+    pub(crate) fn should_reconnect(error: &Error) -> bool {
+        match error.code() {
+            ErrorCode::PoolClosed => true,
+            ErrorCode::UnknownDatabaseError => is_authentication_failure(error),
+            _ => false,
+        }
+    }
+}
+
+/// Checks if the SQLSTATE of a database error reports that logging in was rejected.
+fn is_authentication_failure(error: &Error) -> bool {
+    // Invalid authorization specification, and the Postgres invalid password.
+    matches!(error.sql_state().as_deref(), Some("28000") | Some("28P01"))
+}
+
+/// Checks if the SQLSTATE of a database error reports that the session was terminated.
+fn is_connection_lost_state(error: &Error) -> bool {
+    match error.sql_state() {
+        None => false,
+        Some(state) => matches!(
+            &*state,
+            // Connection exceptions.
+            "08000" | "08003" | "08006" | "08S01"
+            // Postgres admin shutdown, crash shutdown and server start up.
+            | "57P01" | "57P02" | "57P03"
+        ),
+    }
+}
+
+/// Checks if the given SQL statement only reads data, so running it again after a
+/// lost connection has no side effects.
+pub(crate) fn is_idempotent(query: &str) -> bool {
+    let keyword: String = skip_comments(query)
+        .trim_start_matches('(')
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    matches!(
+        &*keyword.to_ascii_lowercase(),
+        "select" | "show" | "describe" | "desc" | "explain"
+    )
+}
+
+/// Skips leading whitespace and SQL comments.
+fn skip_comments(mut query: &str) -> &str {
+    loop {
+        query = query.trim_start();
+        if let Some(rest) = query.strip_prefix("--") {
+            query = rest.find('\n').map_or("", |end| &rest[end..]);
+        } else if let Some(rest) = query.strip_prefix('#') {
+            query = rest.find('\n').map_or("", |end| &rest[end..]);
+        } else if let Some(rest) = query.strip_prefix("/*") {
+            query = rest.find("*/").map_or("", |end| &rest[end + 2..]);
+        } else {
+            return query;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DataJointError;
+
+    fn error(code: ErrorCode) -> Error {
+        DataJointError::new(code)
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = ReconnectPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(4), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(40), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_should_retry() {
+        let policy = ReconnectPolicy::default();
+        let io_error = error(ErrorCode::IoError);
+        let timed_out = error(ErrorCode::PoolTimedOut);
+        let closed = error(ErrorCode::PoolClosed);
+        let no_rows = error(ErrorCode::RowNotFound);
+
+        assert!(policy.should_retry(0, &io_error, true));
+        assert!(!policy.should_retry(0, &io_error, false));
+        assert!(!policy.should_retry(0, &timed_out, true));
+        assert!(policy.should_retry(0, &closed, false));
+        assert!(ReconnectPolicy::should_reconnect(&closed));
+        assert!(!ReconnectPolicy::should_reconnect(&io_error));
+        assert!(!policy.should_retry(0, &no_rows, true));
+        assert!(!policy.should_retry(policy.max_attempts, &io_error, true));
+    }
+
+    #[test]
+    fn test_is_idempotent() {
+        assert!(is_idempotent("select * from tweet"));
+        assert!(is_idempotent("  SELECT 1"));
+        assert!(is_idempotent("(select 1) union (select 2)"));
+        assert!(is_idempotent("-- comment\n/* block */ show tables"));
+        assert!(is_idempotent("# mysql comment\ndescribe tweet"));
+        assert!(!is_idempotent("insert into tweet (text) values ('select')"));
+        assert!(!is_idempotent("/* select */ delete from tweet"));
+        assert!(!is_idempotent("selectx"));
+        assert!(!is_idempotent(""));
+    }
+}
@@ -0,0 +1,17 @@
+use std::sync::OnceLock;
+use tokio::runtime::{Builder, Runtime};
+
+/// Returns the runtime that runs the queries of every blocking connection.
+///
+/// The runtime is created on first use and lives for the rest of the process. It uses
+/// worker threads, so any number of threads can block on it at the same time.
+pub(crate) fn shared_runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("datajoint-core")
+            .build()
+            .unwrap()
+    })
+}
@@ -0,0 +1,85 @@
+use crate::error::{DataJointError, Error};
+
+/// The outcome of running a SQL script statement by statement.
+///
+/// Statements run in order until one of them fails, and the statements after it are not
+/// run.
+#[derive(Debug)]
+pub struct ScriptResult {
+    statements: Vec<String>,
+    rows_affected: Vec<u64>,
+    error: Option<Error>,
+    in_transaction: bool,
+}
+
+impl ScriptResult {
+    /// Creates the result of a script split into the given statements.
+    pub(crate) fn new(statements: Vec<String>, in_transaction: bool) -> Self {
+        ScriptResult {
+            rows_affected: Vec::with_capacity(statements.len()),
+            statements,
+            error: None,
+            in_transaction,
+        }
+    }
+
+    /// Records the number of rows affected by the next statement.
+    pub(crate) fn push_rows_affected(&mut self, rows_affected: u64) {
+        self.rows_affected.push(rows_affected);
+    }
+
+    /// Records the error of the next statement, which ends the script.
+    pub(crate) fn fail(&mut self, error: Error) {
+        self.error = Some(error);
+    }
+
+    /// Returns the statements the script was split into, in order.
+    pub fn statements(&self) -> &[String] {
+        &self.statements
+    }
+
+    /// Returns the number of rows affected by each statement that succeeded, in order.
+    ///
+    /// If the script ran in a transaction that was rolled back, these statements no
+    /// longer have any effect.
+    pub fn rows_affected(&self) -> &[u64] {
+        &self.rows_affected
+    }
+
+    /// Returns the index of the statement that failed, if any.
+    pub fn failed_statement(&self) -> Option<usize> {
+        self.error.as_ref().map(|_| self.rows_affected.len())
+    }
+
+    /// Returns the error of the statement that failed, if any.
+    pub fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    /// Checks if every statement of the script succeeded.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Checks if the script ran inside of a single transaction, which was rolled back if
+    /// a statement failed.
+    pub fn in_transaction(&self) -> bool {
+        self.in_transaction
+    }
+
+    /// Converts the outcome into the number of rows affected by each statement, or the
+    /// error of the statement that failed, whose message is prefixed with its index.
+    pub fn into_result(self) -> Result<Vec<u64>, Error> {
+        match self.error {
+            None => Ok(self.rows_affected),
+            Some(error) => Err(DataJointError::new_with_message(
+                &format!(
+                    "statement {} of script failed: {}",
+                    self.rows_affected.len(),
+                    error.message()
+                ),
+                error.code(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crate::common::DatabaseType;
+use crate::error::{DataJointError, Error, ErrorCode};
+use crate::results::TableRow;
+use crate::types::NativeType;
+
+/// Information about the database server behind a connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerInfo {
+    /// The database backend of the server.
+    pub database_type: DatabaseType,
+    /// The full version string reported by the server, such as `8.0.30` or
+    /// `13.4 (Debian 13.4-1.pgdg100+1)`.
+    pub version: String,
+    /// The major version of the server, such as `8` for MySQL 8.0.
+    pub major_version: u32,
+    /// The minor version of the server, such as `0` for MySQL 8.0.
+    pub minor_version: u32,
+    /// The user the session is authenticated as, or `None` for SQLite.
+    pub current_user: Option<String>,
+    /// The current database of the session, or `None` if no database is selected.
+    pub current_database: Option<String>,
+}
+
+impl ServerInfo {
+    /// Returns the query that reads the version, current user and current database of
+    /// the given database type, in that order.
+    pub(crate) fn query(database_type: DatabaseType) -> &'static str {
+        match database_type {
+            DatabaseType::MySql => "SELECT version(), current_user(), database()",
+            DatabaseType::Postgres => {
+                "SELECT current_setting('server_version'), current_user::text, current_database()::text"
+            }
+            DatabaseType::Sqlite => "SELECT sqlite_version(), NULL, 'main'",
+        }
+    }
+
+    /// Builds the server information from the row returned by [`ServerInfo::query`].
+    pub(crate) fn from_row(database_type: DatabaseType, row: &TableRow) -> Result<Self, Error> {
+        let version = match ServerInfo::try_get_text(row, 0)? {
+            None => return Err(DataJointError::new(ErrorCode::UnexpectedNullValue)),
+            Some(version) => version,
+        };
+        let (major_version, minor_version) = ServerInfo::parse_version(&version);
+        Ok(ServerInfo {
+            database_type,
+            version,
+            major_version,
+            minor_version,
+            current_user: ServerInfo::try_get_text(row, 1)?,
+            current_database: ServerInfo::try_get_text(row, 2)?,
+        })
+    }
+
+    /// Reads a text column of the row, which may be null.
+    ///
+    /// Owned rows, such as rows scripted by a mock database, hold decoded values.
+    fn try_get_text(row: &TableRow, index: usize) -> Result<Option<String>, Error> {
+        if let TableRow::Owned(_) = row {
+            return match row.try_decode_optional(row.try_column(index)?)? {
+                None => Ok(None),
+                Some(NativeType::String(text)) => Ok(Some(text)),
+                Some(_) => Err(DataJointError::new_with_message(
+                    "expected a text column",
+                    ErrorCode::ColumnDecodeError,
+                )),
+            };
+        }
+        row.try_get(index)
+    }
+
+    /// Parses the major and minor version from the start of a version string.
+    ///
+    /// Missing components are parsed as `0`.
+    fn parse_version(version: &str) -> (u32, u32) {
+        let mut components = version
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|component| component.parse().unwrap_or(0));
+        let major = components.next().unwrap_or(0);
+        // Only components joined by dots belong to the version number.
+        let minor = if version
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .starts_with('.')
+        {
+            components.next().unwrap_or(0)
+        } else {
+            0
+        };
+        (major, minor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(ServerInfo::parse_version("5.6.51"), (5, 6));
+        assert_eq!(ServerInfo::parse_version("8.0.30-log"), (8, 0));
+        assert_eq!(
+            ServerInfo::parse_version("10.6.4-MariaDB-1:10.6.4"),
+            (10, 6)
+        );
+        assert_eq!(
+            ServerInfo::parse_version("13.4 (Debian 13.4-1.pgdg100+1)"),
+            (13, 4)
+        );
+        assert_eq!(ServerInfo::parse_version("15beta1"), (15, 0));
+        assert_eq!(ServerInfo::parse_version("3.38.5"), (3, 38));
+        assert_eq!(ServerInfo::parse_version(""), (0, 0));
+    }
+}
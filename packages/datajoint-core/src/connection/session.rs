@@ -0,0 +1,193 @@
+use crate::common::DatabaseType;
+use crate::error::{DataJointError, Error, ErrorCode};
+
+/// Settings for the session of every connection opened to the database.
+///
+/// The resulting statements run on every physical connection the pool opens, including
+/// connections that replace broken ones, so all pooled sessions are set up the same way.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SessionSettings {
+    /// Character set of the client, such as `utf8mb4` for MySQL or `UTF8` for Postgres.
+    pub charset: Option<String>,
+    /// Time zone of the session, such as `+00:00` for MySQL or `UTC` for Postgres.
+    pub time_zone: Option<String>,
+    /// The MySQL `sql_mode` of the session.
+    pub sql_mode: Option<String>,
+    /// The Postgres `search_path` of the session, as a comma-separated list of schemas.
+    pub search_path: Option<String>,
+    /// Statements run after the session options are applied, in order.
+    pub init_statements: Vec<String>,
+}
+
+impl SessionSettings {
+    /// Checks if no session options or init statements are set.
+    pub fn is_empty(&self) -> bool {
+        *self == SessionSettings::default()
+    }
+
+    fn unsupported_option_error(option: &str, database_type: DatabaseType) -> Error {
+        DataJointError::new_with_message(
+            &format!(
+                "{} is not supported for {:?} sessions",
+                option, database_type
+            ),
+            ErrorCode::ConfigurationError,
+        )
+    }
+
+    /// Quotes a value as a SQL string literal.
+    fn quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    /// Returns the statements that set up a session on the given database type.
+    ///
+    /// Fails if an option is set that the database type does not support.
+    pub(crate) fn statements(&self, database_type: DatabaseType) -> Result<Vec<String>, Error> {
+        let mut statements = Vec::new();
+        match database_type {
+            DatabaseType::MySql => {
+                if let Some(charset) = &self.charset {
+                    statements.push(format!("SET NAMES {}", SessionSettings::quote(charset)));
+                }
+                if let Some(time_zone) = &self.time_zone {
+                    statements.push(format!(
+                        "SET time_zone = {}",
+                        SessionSettings::quote(time_zone)
+                    ));
+                }
+                if let Some(sql_mode) = &self.sql_mode {
+                    statements.push(format!(
+                        "SET sql_mode = {}",
+                        SessionSettings::quote(sql_mode)
+                    ));
+                }
+                if self.search_path.is_some() {
+                    return Err(SessionSettings::unsupported_option_error(
+                        "search_path",
+                        database_type,
+                    ));
+                }
+            }
+            DatabaseType::Postgres => {
+                if let Some(charset) = &self.charset {
+                    statements.push(format!(
+                        "SET client_encoding TO {}",
+                        SessionSettings::quote(charset)
+                    ));
+                }
+                if let Some(time_zone) = &self.time_zone {
+                    statements.push(format!(
+                        "SET TIME ZONE {}",
+                        SessionSettings::quote(time_zone)
+                    ));
+                }
+                if self.sql_mode.is_some() {
+                    return Err(SessionSettings::unsupported_option_error(
+                        "sql_mode",
+                        database_type,
+                    ));
+                }
+                if let Some(search_path) = &self.search_path {
+                    // The search path is a list of identifiers rather than a string literal.
+                    statements.push(format!("SET search_path TO {}", search_path));
+                }
+            }
+            DatabaseType::Sqlite => {
+                let options = [
+                    ("charset", &self.charset),
+                    ("time_zone", &self.time_zone),
+                    ("sql_mode", &self.sql_mode),
+                    ("search_path", &self.search_path),
+                ];
+                if let Some((option, _)) = options.iter().find(|(_, value)| value.is_some()) {
+                    return Err(SessionSettings::unsupported_option_error(
+                        option,
+                        database_type,
+                    ));
+                }
+            }
+        }
+        statements.extend(self.init_statements.iter().cloned());
+        Ok(statements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_statements() {
+        let session = SessionSettings {
+            charset: Some("utf8mb4".to_string()),
+            time_zone: Some("+00:00".to_string()),
+            sql_mode: Some("ANSI_QUOTES,STRICT_ALL_TABLES".to_string()),
+            search_path: None,
+            init_statements: vec!["SET autocommit = 1".to_string()],
+        };
+        assert_eq!(
+            session.statements(DatabaseType::MySql).unwrap(),
+            vec![
+                "SET NAMES 'utf8mb4'",
+                "SET time_zone = '+00:00'",
+                "SET sql_mode = 'ANSI_QUOTES,STRICT_ALL_TABLES'",
+                "SET autocommit = 1",
+            ]
+        );
+        assert_eq!(
+            session
+                .statements(DatabaseType::Postgres)
+                .unwrap_err()
+                .code(),
+            ErrorCode::ConfigurationError
+        );
+
+        let session = SessionSettings {
+            charset: Some("UTF8".to_string()),
+            time_zone: Some("America/Chicago".to_string()),
+            search_path: Some("lab, public".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            session.statements(DatabaseType::Postgres).unwrap(),
+            vec![
+                "SET client_encoding TO 'UTF8'",
+                "SET TIME ZONE 'America/Chicago'",
+                "SET search_path TO lab, public",
+            ]
+        );
+        assert_eq!(
+            session.statements(DatabaseType::MySql).unwrap_err().code(),
+            ErrorCode::ConfigurationError
+        );
+        assert_eq!(
+            session.statements(DatabaseType::Sqlite).unwrap_err().code(),
+            ErrorCode::ConfigurationError
+        );
+
+        let session = SessionSettings {
+            time_zone: Some("it's".to_string()),
+            init_statements: vec!["PRAGMA foreign_keys = ON".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            session.statements(DatabaseType::MySql).unwrap()[0],
+            "SET time_zone = 'it''s'"
+        );
+        assert_eq!(
+            session.statements(DatabaseType::Sqlite).unwrap_err().code(),
+            ErrorCode::ConfigurationError
+        );
+        let session = SessionSettings {
+            init_statements: vec!["PRAGMA foreign_keys = ON".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            session.statements(DatabaseType::Sqlite).unwrap(),
+            vec!["PRAGMA foreign_keys = ON"]
+        );
+        assert!(SessionSettings::default().is_empty());
+        assert!(!session.is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::common::DatabaseType;
+use std::path::PathBuf;
+
+/// Enum type for representing how strictly a connection uses TLS.
+///
+/// Mirrors the `ssl-mode` option of MySQL clients, which is mapped onto the
+/// `sslmode` option of Postgres clients.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, FromPrimitive)]
+#[repr(i32)]
+pub enum TlsMode {
+    /// Never use TLS.
+    Disabled,
+    /// Use TLS if the server supports it, without verifying the server certificate.
+    #[default]
+    Preferred,
+    /// Always use TLS, without verifying the server certificate.
+    Required,
+    /// Always use TLS and verify that the server certificate is signed by a trusted CA.
+    VerifyCa,
+    /// Always use TLS, verify the server certificate, and verify that the certificate
+    /// matches the hostname.
+    VerifyIdentity,
+}
+
+impl From<Option<bool>> for TlsMode {
+    /// Converts the DataJoint Python `use_tls` option to a TLS mode.
+    fn from(use_tls: Option<bool>) -> Self {
+        match use_tls {
+            None => TlsMode::Preferred,
+            Some(true) => TlsMode::Required,
+            Some(false) => TlsMode::Disabled,
+        }
+    }
+}
+
+impl TlsMode {
+    /// Converts the TLS mode to the DataJoint Python `use_tls` option.
+    pub fn use_tls(&self) -> Option<bool> {
+        match self {
+            TlsMode::Disabled => Some(false),
+            TlsMode::Preferred => None,
+            _ => Some(true),
+        }
+    }
+
+    /// Returns the value of the TLS mode in a connection URI for the given database type.
+    ///
+    /// Returns `None` for database types that do not connect over the network.
+    pub(crate) fn uri_value(&self, database_type: DatabaseType) -> Option<&'static str> {
+        Some(match database_type {
+            DatabaseType::MySql => match self {
+                TlsMode::Disabled => "disabled",
+                TlsMode::Preferred => "preferred",
+                TlsMode::Required => "required",
+                TlsMode::VerifyCa => "verify_ca",
+                TlsMode::VerifyIdentity => "verify_identity",
+            },
+            DatabaseType::Postgres => match self {
+                TlsMode::Disabled => "disable",
+                TlsMode::Preferred => "prefer",
+                TlsMode::Required => "require",
+                TlsMode::VerifyCa => "verify-ca",
+                TlsMode::VerifyIdentity => "verify-full",
+            },
+            DatabaseType::Sqlite => return None,
+        })
+    }
+
+    /// Parses the value of a TLS mode in a connection URI.
+    ///
+    /// Both the MySQL and the Postgres spellings are accepted, ignoring case.
+    pub(crate) fn from_uri_value(value: &str) -> Option<Self> {
+        match &*value.to_ascii_lowercase().replace('-', "_") {
+            "disabled" | "disable" => Some(TlsMode::Disabled),
+            "preferred" | "prefer" | "allow" => Some(TlsMode::Preferred),
+            "required" | "require" => Some(TlsMode::Required),
+            "verify_ca" => Some(TlsMode::VerifyCa),
+            "verify_identity" | "verify_full" => Some(TlsMode::VerifyIdentity),
+            _ => None,
+        }
+    }
+}
+
+/// Settings for securing a connection with TLS.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TlsSettings {
+    /// How strictly the connection uses TLS.
+    pub mode: TlsMode,
+    /// Path to a PEM file of CA certificates to trust when verifying the server certificate.
+    ///
+    /// `None` uses the trusted CA certificates of the system.
+    pub ca_path: Option<PathBuf>,
+}
+
+impl TlsSettings {
+    /// Creates new TLS settings with the given mode and no CA path.
+    pub fn new(mode: TlsMode) -> Self {
+        TlsSettings {
+            mode,
+            ..Default::default()
+        }
+    }
+}
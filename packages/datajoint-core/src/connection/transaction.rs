@@ -0,0 +1,574 @@
+use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::connection::observer::{finish_observed, ObservedQuery};
+use crate::connection::{
+    AsyncCursor, Cursor, ExecuteResult, MockDatabase, QueryObserver, SqlxConnection,
+};
+use crate::error::{DataJointError, Error, ErrorCode, SqlxError};
+use crate::placeholders::{PlaceholderArgumentCollection, PlaceholderArgumentVector};
+use crate::query::{MockQuery, Query};
+use crate::results::{TableColumn, TableColumnRef, TableRow};
+use sqlx::Executor;
+use std::sync::Arc;
+
+/// A wrapper around an open SQLx transaction, which owns a single pooled connection.
+pub(crate) enum SqlxTransaction {
+    MySql(sqlx::Transaction<'static, sqlx::MySql>),
+    Postgres(sqlx::Transaction<'static, sqlx::Postgres>),
+    Sqlite(sqlx::Transaction<'static, sqlx::Sqlite>),
+    Mock(MockDatabase),
+}
+
+impl DatabaseTypeAgnostic for SqlxTransaction {
+    fn database_type(&self) -> DatabaseType {
+        match self {
+            Self::MySql(_) => DatabaseType::MySql,
+            Self::Postgres(_) => DatabaseType::Postgres,
+            Self::Sqlite(_) => DatabaseType::Sqlite,
+            Self::Mock(database) => database.database_type(),
+        }
+    }
+}
+
+impl SqlxTransaction {
+    fn wrong_database_type_error() -> Error {
+        DataJointError::new_with_message(
+            "prepared query is for the wrong database type",
+            ErrorCode::WrongDatabaseType,
+        )
+    }
+
+    /// Creates a query with the given string for the database behind the transaction.
+    pub fn query<'q>(&self, query: &'q str) -> Query<'q> {
+        match self {
+            Self::Mock(_) => Query::Mock(MockQuery::new(self.database_type(), query)),
+            _ => Query::new(self.database_type(), query),
+        }
+    }
+
+    /// Describes the result columns of the given query inside of the transaction, without
+    /// running it.
+    pub async fn describe(&mut self, query: &str) -> Result<Vec<TableColumn>, Error> {
+        let result = match self {
+            Self::MySql(transaction) => transaction.describe(query).await.map(|describe| {
+                SqlxConnection::described_columns(&describe, |column| {
+                    TableColumnRef::MySql(column).to_owned()
+                })
+            }),
+            Self::Postgres(transaction) => transaction.describe(query).await.map(|describe| {
+                SqlxConnection::described_columns(&describe, |column| {
+                    TableColumnRef::Postgres(column).to_owned()
+                })
+            }),
+            Self::Sqlite(transaction) => transaction.describe(query).await.map(|describe| {
+                SqlxConnection::described_columns(&describe, |column| {
+                    TableColumnRef::Sqlite(column).to_owned()
+                })
+            }),
+            Self::Mock(_) => Ok(Vec::new()),
+        };
+        result.map_err(|error| SqlxError::new(error) as Error)
+    }
+
+    /// Attempts to execute a non-returning query inside of the transaction.
+    ///
+    /// Returns the number of rows affected by the query, along with the id of an inserted
+    /// row, if the database reports them. MySQL warnings are only counted if
+    /// `count_warnings` is set, which takes another round-trip to the server.
+    pub async fn try_execute<'q>(
+        &mut self,
+        query: Query<'q>,
+        count_warnings: bool,
+    ) -> Result<ExecuteResult, Error> {
+        match self {
+            Self::MySql(transaction) => {
+                if let Query::MySql(query) = query {
+                    SqlxConnection::execute_mysql(transaction, query, count_warnings).await
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+            Self::Postgres(transaction) => {
+                if let Query::Postgres(query) = query {
+                    SqlxConnection::execute_postgres(transaction, query).await
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+            Self::Sqlite(transaction) => {
+                if let Query::Sqlite(query) = query {
+                    SqlxConnection::execute_sqlite(transaction, query).await
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+            Self::Mock(database) => {
+                if let Query::Mock(query) = query {
+                    database.execute(query, count_warnings)
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+        }
+    }
+
+    /// Fetches the warnings produced by the last statement run inside of the transaction.
+    pub async fn show_warnings(&mut self) -> Result<Vec<String>, Error> {
+        match self {
+            Self::MySql(transaction) => SqlxConnection::show_mysql_warnings(transaction).await,
+            Self::Postgres(_) | Self::Sqlite(_) => Ok(Vec::new()),
+            Self::Mock(database) => database
+                .fetch_all(MockQuery::new(database.database_type(), "SHOW WARNINGS"))?
+                .iter()
+                .map(SqlxConnection::warning_message)
+                .collect(),
+        }
+    }
+
+    /// Attempts to execute a returning query inside of the transaction.
+    ///
+    /// Returns a single row returned by the query.
+    pub async fn try_fetch_one<'q>(&mut self, query: Query<'q>) -> Result<TableRow, Error> {
+        match self {
+            Self::MySql(transaction) => {
+                if let Query::MySql(query) = query {
+                    match transaction.fetch_one(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(row) => Ok(TableRow::MySql(row)),
+                    }
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+            Self::Postgres(transaction) => {
+                if let Query::Postgres(query) = query {
+                    match transaction.fetch_one(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(row) => Ok(TableRow::Postgres(row)),
+                    }
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+            Self::Sqlite(transaction) => {
+                if let Query::Sqlite(query) = query {
+                    match transaction.fetch_one(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(row) => Ok(TableRow::Sqlite(row)),
+                    }
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+            Self::Mock(database) => {
+                if let Query::Mock(query) = query {
+                    database.fetch_one(query)
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+        }
+    }
+
+    /// Attempts to execute a returning query inside of the transaction.
+    ///
+    /// Returns all rows returned by the query.
+    pub async fn try_fetch_all<'q>(&mut self, query: Query<'q>) -> Result<Vec<TableRow>, Error> {
+        match self {
+            Self::MySql(transaction) => {
+                if let Query::MySql(query) = query {
+                    match transaction.fetch_all(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(rows) => Ok(rows.into_iter().map(TableRow::MySql).collect()),
+                    }
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+            Self::Postgres(transaction) => {
+                if let Query::Postgres(query) = query {
+                    match transaction.fetch_all(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(rows) => Ok(rows.into_iter().map(TableRow::Postgres).collect()),
+                    }
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+            Self::Sqlite(transaction) => {
+                if let Query::Sqlite(query) = query {
+                    match transaction.fetch_all(query).await {
+                        Err(error) => Err(SqlxError::new(error)),
+                        Ok(rows) => Ok(rows.into_iter().map(TableRow::Sqlite).collect()),
+                    }
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+            Self::Mock(database) => {
+                if let Query::Mock(query) = query {
+                    database.fetch_all(query)
+                } else {
+                    Err(SqlxTransaction::wrong_database_type_error())
+                }
+            }
+        }
+    }
+
+    /// Commits the transaction.
+    pub async fn commit(self) -> Result<(), Error> {
+        match match self {
+            Self::MySql(transaction) => transaction.commit().await,
+            Self::Postgres(transaction) => transaction.commit().await,
+            Self::Sqlite(transaction) => transaction.commit().await,
+            Self::Mock(database) => {
+                database.record("COMMIT");
+                Ok(())
+            }
+        } {
+            Err(error) => Err(SqlxError::new(error)),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    /// Rolls back the transaction.
+    pub async fn rollback(self) -> Result<(), Error> {
+        match match self {
+            Self::MySql(transaction) => transaction.rollback().await,
+            Self::Postgres(transaction) => transaction.rollback().await,
+            Self::Sqlite(transaction) => transaction.rollback().await,
+            Self::Mock(database) => {
+                database.record("ROLLBACK");
+                Ok(())
+            }
+        } {
+            Err(error) => Err(SqlxError::new(error)),
+            Ok(_) => Ok(()),
+        }
+    }
+}
+
+/// A database transaction, which runs every query on the same physical connection
+/// until it is committed or rolled back.
+///
+/// Instances of `Transaction` should not be created manually but by calling
+/// [`begin()`][crate::connection::Connection::begin] on a
+/// [`Connection`][crate::connection::Connection] instance.
+///
+/// If a transaction is dropped before it is committed, it is rolled back.
+pub struct Transaction<'c> {
+    // Always `Some` until the transaction is committed or rolled back.
+    transaction: Option<SqlxTransaction>,
+    runtime: &'c tokio::runtime::Runtime,
+    savepoints: usize,
+    observer: Option<Arc<dyn QueryObserver>>,
+}
+
+impl<'c> DatabaseTypeAgnostic for Transaction<'c> {
+    fn database_type(&self) -> DatabaseType {
+        self.transaction.as_ref().unwrap().database_type()
+    }
+}
+
+impl<'c> Transaction<'c> {
+    /// Creates a new transaction over an open SQLx transaction.
+    pub(crate) fn new(
+        transaction: SqlxTransaction,
+        runtime: &'c tokio::runtime::Runtime,
+        observer: Option<Arc<dyn QueryObserver>>,
+    ) -> Self {
+        Transaction {
+            transaction: Some(transaction),
+            runtime,
+            savepoints: 0,
+            observer,
+        }
+    }
+
+    pub(crate) fn sqlx_transaction(&mut self) -> &mut SqlxTransaction {
+        self.transaction.as_mut().unwrap()
+    }
+
+    /// Executes the given query inside of the transaction.
+    ///
+    /// Panics on error.
+    pub fn execute(&mut self, query: &str) -> ExecuteResult {
+        self.try_execute(query).unwrap()
+    }
+
+    /// Executes the given query inside of the transaction.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// Panics on error.
+    pub fn execute_ph(
+        &mut self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> ExecuteResult {
+        self.try_execute_ph(query, args).unwrap()
+    }
+
+    /// Executes the given query inside of the transaction.
+    ///
+    /// Returns the number of rows affected by the query, along with the id of an inserted
+    /// row, if the database reports it. Warnings are not counted inside of transactions.
+    pub fn try_execute(&mut self, query: &str) -> Result<ExecuteResult, Error> {
+        let observed = ObservedQuery::start(&self.observer, Vec::new);
+        let statement = self.sqlx_transaction().query(query);
+        let result = self
+            .runtime
+            .block_on(self.sqlx_transaction().try_execute(statement, false));
+        finish_observed(observed, query, &result, |result| result.rows_affected);
+        result
+    }
+
+    /// Executes the given query inside of the transaction.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    pub fn try_execute_ph(
+        &mut self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<ExecuteResult, Error> {
+        let observed = ObservedQuery::start(&self.observer, || args.redacted());
+        let result = match args.bind_to_query(self.sqlx_transaction().query(query)) {
+            Err(error) => Err(error),
+            Ok(statement) => self
+                .runtime
+                .block_on(self.sqlx_transaction().try_execute(statement, false)),
+        };
+        finish_observed(observed, query, &result, |result| result.rows_affected);
+        result
+    }
+
+    /// Fetches one row using the given query.
+    ///
+    /// Panics on error.
+    pub fn fetch_one(&mut self, query: &str) -> TableRow {
+        self.try_fetch_one(query).unwrap()
+    }
+
+    /// Fetches one row using the given query.
+    pub fn try_fetch_one(&mut self, query: &str) -> Result<TableRow, Error> {
+        let observed = ObservedQuery::start(&self.observer, Vec::new);
+        let statement = self.sqlx_transaction().query(query);
+        let result = self
+            .runtime
+            .block_on(self.sqlx_transaction().try_fetch_one(statement));
+        finish_observed(observed, query, &result, |_| 1);
+        result
+    }
+
+    /// Fetches one row using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// Panics on error.
+    pub fn fetch_one_ph(
+        &mut self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> TableRow {
+        self.try_fetch_one_ph(query, args).unwrap()
+    }
+
+    /// Fetches one row using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    pub fn try_fetch_one_ph(
+        &mut self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<TableRow, Error> {
+        let observed = ObservedQuery::start(&self.observer, || args.redacted());
+        let result = match args.bind_to_query(self.sqlx_transaction().query(query)) {
+            Err(error) => Err(error),
+            Ok(statement) => self
+                .runtime
+                .block_on(self.sqlx_transaction().try_fetch_one(statement)),
+        };
+        finish_observed(observed, query, &result, |_| 1);
+        result
+    }
+
+    /// Fetches multiple rows using the given query.
+    ///
+    /// Panics on error.
+    pub fn fetch_all(&mut self, query: &str) -> Vec<TableRow> {
+        self.try_fetch_all(query).unwrap()
+    }
+
+    /// Fetches multiple rows using the given query.
+    pub fn try_fetch_all(&mut self, query: &str) -> Result<Vec<TableRow>, Error> {
+        let observed = ObservedQuery::start(&self.observer, Vec::new);
+        let statement = self.sqlx_transaction().query(query);
+        let result = self
+            .runtime
+            .block_on(self.sqlx_transaction().try_fetch_all(statement));
+        finish_observed(observed, query, &result, |rows| rows.len() as u64);
+        result
+    }
+
+    /// Fetches multiple rows using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// Panics on error.
+    pub fn fetch_all_ph(
+        &mut self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Vec<TableRow> {
+        self.try_fetch_all_ph(query, args).unwrap()
+    }
+
+    /// Fetches multiple rows using the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    pub fn try_fetch_all_ph(
+        &mut self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<Vec<TableRow>, Error> {
+        let observed = ObservedQuery::start(&self.observer, || args.redacted());
+        let result = match args.bind_to_query(self.sqlx_transaction().query(query)) {
+            Err(error) => Err(error),
+            Ok(statement) => self
+                .runtime
+                .block_on(self.sqlx_transaction().try_fetch_all(statement)),
+        };
+        finish_observed(observed, query, &result, |rows| rows.len() as u64);
+        result
+    }
+
+    /// Describes the columns of the rows returned by the given query inside of the
+    /// transaction, without running it.
+    ///
+    /// Panics on error.
+    pub fn describe(&mut self, query: &str) -> Vec<TableColumn> {
+        self.try_describe(query).unwrap()
+    }
+
+    /// Describes the columns of the rows returned by the given query inside of the
+    /// transaction, without running it.
+    pub fn try_describe(&mut self, query: &str) -> Result<Vec<TableColumn>, Error> {
+        let runtime = self.runtime;
+        runtime.block_on(self.sqlx_transaction().describe(query))
+    }
+
+    /// Creates a cursor for the given query.
+    ///
+    /// The transaction cannot be used again until the cursor is dropped.
+    pub fn cursor<'t>(&'t mut self, query: &str) -> Result<Cursor<'t>, Error> {
+        self.cursor_with_args(query, None as Option<PlaceholderArgumentVector>)
+    }
+
+    /// Creates a cursor for the given query.
+    ///
+    /// Uses placeholder arguments, binding them to the query prior to execution.
+    ///
+    /// The transaction cannot be used again until the cursor is dropped.
+    pub fn cursor_ph<'t>(
+        &'t mut self,
+        query: &str,
+        args: impl PlaceholderArgumentCollection,
+    ) -> Result<Cursor<'t>, Error> {
+        self.cursor_with_args(query, Some(args))
+    }
+
+    fn cursor_with_args<'t>(
+        &'t mut self,
+        query: &str,
+        args: Option<impl PlaceholderArgumentCollection>,
+    ) -> Result<Cursor<'t>, Error> {
+        let runtime = self.runtime;
+        let observed = ObservedQuery::start(&self.observer, || match &args {
+            None => Vec::new(),
+            Some(args) => args.redacted(),
+        });
+        let transaction = self.transaction.as_mut().unwrap();
+        Ok(Cursor::new(
+            AsyncCursor::new_from_transaction(query, transaction, observed, args)?,
+            runtime,
+        ))
+    }
+
+    /// Returns the number of savepoints currently established inside of the transaction.
+    pub fn savepoint_depth(&self) -> usize {
+        self.savepoints
+    }
+
+    fn savepoint_name(depth: usize) -> String {
+        format!("_dj_savepoint_{}", depth)
+    }
+
+    fn no_savepoint_error() -> Error {
+        DataJointError::new_with_message(
+            "transaction has no savepoint to release or roll back to",
+            ErrorCode::NoActiveSavepoint,
+        )
+    }
+
+    /// Establishes a new savepoint inside of the transaction.
+    ///
+    /// Savepoints nest: [`release_savepoint`][Transaction::release_savepoint] and
+    /// [`rollback_to_savepoint`][Transaction::rollback_to_savepoint] always apply to the
+    /// most recently established savepoint.
+    pub fn savepoint(&mut self) -> Result<(), Error> {
+        let name = Transaction::savepoint_name(self.savepoints + 1);
+        self.try_execute(&format!("SAVEPOINT {}", name))?;
+        self.savepoints += 1;
+        Ok(())
+    }
+
+    /// Releases the most recent savepoint, keeping all changes made since it was established.
+    pub fn release_savepoint(&mut self) -> Result<(), Error> {
+        if self.savepoints == 0 {
+            return Err(Transaction::no_savepoint_error());
+        }
+        let name = Transaction::savepoint_name(self.savepoints);
+        self.try_execute(&format!("RELEASE SAVEPOINT {}", name))?;
+        self.savepoints -= 1;
+        Ok(())
+    }
+
+    /// Discards all changes made since the most recent savepoint was established, then
+    /// releases the savepoint.
+    ///
+    /// The savepoint no longer counts towards the
+    /// [`savepoint_depth`][Transaction::savepoint_depth] once the changes are discarded,
+    /// even if releasing it fails. A savepoint left behind is replaced by the next
+    /// savepoint established at the same depth.
+    pub fn rollback_to_savepoint(&mut self) -> Result<(), Error> {
+        if self.savepoints == 0 {
+            return Err(Transaction::no_savepoint_error());
+        }
+        let name = Transaction::savepoint_name(self.savepoints);
+        self.try_execute(&format!("ROLLBACK TO SAVEPOINT {}", name))?;
+        self.savepoints -= 1;
+        self.try_execute(&format!("RELEASE SAVEPOINT {}", name))?;
+        Ok(())
+    }
+
+    /// Commits the transaction, including all changes inside of unreleased savepoints.
+    pub fn commit(mut self) -> Result<(), Error> {
+        let transaction = self.transaction.take().unwrap();
+        self.runtime.block_on(transaction.commit())
+    }
+
+    /// Rolls back the transaction, discarding all changes made inside of it.
+    pub fn rollback(mut self) -> Result<(), Error> {
+        let transaction = self.transaction.take().unwrap();
+        self.runtime.block_on(transaction.rollback())
+    }
+}
+
+impl<'c> Drop for Transaction<'c> {
+    fn drop(&mut self) {
+        // Roll back eagerly rather than leaving SQLx to queue the rollback, so that the
+        // connection is clean by the time the next query is issued.
+        if let Some(transaction) = self.transaction.take() {
+            let _ = self.runtime.block_on(transaction.rollback());
+        }
+    }
+}
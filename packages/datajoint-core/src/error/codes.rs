@@ -37,6 +37,13 @@ pub enum ErrorCode {
     WrongDatabaseType,
     UnexpectedNullValue,
     UnexpectedNoneType,
+    NoActiveSavepoint,
+    QueryCancelled,
+    QueryTimedOut,
+    WrongArgumentCount,
+    MalformedDelimitedText,
+    StatementWarnings,
+    InvalidBatchSize,
 
     // C FFI error codes.
     NullNotAllowed,
@@ -81,6 +88,13 @@ impl ErrorCode {
             WrongDatabaseType => "wrong database type",
             UnexpectedNullValue => "unexpected null value encountered in decoding",
             UnexpectedNoneType => "unexpected none type encountered in encoding",
+            NoActiveSavepoint => "no active savepoint",
+            QueryCancelled => "query cancelled",
+            QueryTimedOut => "query timed out",
+            WrongArgumentCount => "wrong number of placeholder arguments",
+            MalformedDelimitedText => "malformed delimited text",
+            StatementWarnings => "statement produced warnings",
+            InvalidBatchSize => "batch size must be greater than zero",
 
             NullNotAllowed => "null not allowed",
             BufferNotEnough => "buffer not enough",
@@ -8,6 +8,32 @@ pub trait PlaceholderArgumentCollection {
     ///
     /// Returns the new query with the bound parameters.
     fn bind_to_query<'q>(self, query: Query<'q>) -> Result<Query<'q>, Error>;
+
+    /// Describes each placeholder argument without revealing its value, such as
+    /// `<int32>`, for reporting to a query observer.
+    ///
+    /// Returns no descriptions by default.
+    fn redacted(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns the values of the placeholder arguments, which key the results of the query
+    /// in the result cache and are bound again when a statement is retried after a lost
+    /// connection.
+    ///
+    /// Returns `None` by default, which bypasses the result cache and never retries the
+    /// statement.
+    fn values(&self) -> Option<Vec<PlaceholderArgument>> {
+        None
+    }
+
+    /// Returns the number of placeholder arguments, which is checked against the
+    /// parameters of a prepared statement.
+    ///
+    /// Returns `None` by default, which skips the check.
+    fn argument_count(&self) -> Option<usize> {
+        None
+    }
 }
 
 /// A single placeholder argument.
@@ -17,14 +43,27 @@ pub type PlaceholderArgument = NativeType;
 pub type PlaceholderArgumentVector = Vec<PlaceholderArgument>;
 
 impl PlaceholderArgumentCollection for PlaceholderArgumentVector {
+    fn redacted(&self) -> Vec<String> {
+        self.iter()
+            .map(|arg| format!("<{}>", arg.type_name()))
+            .collect()
+    }
+
+    fn values(&self) -> Option<Vec<PlaceholderArgument>> {
+        Some(self.clone())
+    }
+
+    fn argument_count(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
     fn bind_to_query<'q>(self, query: Query<'q>) -> Result<Query<'q>, Error> {
         match query {
             Query::MySql(mut query) => {
                 for arg in self {
                     match arg {
-                        NativeType::None => {
-                            return Err(DataJointError::new(ErrorCode::UnexpectedNoneType))
-                        }
+                        // A null takes on the type of the column it is bound to.
+                        NativeType::None => query = query.bind(Option::<String>::None),
                         NativeType::Bool(val) => query = query.bind(val),
                         NativeType::Int8(val) => query = query.bind(val),
                         NativeType::UInt8(val) => query = query.bind(val),
@@ -45,6 +84,7 @@ impl PlaceholderArgumentCollection for PlaceholderArgumentVector {
             Query::Postgres(mut query) => {
                 for arg in self {
                     match arg {
+                        // Parameters are typed, so a null has no type to be bound with.
                         NativeType::None => {
                             return Err(DataJointError::new(ErrorCode::UnexpectedNoneType))
                         }
@@ -70,6 +110,40 @@ impl PlaceholderArgumentCollection for PlaceholderArgumentVector {
                 }
                 Ok(Query::Postgres(query))
             }
+            Query::Sqlite(mut query) => {
+                for arg in self {
+                    match arg {
+                        // A null takes on the type of the column it is bound to.
+                        NativeType::None => query = query.bind(Option::<String>::None),
+                        NativeType::Bool(val) => query = query.bind(val),
+                        NativeType::Int8(val) => query = query.bind(val),
+                        NativeType::UInt8(val) => query = query.bind(val),
+                        NativeType::Int16(val) => query = query.bind(val),
+                        NativeType::UInt16(val) => query = query.bind(val),
+                        NativeType::Int32(val) => query = query.bind(val),
+                        NativeType::UInt32(val) => query = query.bind(val),
+                        NativeType::Int64(val) => query = query.bind(val),
+                        NativeType::String(val) => query = query.bind(val),
+                        NativeType::Float32(val) => query = query.bind(val),
+                        NativeType::Float64(val) => query = query.bind(val),
+                        NativeType::Bytes(val) => query = query.bind(val),
+                        NativeType::UInt64(_) => {
+                            return Err(DataJointError::new_with_message(
+                                "sqlite does not support unsigned 64-bit integers",
+                                ErrorCode::UnsupportedNativeType,
+                            ))
+                        }
+                    };
+                }
+                Ok(Query::Sqlite(query))
+            }
+            Query::Mock(mut query) => {
+                // Arguments are checked as the emulated database would bind them.
+                self.clone()
+                    .bind_to_query(Query::new(query.database_type, query.sql))?;
+                query.arguments.extend(self);
+                Ok(Query::Mock(query))
+            }
         }
     }
 }
@@ -0,0 +1,318 @@
+use crate::common::DatabaseType;
+use crate::placeholders::PlaceholderArgumentVector;
+use crate::query::lexer::CodeChars;
+use crate::types::NativeType;
+use std::fmt::Write;
+
+/// The largest number of placeholder parameters a MySQL or Postgres statement may have.
+const MAX_PARAMETERS: usize = 65535;
+
+/// The largest number of placeholder parameters a SQLite statement may have.
+const SQLITE_MAX_PARAMETERS: usize = 32766;
+
+/// Bytes reserved in every statement for the protocol around the SQL text and arguments.
+const STATEMENT_OVERHEAD: usize = 1024;
+
+/// An insert of a single row of placeholder arguments, whose row of values is repeated to
+/// insert many rows with a single statement.
+pub(crate) struct BatchStatement<'q> {
+    database_type: DatabaseType,
+    // The text before the row of values, including the `VALUES` keyword.
+    prefix: &'q str,
+    // The row of values, such as `(?, ?)`.
+    row: &'q str,
+    // The text after the row of values, such as an `ON CONFLICT` clause.
+    suffix: &'q str,
+    // The byte ranges and numbers of the numbered placeholders of the row.
+    numbered: Vec<(usize, usize, usize)>,
+    parameters: usize,
+}
+
+impl<'q> BatchStatement<'q> {
+    /// Finds the row of values of an insert with the given number of parameters, such as
+    /// `(?, ?)` in `insert into tweet (text, owner_id) values (?, ?)`.
+    ///
+    /// Returns `None` if the statement does not have a single row of values holding all of
+    /// its placeholders, so it cannot be batched.
+    pub fn parse(database_type: DatabaseType, sql: &'q str, parameters: usize) -> Option<Self> {
+        let chars: Vec<(usize, char)> = CodeChars::new(database_type, sql).collect();
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+        // Find the `VALUES` keyword outside of parentheses.
+        let mut depth = 0;
+        let mut keyword = None;
+        for (index, &(_, c)) in chars.iter().enumerate() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                'v' | 'V' if depth == 0 => {
+                    let word: String = chars[index..]
+                        .iter()
+                        .map(|&(_, c)| c)
+                        .take_while(|&c| is_word(c))
+                        .collect();
+                    let follows_word = index > 0 && is_word(chars[index - 1].1);
+                    if !follows_word && word.eq_ignore_ascii_case("values") {
+                        if keyword.is_some() {
+                            return None;
+                        }
+                        keyword = Some(index + word.len());
+                    }
+                }
+                _ => (),
+            }
+        }
+        let after_keyword = keyword?;
+
+        // The row of values is the parenthesized list right after the keyword.
+        let mut rest = chars[after_keyword..]
+            .iter()
+            .skip_while(|(_, c)| c.is_whitespace());
+        let &(row_start, open) = rest.next()?;
+        if open != '(' {
+            return None;
+        }
+        let mut depth = 1;
+        let mut row_end = None;
+        for &(offset, c) in &mut rest {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => (),
+            }
+            if depth == 0 {
+                row_end = Some(offset + 1);
+                break;
+            }
+        }
+        let row_end = row_end?;
+        // A statement that already inserts several rows is left alone.
+        if let Some((_, ',')) = rest.find(|(_, c)| !c.is_whitespace()) {
+            return None;
+        }
+
+        // Every placeholder must be inside of the row, to be repeated along with it.
+        let mut numbered = Vec::new();
+        let mut count = 0;
+        for (index, &(offset, c)) in chars.iter().enumerate() {
+            match (c, database_type) {
+                ('$', DatabaseType::Postgres) => {
+                    let digits: String = chars[index + 1..]
+                        .iter()
+                        .map(|&(_, c)| c)
+                        .take_while(|c| c.is_ascii_digit())
+                        .collect();
+                    if digits.is_empty() {
+                        continue;
+                    }
+                    let number: usize = digits.parse().ok()?;
+                    if number == 0 || number > parameters {
+                        return None;
+                    }
+                    numbered.push((
+                        offset - row_start,
+                        offset + 1 + digits.len() - row_start,
+                        number,
+                    ));
+                }
+                ('?', DatabaseType::MySql | DatabaseType::Sqlite) => {
+                    // Numbered SQLite placeholders are not renumbered.
+                    if let Some((_, next)) = chars.get(index + 1) {
+                        if next.is_ascii_digit() {
+                            return None;
+                        }
+                    }
+                    count += 1;
+                }
+                _ => continue,
+            }
+            if offset < row_start || offset >= row_end {
+                return None;
+            }
+        }
+        if database_type != DatabaseType::Postgres && count != parameters {
+            return None;
+        }
+
+        Some(BatchStatement {
+            database_type,
+            prefix: &sql[..row_start],
+            row: &sql[row_start..row_end],
+            suffix: &sql[row_end..],
+            numbered,
+            parameters,
+        })
+    }
+
+    /// Creates the SQL text of a statement inserting the given number of rows.
+    pub fn sql(&self, rows: usize) -> String {
+        let mut sql = String::with_capacity(
+            self.prefix.len() + (self.row.len() + 8) * rows + self.suffix.len(),
+        );
+        sql.push_str(self.prefix);
+        for row in 0..rows {
+            if row > 0 {
+                sql.push_str(", ");
+            }
+            let offset = row * self.parameters;
+            let mut copied = 0;
+            for &(start, end, number) in &self.numbered {
+                sql.push_str(&self.row[copied..start]);
+                write!(sql, "${}", number + offset).unwrap();
+                copied = end;
+            }
+            sql.push_str(&self.row[copied..]);
+        }
+        sql.push_str(self.suffix);
+        sql
+    }
+
+    /// Splits sets of placeholder arguments into the number of rows of each statement of
+    /// the batch.
+    ///
+    /// Each statement stays within the parameter limit of the database and, if given, the
+    /// largest statement size in bytes accepted by the server. A row too large to fit is
+    /// inserted by a statement of its own.
+    pub fn chunks(
+        &self,
+        args: &[PlaceholderArgumentVector],
+        max_size: Option<usize>,
+    ) -> Vec<usize> {
+        let max_parameters = match self.database_type {
+            DatabaseType::Sqlite => SQLITE_MAX_PARAMETERS,
+            _ => MAX_PARAMETERS,
+        };
+        let max_rows = match self.parameters {
+            0 => usize::MAX,
+            parameters => (max_parameters / parameters).max(1),
+        };
+        let base_size = STATEMENT_OVERHEAD + self.prefix.len() + self.suffix.len();
+
+        let mut chunks = Vec::new();
+        let mut rows = 0;
+        let mut size = base_size;
+        for row in args {
+            let row_size =
+                self.row.len() + 2 + row.iter().map(BatchStatement::encoded_size).sum::<usize>();
+            let too_large = match max_size {
+                None => false,
+                Some(max_size) => size + row_size > max_size,
+            };
+            if rows > 0 && (rows == max_rows || too_large) {
+                chunks.push(rows);
+                rows = 0;
+                size = base_size;
+            }
+            rows += 1;
+            size += row_size;
+        }
+        if rows > 0 {
+            chunks.push(rows);
+        }
+        chunks
+    }
+
+    /// Estimates the number of bytes an argument takes up when sent to the server.
+    fn encoded_size(value: &NativeType) -> usize {
+        // Each argument also sends its type and a length prefix.
+        const ARGUMENT_OVERHEAD: usize = 11;
+        ARGUMENT_OVERHEAD
+            + match value {
+                NativeType::None => 0,
+                NativeType::Bool(_) | NativeType::Int8(_) | NativeType::UInt8(_) => 1,
+                NativeType::Int16(_) | NativeType::UInt16(_) => 2,
+                NativeType::Int32(_) | NativeType::UInt32(_) | NativeType::Float32(_) => 4,
+                NativeType::Int64(_) | NativeType::UInt64(_) | NativeType::Float64(_) => 8,
+                NativeType::String(string) => string.len(),
+                NativeType::Bytes(bytes) => bytes.len(),
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_sql() {
+        let statement = BatchStatement::parse(
+            DatabaseType::MySql,
+            "INSERT INTO tweet (text, owner_id) VALUES (?, ifnull(?, 0)) ON DUPLICATE KEY UPDATE text = 'values (?)'",
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            statement.sql(3),
+            "INSERT INTO tweet (text, owner_id) VALUES (?, ifnull(?, 0)), (?, ifnull(?, 0)), (?, ifnull(?, 0)) ON DUPLICATE KEY UPDATE text = 'values (?)'"
+        );
+
+        let statement = BatchStatement::parse(
+            DatabaseType::Postgres,
+            "insert into tweet (text, owner_id) values ($2, $1) returning id",
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            statement.sql(2),
+            "insert into tweet (text, owner_id) values ($2, $1), ($4, $3) returning id"
+        );
+    }
+
+    #[test]
+    fn test_unbatchable_statements() {
+        let unbatchable = [
+            (
+                DatabaseType::MySql,
+                "update tweet set text = ? where id = ?",
+                2,
+            ),
+            (
+                DatabaseType::MySql,
+                "insert into tweet values (?, ?), (?, ?)",
+                4,
+            ),
+            (
+                DatabaseType::MySql,
+                "insert into tweet values (?) on duplicate key update text = ?",
+                2,
+            ),
+            (DatabaseType::MySql, "insert into tweet values (?, ?)", 3),
+            (DatabaseType::Sqlite, "insert into tweet values (?1, ?2)", 2),
+            (
+                DatabaseType::Postgres,
+                "insert into tweet values ($1) on conflict do update set id = $2",
+                2,
+            ),
+            (
+                DatabaseType::Postgres,
+                "insert into tweet values ($1, $2)",
+                1,
+            ),
+        ];
+        for (database_type, sql, parameters) in unbatchable.iter() {
+            assert!(
+                BatchStatement::parse(*database_type, sql, *parameters).is_none(),
+                "{} was batched",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_chunks() {
+        let statement =
+            BatchStatement::parse(DatabaseType::Postgres, "insert into t values ($1)", 1).unwrap();
+        let args = vec![vec![NativeType::Int32(1)]; MAX_PARAMETERS + 1];
+        assert_eq!(statement.chunks(&args, None), vec![MAX_PARAMETERS, 1]);
+
+        let statement =
+            BatchStatement::parse(DatabaseType::MySql, "insert into t values (?)", 1).unwrap();
+        let args = vec![
+            vec![NativeType::Bytes(vec![0; 600])],
+            vec![NativeType::Bytes(vec![0; 600])],
+            vec![NativeType::Bytes(vec![0; 5000])],
+            vec![NativeType::None],
+        ];
+        assert_eq!(statement.chunks(&args, Some(3000)), vec![2, 1, 1]);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::common::DatabaseType;
+
+/// An iterator over the characters of SQL text that are outside of quoted strings, quoted
+/// identifiers and comments, along with their byte offsets.
+///
+/// Each comment is given as a single space, so it still separates the words around it.
+///
+/// Quoting and comments follow the given database type, such as backslash escapes in
+/// MySQL strings and dollar-quoted strings in Postgres. MySQL runs the text of executable
+/// comments, such as `/*!40101 SET NAMES utf8 */`, and optimizer hints, such as
+/// `/*+ NO_ICP(t) */`, so their text is given as code, with their delimiters given as
+/// spaces.
+pub(crate) struct CodeChars<'q> {
+    sql: &'q str,
+    database_type: DatabaseType,
+    position: usize,
+    // Whether the iteration is inside of a MySQL executable comment or optimizer hint.
+    in_executable_comment: bool,
+}
+
+impl<'q> CodeChars<'q> {
+    /// Creates an iterator over the given SQL text.
+    pub fn new(database_type: DatabaseType, sql: &'q str) -> Self {
+        CodeChars {
+            sql,
+            database_type,
+            position: 0,
+            in_executable_comment: false,
+        }
+    }
+
+    /// Continues the iteration at the given byte offset, which must be at a character
+    /// boundary outside of quoted text and comments.
+    pub fn skip_to(&mut self, position: usize) {
+        self.position = position;
+    }
+
+    fn rest(&self) -> &'q str {
+        &self.sql[self.position..]
+    }
+
+    /// Skips past the closing quote of quoted text, starting after the opening quote.
+    ///
+    /// A doubled quote closes the text and opens it again, which has the same effect as
+    /// skipping over it.
+    fn skip_quoted(&mut self, quote: char, backslash_escapes: bool) {
+        let mut chars = self.rest().char_indices();
+        while let Some((offset, c)) = chars.next() {
+            if backslash_escapes && c == '\\' {
+                chars.next();
+            } else if c == quote {
+                self.position += offset + c.len_utf8();
+                return;
+            }
+        }
+        self.position = self.sql.len();
+    }
+
+    /// Skips past the next occurrence of the given text, or to the end.
+    fn skip_past(&mut self, end: &str) {
+        match self.rest().find(end) {
+            None => self.position = self.sql.len(),
+            Some(offset) => self.position += offset + end.len(),
+        }
+    }
+
+    /// Skips to the end of the line, leaving the line break.
+    fn skip_line(&mut self) {
+        match self.rest().find('\n') {
+            None => self.position = self.sql.len(),
+            Some(offset) => self.position += offset,
+        }
+    }
+
+    /// Checks if text starting with `--` starts a comment.
+    ///
+    /// MySQL only starts a comment if the dashes are followed by whitespace or a control
+    /// character, so `1--1` subtracts a negative number.
+    fn is_dash_comment(&self, text: &str) -> bool {
+        match self.database_type {
+            DatabaseType::MySql => match text[2..].chars().next() {
+                None => true,
+                Some(c) => c.is_whitespace() || c.is_control(),
+            },
+            _ => true,
+        }
+    }
+
+    /// Returns the length of the opening of a MySQL executable comment or optimizer hint
+    /// at the start of the text, such as `/*!40101` or `/*+`, if there is one.
+    ///
+    /// The MariaDB form `/*M!100100` is included.
+    fn executable_comment_opening(text: &str) -> Option<usize> {
+        if text.starts_with("/*+") {
+            return Some(3);
+        }
+        let after = text
+            .strip_prefix("/*!")
+            .or_else(|| text.strip_prefix("/*M!"))?;
+        let version = after
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(after.len());
+        Some(text.len() - after.len() + version)
+    }
+
+    /// Checks if a Postgres string starting at the given offset is an escape string, such
+    /// as `E'\n'`, in which backslashes escape characters.
+    fn is_escape_string(&self, start: usize) -> bool {
+        let before = &self.sql[..start];
+        match before.strip_suffix(|c| c == 'E' || c == 'e') {
+            None => false,
+            Some(before) => !before.ends_with(|c: char| c.is_alphanumeric() || c == '_'),
+        }
+    }
+
+    /// Returns the tag of a Postgres dollar quote starting at the given offset, such as
+    /// `$body$`, which is not to be confused with a numbered placeholder such as `$1`.
+    fn dollar_quote_tag(&self, start: usize) -> Option<&'q str> {
+        let after = &self.sql[start + 1..];
+        if after.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let end = after.find(|c: char| !(c.is_alphanumeric() || c == '_'))?;
+        if after[end..].starts_with('$') {
+            Some(&self.sql[start..start + end + 2])
+        } else {
+            None
+        }
+    }
+}
+
+impl<'q> Iterator for CodeChars<'q> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.position;
+            let rest = self.rest();
+            let c = rest.chars().next()?;
+            self.position += c.len_utf8();
+            match (c, self.database_type) {
+                ('\'', DatabaseType::MySql) => self.skip_quoted('\'', true),
+                ('\'', DatabaseType::Postgres) => {
+                    let escapes = self.is_escape_string(start);
+                    self.skip_quoted('\'', escapes)
+                }
+                ('\'', _) => self.skip_quoted('\'', false),
+                ('"', DatabaseType::MySql) => self.skip_quoted('"', true),
+                ('"', _) => self.skip_quoted('"', false),
+                ('`', DatabaseType::MySql | DatabaseType::Sqlite) => self.skip_quoted('`', false),
+                ('-', _) if rest.starts_with("--") && self.is_dash_comment(rest) => {
+                    self.skip_line();
+                    return Some((start, ' '));
+                }
+                ('#', DatabaseType::MySql) => {
+                    self.skip_line();
+                    return Some((start, ' '));
+                }
+                ('*', DatabaseType::MySql)
+                    if self.in_executable_comment && rest.starts_with("*/") =>
+                {
+                    self.position += 1;
+                    self.in_executable_comment = false;
+                    return Some((start, ' '));
+                }
+                ('/', DatabaseType::MySql)
+                    if rest.starts_with("/*") && !self.in_executable_comment =>
+                {
+                    match CodeChars::executable_comment_opening(rest) {
+                        None => {
+                            self.position += 1;
+                            self.skip_past("*/");
+                        }
+                        Some(length) => {
+                            self.position = start + length;
+                            self.in_executable_comment = true;
+                        }
+                    }
+                    return Some((start, ' '));
+                }
+                ('/', _) if rest.starts_with("/*") => {
+                    self.position += 1;
+                    self.skip_past("*/");
+                    return Some((start, ' '));
+                }
+                ('$', DatabaseType::Postgres) => match self.dollar_quote_tag(start) {
+                    None => return Some((start, c)),
+                    Some(tag) => {
+                        self.position = start + tag.len();
+                        self.skip_past(tag);
+                    }
+                },
+                _ => return Some((start, c)),
+            }
+        }
+    }
+}
+
+/// Returns the words of SQL text outside of quoted text, comments and parentheses, such as
+/// keywords and unquoted identifiers, in order.
+///
+/// Words in parentheses belong to subqueries, common table expressions, column lists and
+/// function arguments, so they are left out.
+pub(crate) fn top_level_words(database_type: DatabaseType, sql: &str) -> Vec<String> {
+    let mut depth = 0usize;
+    let code: String = CodeChars::new(database_type, sql)
+        .map(|(_, c)| match c {
+            '(' => {
+                depth += 1;
+                ' '
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                ' '
+            }
+            _ if depth > 0 => ' ',
+            c => c,
+        })
+        .collect();
+    code.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(database_type: DatabaseType, sql: &str) -> String {
+        CodeChars::new(database_type, sql).map(|(_, c)| c).collect()
+    }
+
+    #[test]
+    fn test_code_chars() {
+        assert_eq!(
+            code(
+                DatabaseType::MySql,
+                "select 'a\\'b', \"c\", `d` # comment\nfrom t"
+            ),
+            "select , ,   \nfrom t"
+        );
+        assert_eq!(
+            code(
+                DatabaseType::Sqlite,
+                "select 'it''s' -- comment\n/* block */ ?"
+            ),
+            "select   \n  ?"
+        );
+        assert_eq!(
+            code(
+                DatabaseType::Postgres,
+                "select $1, E'\\'', $body$ ; $x$ $body$, 'a\\' #"
+            ),
+            "select $1, E, ,  #"
+        );
+        assert_eq!(
+            code(
+                DatabaseType::MySql,
+                "/*!40101 SET x = 1 */; select /*+ NO_ICP(t) */ 1--1 /* c */ -- c\n"
+            ),
+            "  SET x = 1  ; select   NO_ICP(t)   1--1    \n"
+        );
+        assert_eq!(
+            code(DatabaseType::Sqlite, "select /*!1 2 */ 1--1"),
+            "select   1 "
+        );
+    }
+
+    #[test]
+    fn test_top_level_words() {
+        assert_eq!(
+            top_level_words(
+                DatabaseType::Postgres,
+                "/* insert */ INSERT INTO t(a) VALUES ('returning') RETURNING id;"
+            ),
+            vec!["INSERT", "INTO", "t", "VALUES", "RETURNING", "id"]
+        );
+        assert_eq!(
+            top_level_words(
+                DatabaseType::Postgres,
+                "with x as (select (1) as a) insert into t select a from x"
+            ),
+            vec!["with", "x", "as", "insert", "into", "t", "select", "a", "from", "x"]
+        );
+        assert_eq!(
+            top_level_words(DatabaseType::MySql, "/*!50003 INSERT */ INTO t VALUES (1)"),
+            vec!["INSERT", "INTO", "t", "VALUES"]
+        );
+    }
+}
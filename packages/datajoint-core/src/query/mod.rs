@@ -1,3 +1,9 @@
+mod batch;
+mod lexer;
 mod query;
+mod script;
 
-pub use query::Query;
+pub(crate) use batch::BatchStatement;
+pub(crate) use lexer::top_level_words;
+pub use query::{MockQuery, Query};
+pub(crate) use script::split_script;
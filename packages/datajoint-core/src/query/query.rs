@@ -1,4 +1,5 @@
 use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::types::NativeType;
 
 /// Type trait for indicating if a type is safe to be encoded into a query.
 ///
@@ -8,6 +9,8 @@ pub trait ValueEncodable<'r>:
     + sqlx::Type<sqlx::MySql>
     + sqlx::Encode<'r, sqlx::Postgres>
     + sqlx::Type<sqlx::Postgres>
+    + sqlx::Encode<'r, sqlx::Sqlite>
+    + sqlx::Type<sqlx::Sqlite>
 {
 }
 
@@ -16,6 +19,8 @@ impl<'r, T> ValueEncodable<'r> for T where
         + sqlx::Type<sqlx::MySql>
         + sqlx::Encode<'r, sqlx::Postgres>
         + sqlx::Type<sqlx::Postgres>
+        + sqlx::Encode<'r, sqlx::Sqlite>
+        + sqlx::Type<sqlx::Sqlite>
 {
 }
 
@@ -40,6 +45,35 @@ pub enum Query<'q> {
             <sqlx::Postgres as sqlx::database::HasArguments<'q>>::Arguments,
         >,
     ),
+    Sqlite(
+        sqlx::query::Query<
+            'q,
+            sqlx::Sqlite,
+            <sqlx::Sqlite as sqlx::database::HasArguments<'q>>::Arguments,
+        >,
+    ),
+    Mock(MockQuery<'q>),
+}
+
+/// A query for a [`MockDatabase`][crate::connection::MockDatabase], which records its
+/// SQL text and placeholder arguments instead of sending them to a server.
+#[derive(Debug, Clone)]
+pub struct MockQuery<'q> {
+    pub(crate) database_type: DatabaseType,
+    pub(crate) sql: &'q str,
+    pub(crate) arguments: Vec<NativeType>,
+}
+
+impl<'q> MockQuery<'q> {
+    /// Creates a new mock query with the given string, for a database emulating the
+    /// given database type.
+    pub(crate) fn new(database_type: DatabaseType, sql: &'q str) -> Self {
+        MockQuery {
+            database_type,
+            sql,
+            arguments: Vec::new(),
+        }
+    }
 }
 
 impl<'q> DatabaseTypeAgnostic for Query<'q> {
@@ -47,6 +81,8 @@ impl<'q> DatabaseTypeAgnostic for Query<'q> {
         match self {
             Self::MySql(_) => DatabaseType::MySql,
             Self::Postgres(_) => DatabaseType::Postgres,
+            Self::Sqlite(_) => DatabaseType::Sqlite,
+            Self::Mock(query) => query.database_type,
         }
     }
 }
@@ -60,6 +96,7 @@ impl<'q> Query<'q> {
         match database_type {
             DatabaseType::MySql => Self::MySql(sqlx::query::<sqlx::MySql>(query)),
             DatabaseType::Postgres => Self::Postgres(sqlx::query::<sqlx::Postgres>(query)),
+            DatabaseType::Sqlite => Self::Sqlite(sqlx::query::<sqlx::Sqlite>(query)),
         }
     }
 }
@@ -0,0 +1,112 @@
+use crate::common::DatabaseType;
+use crate::query::lexer::CodeChars;
+
+/// The client command of MySQL scripts that changes the statement delimiter.
+const DELIMITER_COMMAND: &str = "delimiter";
+
+/// Splits a SQL script into its statements, in order.
+///
+/// Statements end at semicolons outside of quoted text, comments and Postgres
+/// dollar-quoted function bodies. MySQL scripts may change the delimiter with a
+/// `DELIMITER` command on a line of its own, so that semicolons inside of the bodies of
+/// stored procedures and triggers do not end them. Statements holding nothing but
+/// comments are left out, though MySQL executable comments such as
+/// `/*!40101 SET NAMES utf8 */` count as statements.
+pub(crate) fn split_script(database_type: DatabaseType, script: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut delimiter = ";".to_string();
+    let mut start = 0;
+    // Whether the current statement has any text besides whitespace and comments.
+    let mut has_code = false;
+    let mut chars = CodeChars::new(database_type, script);
+    while let Some((offset, c)) = chars.next() {
+        let rest = &script[offset..];
+        if !has_code && database_type == DatabaseType::MySql && is_delimiter_command(rest) {
+            let line_end = match rest.find('\n') {
+                None => script.len(),
+                Some(end) => offset + end,
+            };
+            let new_delimiter = script[offset + DELIMITER_COMMAND.len()..line_end].trim();
+            if !new_delimiter.is_empty() {
+                delimiter = new_delimiter.to_string();
+            }
+            start = line_end;
+            chars.skip_to(line_end);
+        } else if rest.starts_with(delimiter.as_str()) {
+            if has_code {
+                statements.push(script[start..offset].trim());
+            }
+            start = offset + delimiter.len();
+            has_code = false;
+            chars.skip_to(start);
+        } else if !c.is_whitespace() {
+            has_code = true;
+        }
+    }
+    if has_code {
+        statements.push(script[start..].trim());
+    }
+    statements
+}
+
+/// Checks if text starts with a MySQL `DELIMITER` command.
+fn is_delimiter_command(text: &str) -> bool {
+    match text.get(..DELIMITER_COMMAND.len()) {
+        None => false,
+        Some(word) => {
+            word.eq_ignore_ascii_case(DELIMITER_COMMAND)
+                && text[DELIMITER_COMMAND.len()..].starts_with([' ', '\t'])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_script() {
+        assert_eq!(
+            split_script(
+                DatabaseType::Postgres,
+                "-- bootstrap\ncreate table t (a text default ';');\n\n\
+                 create function f() returns int as $body$ select 1; $body$ language sql;\n\
+                 /* done; */ select 'x'"
+            ),
+            vec![
+                "-- bootstrap\ncreate table t (a text default ';')",
+                "create function f() returns int as $body$ select 1; $body$ language sql",
+                "/* done; */ select 'x'",
+            ]
+        );
+        assert_eq!(
+            split_script(
+                DatabaseType::MySql,
+                "set @a = 'it\\'s;';\nDELIMITER //\ncreate procedure p() begin select 1; select 2; end//\n\
+                 delimiter ;\n# comment only\n;\nselect `a;b` from t;"
+            ),
+            vec![
+                "set @a = 'it\\'s;'",
+                "create procedure p() begin select 1; select 2; end",
+                "select `a;b` from t",
+            ]
+        );
+        assert!(split_script(DatabaseType::Sqlite, " ; -- nothing\n;").is_empty());
+
+        // MySQL runs executable comments, as written by mysqldump.
+        assert_eq!(
+            split_script(
+                DatabaseType::MySql,
+                "/*!40101 SET NAMES utf8 */;\n/* comment only */;\nDELIMITER ;;\n\
+                 /*!50003 CREATE*/ /*!50003 TRIGGER tr BEFORE INSERT ON t FOR EACH ROW SET NEW.a = 1 */;;\n\
+                 DELIMITER ;\nselect 1--1;\nselect 2 -- one; two\n;"
+            ),
+            vec![
+                "/*!40101 SET NAMES utf8 */",
+                "/*!50003 CREATE*/ /*!50003 TRIGGER tr BEFORE INSERT ON t FOR EACH ROW SET NEW.a = 1 */",
+                "select 1--1",
+                "select 2 -- one; two",
+            ]
+        );
+    }
+}
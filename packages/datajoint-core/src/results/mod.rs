@@ -1,5 +1,7 @@
+mod owned_row;
 mod table_column;
 mod table_row;
 
+pub use owned_row::OwnedRow;
 pub use table_column::{ColumnIndex, TableColumn, TableColumnRef};
 pub use table_row::TableRow;
@@ -0,0 +1,31 @@
+use crate::common::DatabaseType;
+use crate::results::TableColumn;
+use crate::types::NativeType;
+use std::sync::Arc;
+
+/// A row of a query result that is held in memory, such as a row read from the result
+/// cache or scripted by a [`MockDatabase`][crate::connection::MockDatabase].
+///
+/// Values are stored already decoded, with null values stored as [`NativeType::None`].
+/// Rows of the same result share their columns.
+#[derive(Debug, Clone)]
+pub struct OwnedRow {
+    pub(crate) database_type: DatabaseType,
+    pub(crate) columns: Arc<[TableColumn]>,
+    pub(crate) values: Vec<NativeType>,
+}
+
+impl OwnedRow {
+    /// Creates a row out of the decoded values of its columns.
+    pub(crate) fn new(
+        database_type: DatabaseType,
+        columns: Arc<[TableColumn]>,
+        values: Vec<NativeType>,
+    ) -> Self {
+        OwnedRow {
+            database_type,
+            columns,
+            values,
+        }
+    }
+}
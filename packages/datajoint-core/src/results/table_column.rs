@@ -1,35 +1,82 @@
 use crate::common::{DatabaseType, DatabaseTypeAgnostic};
+use crate::error::{DataJointError, Error, ErrorCode};
 use crate::types::DataJointType;
 use sqlx::{Column, TypeInfo};
 
 /// Owned data about a table column.
+#[derive(Debug, Clone, PartialEq)]
 pub struct TableColumn {
     pub database_type: DatabaseType,
     pub ordinal: usize,
     pub name: String,
     pub type_name: DataJointType,
+    /// Whether the column may hold nulls, if known.
+    ///
+    /// Only columns returned by [`Executor::describe()`][crate::connection::Executor::describe]
+    /// report it, since the columns of fetched rows do not carry their nullability.
+    pub nullable: Option<bool>,
 }
 
 /// Trait for types that can be used to index columns.
 ///
 /// Currently implemented for string (column names) and numbers (ordinals).
 pub trait ColumnIndex:
-    sqlx::ColumnIndex<sqlx::mysql::MySqlRow> + sqlx::ColumnIndex<sqlx::postgres::PgRow>
+    sqlx::ColumnIndex<sqlx::mysql::MySqlRow>
+    + sqlx::ColumnIndex<sqlx::postgres::PgRow>
+    + sqlx::ColumnIndex<sqlx::sqlite::SqliteRow>
 {
+    /// Returns the ordinal of the indexed column out of the columns of an owned row.
+    fn owned_ordinal(&self, columns: &[TableColumn]) -> Result<usize, Error>;
 }
-impl<T> ColumnIndex for T where
-    T: sqlx::ColumnIndex<sqlx::mysql::MySqlRow> + sqlx::ColumnIndex<sqlx::postgres::PgRow>
+
+impl ColumnIndex for usize {
+    fn owned_ordinal(&self, columns: &[TableColumn]) -> Result<usize, Error> {
+        if *self >= columns.len() {
+            return Err(DataJointError::new_with_message(
+                &format!(
+                    "column index {} is out of bounds for {} columns",
+                    self,
+                    columns.len()
+                ),
+                ErrorCode::ColumnIndexOutOfBounds,
+            ));
+        }
+        Ok(*self)
+    }
+}
+
+impl ColumnIndex for &str {
+    fn owned_ordinal(&self, columns: &[TableColumn]) -> Result<usize, Error> {
+        match columns.iter().position(|column| column.name == *self) {
+            None => Err(DataJointError::new_with_message(
+                &format!("column {} not found", self),
+                ErrorCode::ColumnNotFound,
+            )),
+            Some(ordinal) => Ok(ordinal),
+        }
+    }
+}
+
+impl<I> ColumnIndex for &I
+where
+    I: ColumnIndex,
 {
+    fn owned_ordinal(&self, columns: &[TableColumn]) -> Result<usize, Error> {
+        (**self).owned_ordinal(columns)
+    }
 }
 
 /// A reference to a table column object.
 ///
 /// [`TableRow`][`crate::results::TableRow`] objects share table columns when they are
 /// created from the same query, which is why columns are accessed by reference.
+/// Columns of owned rows are owned by the rows.
 #[derive(Copy, Clone)]
 pub enum TableColumnRef<'r> {
     MySql(&'r sqlx::mysql::MySqlColumn),
     Postgres(&'r sqlx::postgres::PgColumn),
+    Sqlite(&'r sqlx::sqlite::SqliteColumn),
+    Owned(&'r TableColumn),
 }
 
 impl<'r> DatabaseTypeAgnostic for TableColumnRef<'r> {
@@ -37,6 +84,8 @@ impl<'r> DatabaseTypeAgnostic for TableColumnRef<'r> {
         match self {
             Self::MySql(_) => DatabaseType::MySql,
             Self::Postgres(_) => DatabaseType::Postgres,
+            Self::Sqlite(_) => DatabaseType::Sqlite,
+            Self::Owned(column) => column.database_type,
         }
     }
 }
@@ -51,6 +100,8 @@ impl<'r> TableColumnRef<'r> {
         match self {
             Self::MySql(column) => column.ordinal(),
             Self::Postgres(column) => column.ordinal(),
+            Self::Sqlite(column) => column.ordinal(),
+            Self::Owned(column) => column.ordinal,
         }
     }
 
@@ -60,6 +111,8 @@ impl<'r> TableColumnRef<'r> {
         match self {
             Self::MySql(column) => column.name(),
             Self::Postgres(column) => column.name(),
+            Self::Sqlite(column) => column.name(),
+            Self::Owned(column) => &column.name,
         }
     }
 
@@ -126,6 +179,41 @@ impl<'r> TableColumnRef<'r> {
                     _ => Unknown,
                 }
             }
+            Self::Sqlite(column) => TableColumnRef::sqlite_type_name(column.type_info().name()),
+            Self::Owned(column) => column.type_name,
+        }
+    }
+
+    /// Whether the column may hold nulls, or `None` if unknown.
+    ///
+    /// Only owned columns returned by
+    /// [`Executor::describe()`][crate::connection::Executor::describe] know their
+    /// nullability.
+    pub fn nullable(&self) -> Option<bool> {
+        match self {
+            Self::Owned(column) => column.nullable,
+            _ => None,
+        }
+    }
+
+    /// Maps the name of a SQLite storage class or declared type affinity to the
+    /// DataJoint type.
+    ///
+    /// SQLite only declares the types of table columns, so columns of expressions are
+    /// `NULL` until a value is read.
+    pub(crate) fn sqlite_type_name(name: &str) -> DataJointType {
+        use DataJointType::*;
+        match name {
+            "BOOLEAN" => Boolean,
+            "INTEGER" => BigInt,
+            "REAL" => Double,
+            "TEXT" => VarCharN,
+            "BLOB" => Blob,
+            "NUMERIC" => Decimal,
+            "DATE" => Date,
+            "TIME" => Time,
+            "DATETIME" => DateTime,
+            &_ => Unknown,
         }
     }
 
@@ -136,6 +224,7 @@ impl<'r> TableColumnRef<'r> {
             ordinal: self.ordinal(),
             name: self.name().to_string(),
             type_name: self.type_name(),
+            nullable: self.nullable(),
         }
     }
 }
@@ -1,13 +1,14 @@
 use crate::common::{DatabaseType, DatabaseTypeAgnostic};
-use crate::error::{Error, SqlxError};
+use crate::error::{DataJointError, Error, ErrorCode, SqlxError};
 use crate::results::table_column::{ColumnIndex, TableColumnRef};
+use crate::results::OwnedRow;
 use sqlx::Row;
 
 /// Type trait for indicating if a type is safe to be decoded to.
 ///
 /// Currently only implements how SQLx type checks return types.
 pub trait ValueDecodable<'r>:
-    sqlx::Decode<'r, sqlx::MySql> + sqlx::Decode<'r, sqlx::Postgres>
+    sqlx::Decode<'r, sqlx::MySql> + sqlx::Decode<'r, sqlx::Postgres> + sqlx::Decode<'r, sqlx::Sqlite>
 {
 }
 
@@ -16,16 +17,20 @@ impl<'r, T> ValueDecodable<'r> for T where
         + sqlx::Type<sqlx::MySql>
         + sqlx::Decode<'r, sqlx::Postgres>
         + sqlx::Type<sqlx::Postgres>
+        + sqlx::Decode<'r, sqlx::Sqlite>
+        + sqlx::Type<sqlx::Sqlite>
 {
 }
 
 /// A single row in a database table or query result that is used to
 /// read values out of.
 ///
-/// Wraps a SQLx row.
+/// Wraps a SQLx row, or an owned row held in memory.
 pub enum TableRow {
     MySql(sqlx::mysql::MySqlRow),
     Postgres(sqlx::postgres::PgRow),
+    Sqlite(sqlx::sqlite::SqliteRow),
+    Owned(OwnedRow),
 }
 
 impl DatabaseTypeAgnostic for TableRow {
@@ -33,6 +38,8 @@ impl DatabaseTypeAgnostic for TableRow {
         match self {
             Self::MySql(_) => DatabaseType::MySql,
             Self::Postgres(_) => DatabaseType::Postgres,
+            Self::Sqlite(_) => DatabaseType::Sqlite,
+            Self::Owned(row) => row.database_type,
         }
     }
 }
@@ -43,6 +50,8 @@ impl TableRow {
         match self {
             Self::MySql(row) => row.is_empty(),
             Self::Postgres(row) => row.is_empty(),
+            Self::Sqlite(row) => row.is_empty(),
+            Self::Owned(row) => row.columns.is_empty(),
         }
     }
 
@@ -52,6 +61,8 @@ impl TableRow {
         match self {
             Self::MySql(row) => row.columns().iter().map(TableColumnRef::MySql).collect(),
             Self::Postgres(row) => row.columns().iter().map(TableColumnRef::Postgres).collect(),
+            Self::Sqlite(row) => row.columns().iter().map(TableColumnRef::Sqlite).collect(),
+            Self::Owned(row) => row.columns.iter().map(TableColumnRef::Owned).collect(),
         }
     }
 
@@ -61,6 +72,8 @@ impl TableRow {
         match self {
             Self::MySql(row) => row.columns().len(),
             Self::Postgres(row) => row.columns().len(),
+            Self::Sqlite(row) => row.columns().len(),
+            Self::Owned(row) => row.columns.len(),
         }
     }
 
@@ -88,6 +101,13 @@ impl TableRow {
                 Err(err) => Err(SqlxError::new(err)),
                 Ok(column) => Ok(TableColumnRef::Postgres(column)),
             },
+            Self::Sqlite(row) => match row.try_column(index) {
+                Err(err) => Err(SqlxError::new(err)),
+                Ok(column) => Ok(TableColumnRef::Sqlite(column)),
+            },
+            Self::Owned(row) => Ok(TableColumnRef::Owned(
+                &row.columns[index.owned_ordinal(&row.columns)?],
+            )),
         }
     }
 
@@ -103,6 +123,8 @@ impl TableRow {
     }
 
     /// Gets a reference to the value stored at the given column in the row.
+    ///
+    /// Owned rows hold values that are already decoded, so they can only be read with [`try_decode()`][TableRow::try_decode] and its variants.
     pub fn try_get<'r, T, I>(&'r self, index: I) -> Result<T, Error>
     where
         T: ValueDecodable<'r>,
@@ -117,6 +139,14 @@ impl TableRow {
                 Err(err) => Err(SqlxError::new(err)),
                 Ok(value) => Ok(value),
             },
+            Self::Sqlite(row) => match row.try_get_unchecked::<T, I>(index) {
+                Err(err) => Err(SqlxError::new(err)),
+                Ok(value) => Ok(value),
+            },
+            Self::Owned(_) => Err(DataJointError::new_with_message(
+                "owned rows can only be decoded to native types",
+                ErrorCode::ColumnDecodeError,
+            )),
         }
     }
 }
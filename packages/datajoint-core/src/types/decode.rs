@@ -1,7 +1,8 @@
 use crate::error::{DataJointError, Error, ErrorCode, SqlxError};
 use crate::results::{TableColumnRef, TableRow};
 use crate::types::DataJointType;
-use sqlx::Row;
+use sqlx::{Row, TypeInfo, ValueRef};
+use std::convert::TryFrom;
 use std::fmt::{self, Display, Formatter};
 
 /// Enum for a native type and its corresponding value that can be decoded
@@ -49,6 +50,45 @@ impl Display for NativeType {
     }
 }
 
+impl NativeType {
+    /// Returns the name of the native type of the value, such as `int32`.
+    pub(crate) fn type_name(&self) -> &'static str {
+        use NativeType::*;
+        match self {
+            None => "none",
+            Bool(_) => "bool",
+            Int8(_) => "int8",
+            UInt8(_) => "uint8",
+            Int16(_) => "int16",
+            UInt16(_) => "uint16",
+            Int32(_) => "int32",
+            UInt32(_) => "uint32",
+            Int64(_) => "int64",
+            UInt64(_) => "uint64",
+            String(_) => "string",
+            Float32(_) => "float32",
+            Float64(_) => "float64",
+            Bytes(_) => "bytes",
+        }
+    }
+
+    /// Returns the value as a 64-bit signed integer, if it is an integer that fits.
+    pub(crate) fn as_i64(&self) -> Option<i64> {
+        use NativeType::*;
+        match *self {
+            Int8(int) => Some(int.into()),
+            UInt8(int) => Some(int.into()),
+            Int16(int) => Some(int.into()),
+            UInt16(int) => Some(int.into()),
+            Int32(int) => Some(int.into()),
+            UInt32(int) => Some(int.into()),
+            Int64(int) => Some(int),
+            UInt64(int) => i64::try_from(int).ok(),
+            _ => Option::None,
+        }
+    }
+}
+
 impl TableRow {
     fn postgres_unsupported_unsigned_error() -> Error {
         DataJointError::new_with_message(
@@ -57,13 +97,38 @@ impl TableRow {
         )
     }
 
+    fn sqlite_unsupported_unsigned_error() -> Error {
+        DataJointError::new_with_message(
+            "sqlite does not declare unsigned data types",
+            ErrorCode::UnsupportedNativeType,
+        )
+    }
+
     /// Primary implementation of decoding a single column value in a single row.
     ///
     /// Handles null values by returning `None`.
     fn try_decode_impl(&self, column: TableColumnRef) -> Result<Option<NativeType>, Error> {
         use DataJointType::*;
         let index = column.ordinal();
-        match column.type_name() {
+        // Owned rows hold values that are already decoded.
+        if let Self::Owned(row) = self {
+            return match row.values.get(index) {
+                None => Err(DataJointError::new(ErrorCode::ColumnIndexOutOfBounds)),
+                Some(NativeType::None) => Ok(None),
+                Some(value) => Ok(Some(value.clone())),
+            };
+        }
+        let type_name = match (self, column.type_name()) {
+            // SQLite columns of expressions have no declared type, so the type is taken
+            // from the stored value.
+            (Self::Sqlite(row), Unknown) => match row.try_get_raw(index) {
+                Err(err) => return Err(SqlxError::new(err)),
+                Ok(value) if value.is_null() => return Ok(None),
+                Ok(value) => TableColumnRef::sqlite_type_name(value.type_info().name()),
+            },
+            (_, type_name) => type_name,
+        };
+        match type_name {
             Unknown => Err(DataJointError::new_with_message(
                 "unsupported column type",
                 ErrorCode::ColumnDecodeError,
@@ -83,6 +148,8 @@ impl TableRow {
                     Ok(Some(val)) => Ok(Some(NativeType::UInt8(val))),
                 },
                 Self::Postgres(_) => Err(TableRow::postgres_unsupported_unsigned_error()),
+                Self::Sqlite(_) => Err(TableRow::sqlite_unsupported_unsigned_error()),
+                Self::Owned(_) => unreachable!(),
             },
             SmallInt => Ok(match self.try_get::<Option<i16>, usize>(index)? {
                 None => None,
@@ -95,6 +162,8 @@ impl TableRow {
                     Ok(Some(val)) => Ok(Some(NativeType::UInt16(val))),
                 },
                 Self::Postgres(_) => Err(TableRow::postgres_unsupported_unsigned_error()),
+                Self::Sqlite(_) => Err(TableRow::sqlite_unsupported_unsigned_error()),
+                Self::Owned(_) => unreachable!(),
             },
             MediumInt | Int => Ok(match self.try_get::<Option<i32>, usize>(index)? {
                 None => None,
@@ -107,6 +176,8 @@ impl TableRow {
                     Ok(Some(val)) => Ok(Some(NativeType::UInt32(val))),
                 },
                 Self::Postgres(_) => Err(TableRow::postgres_unsupported_unsigned_error()),
+                Self::Sqlite(_) => Err(TableRow::sqlite_unsupported_unsigned_error()),
+                Self::Owned(_) => unreachable!(),
             },
             BigInt => Ok(match self.try_get::<Option<i64>, usize>(index)? {
                 None => None,
@@ -119,6 +190,8 @@ impl TableRow {
                     Ok(Some(val)) => Ok(Some(NativeType::UInt64(val))),
                 },
                 Self::Postgres(_) => Err(TableRow::postgres_unsupported_unsigned_error()),
+                Self::Sqlite(_) => Err(TableRow::sqlite_unsupported_unsigned_error()),
+                Self::Owned(_) => unreachable!(),
             },
             Enum | CharN | VarCharN => Ok(match self.try_get::<Option<String>, usize>(index)? {
                 None => None,
@@ -152,10 +225,22 @@ impl TableRow {
                 None => None,
                 Some(val) => Some(NativeType::Bytes(val)),
             }),
-            Decimal => Ok(match self.try_get::<Option<sqlx::types::BigDecimal>, usize>(index)? {
-                None => None,
-                Some(val) => Some(NativeType::String(val.to_string())),
-            }),
+            Decimal => match self {
+                Self::MySql(row) => match row.try_get_unchecked::<Option<sqlx::types::BigDecimal>, usize>(index) {
+                    Err(err) => Err(SqlxError::new(err)),
+                    Ok(val) => Ok(val.map(|val| NativeType::String(val.to_string()))),
+                },
+                Self::Postgres(row) => match row.try_get_unchecked::<Option<sqlx::types::BigDecimal>, usize>(index) {
+                    Err(err) => Err(SqlxError::new(err)),
+                    Ok(val) => Ok(val.map(|val| NativeType::String(val.to_string()))),
+                },
+                // SQLite stores numeric values as integers or floats, which are read as text.
+                Self::Sqlite(row) => match row.try_get_unchecked::<Option<String>, usize>(index) {
+                    Err(err) => Err(SqlxError::new(err)),
+                    Ok(val) => Ok(val.map(NativeType::String)),
+                },
+                Self::Owned(_) => unreachable!(),
+            },
         }
     }
 
@@ -1,4 +1,5 @@
 use crate::common::DatabaseType;
+use crate::connection::{CacheMode, DelimitedFormat, TlsMode};
 use crate::error::ErrorCode;
 use crate::types::DataJointType;
 use num_traits::FromPrimitive;
@@ -35,3 +36,21 @@ impl IntegerEnum<i32> for DataJointType {
         FromPrimitive::from_i32(val)
     }
 }
+
+impl IntegerEnum<i32> for TlsMode {
+    fn from_int(val: i32) -> Option<Self> {
+        FromPrimitive::from_i32(val)
+    }
+}
+
+impl IntegerEnum<i32> for CacheMode {
+    fn from_int(val: i32) -> Option<Self> {
+        FromPrimitive::from_i32(val)
+    }
+}
+
+impl IntegerEnum<i32> for DelimitedFormat {
+    fn from_int(val: i32) -> Option<Self> {
+        FromPrimitive::from_i32(val)
+    }
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "async")]
+
+use datajoint_core::{
+    common::DatabaseType,
+    connection::{AsyncConnection, ConnectionSettings, MockDatabase, MockRows},
+    error::ErrorCode,
+    results::TableRow,
+    types::{DataJointType, NativeType},
+};
+use futures::stream::StreamExt;
+
+async fn create_tweet_table(con: &AsyncConnection) {
+    con.execute_query(
+        "create table tweet (id integer primary key autoincrement, text text not null, owner_id bigint not null)",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_connection_to_db() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = ":memory:".to_string();
+    let mut con = AsyncConnection::new(settings);
+
+    match con.try_executor() {
+        Ok(_) => panic!("Executor was created before connecting."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::NotConnected),
+    }
+
+    con.connect().await.unwrap();
+    assert!(con.is_connected());
+
+    con.disconnect().await;
+    assert!(!con.is_connected());
+}
+
+#[tokio::test]
+async fn test_executor() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = ":memory:".to_string();
+    let mut con = AsyncConnection::new(settings);
+
+    con.connect().await.unwrap();
+    create_tweet_table(&con).await;
+    let executor = con.executor();
+
+    let rows_affected = executor
+        .execute("insert into tweet (text, owner_id) values ('hello world', 1234);")
+        .await
+        .rows_affected;
+    assert_eq!(rows_affected, 1);
+
+    let placeholders: Vec<NativeType> = vec![
+        NativeType::String("hello world1".to_string()),
+        NativeType::Int64(5678),
+    ];
+    executor
+        .execute_ph(
+            "insert into tweet (text, owner_id) values (?, ?);",
+            placeholders,
+        )
+        .await;
+
+    let count: i64 = executor
+        .fetch_one("select count(*) from tweet")
+        .await
+        .get(0);
+    assert_eq!(count, 2);
+
+    let all_rows: Vec<TableRow> = executor.fetch_all("select * from tweet;").await;
+    assert_eq!(all_rows.len(), 2);
+
+    con.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_cursor_stream() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = ":memory:".to_string();
+    let mut con = AsyncConnection::new(settings);
+
+    con.connect().await.unwrap();
+    create_tweet_table(&con).await;
+    for owner_id in 0..3 {
+        let placeholders: Vec<NativeType> = vec![NativeType::Int64(owner_id)];
+        con.execute_query_ph(
+            "insert into tweet (text, owner_id) values ('hello world', ?);",
+            placeholders,
+        )
+        .await;
+    }
+
+    // Cursors are streams of rows.
+    let owner_ids: Vec<i64> = con
+        .fetch_query("select owner_id from tweet order by owner_id")
+        .await
+        .map(|row| row.unwrap().get::<i64, usize>(0))
+        .collect()
+        .await;
+    assert_eq!(owner_ids, vec![0, 1, 2]);
+
+    let mut cursor = con
+        .fetch_query("select owner_id from tweet order by owner_id")
+        .await;
+    let first: i64 = cursor.next().await.unwrap().unwrap().get(0);
+    assert_eq!(first, 0);
+    assert_eq!(cursor.rest().await.len(), 2);
+    assert!(cursor.next().await.is_none());
+    drop(cursor);
+
+    con.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_mock_database() {
+    let database = MockDatabase::new(DatabaseType::Sqlite);
+    let mut con = AsyncConnection::new_mock(database.clone());
+    con.connect().await.unwrap();
+
+    database.push_rows(
+        MockRows::new(&[("owner_id", DataJointType::BigInt)])
+            .row(vec![NativeType::Int64(0)])
+            .row(vec![NativeType::Int64(1)]),
+    );
+    let owner_ids: Vec<NativeType> = con
+        .fetch_query("select owner_id from tweet")
+        .await
+        .map(|row| {
+            let row = row.unwrap();
+            row.decode(row.column(0))
+        })
+        .collect()
+        .await;
+    assert_eq!(owner_ids, vec![NativeType::Int64(0), NativeType::Int64(1)]);
+    assert_eq!(database.statements()[0].sql, "select owner_id from tweet");
+}
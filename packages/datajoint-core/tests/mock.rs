@@ -0,0 +1,542 @@
+use datajoint_core::{
+    common::DatabaseType,
+    connection::{
+        Connection, ExecuteResult, MockDatabase, MockRows, MockStatement, ReconnectPolicy,
+    },
+    error::ErrorCode,
+    types::{DataJointType, NativeType},
+};
+use std::time::Duration;
+
+fn tweet_rows() -> MockRows {
+    MockRows::new(&[
+        ("text", DataJointType::VarCharN),
+        ("owner_id", DataJointType::BigInt),
+    ])
+    .row(vec![
+        NativeType::String("hello world".to_string()),
+        NativeType::Int64(1234),
+    ])
+    .row(vec![NativeType::None, NativeType::Int64(5678)])
+}
+
+#[test]
+fn test_recorded_statements() {
+    let database = MockDatabase::new(DatabaseType::MySql);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+    assert!(con.is_connected());
+
+    con.execute_query("create table tweet (text text, owner_id bigint)");
+    con.execute_query_ph(
+        "insert into tweet (text, owner_id) values (?, ?)",
+        vec![
+            NativeType::String("hello world".to_string()),
+            NativeType::Int64(1234),
+        ],
+    );
+
+    assert_eq!(
+        database.statements(),
+        vec![
+            MockStatement {
+                sql: "create table tweet (text text, owner_id bigint)".to_string(),
+                arguments: vec![],
+            },
+            MockStatement {
+                sql: "insert into tweet (text, owner_id) values (?, ?)".to_string(),
+                arguments: vec![
+                    NativeType::String("hello world".to_string()),
+                    NativeType::Int64(1234),
+                ],
+            },
+        ]
+    );
+
+    database.clear_statements();
+    assert!(database.statements().is_empty());
+
+    con.disconnect();
+    assert!(!con.is_connected());
+}
+
+#[test]
+fn test_scripted_rows() {
+    let database = MockDatabase::new(DatabaseType::Sqlite);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    database.push_rows(tweet_rows());
+    database.push_rows(tweet_rows());
+    database.push_rows_affected(3);
+    assert_eq!(database.pending_results(), 3);
+
+    let executor = con.executor();
+    let rows = executor.fetch_all("select text, owner_id from tweet");
+    assert_eq!(rows.len(), 2);
+    let cols = rows[0].columns();
+    assert_eq!(cols[0].name(), "text");
+    assert_eq!(cols[1].type_name(), DataJointType::BigInt);
+    assert_eq!(
+        rows[0].decode(rows[0].column("text")),
+        NativeType::String("hello world".to_string())
+    );
+    assert_eq!(rows[1].decode_optional(rows[1].column(0)), None);
+    assert_eq!(
+        rows[1].decode(rows[1].column("owner_id")),
+        NativeType::Int64(5678)
+    );
+
+    let mut cursor = executor.cursor("select text, owner_id from tweet").unwrap();
+    let row = cursor.next().unwrap().unwrap();
+    assert_eq!(row.decode(row.column(1)), NativeType::Int64(1234));
+    assert_eq!(cursor.rest().len(), 1);
+
+    assert_eq!(executor.execute("delete from tweet").rows_affected, 3);
+    assert_eq!(database.pending_results(), 0);
+
+    // Statements without scripted results affect and return no rows.
+    assert_eq!(executor.execute("delete from tweet").rows_affected, 0);
+    assert!(executor.fetch_all("select * from tweet").is_empty());
+    match executor.try_fetch_one("select * from tweet") {
+        Ok(_) => panic!("Row was returned without a scripted result."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::RowNotFound),
+    }
+}
+
+#[test]
+fn test_scripted_errors() {
+    let database = MockDatabase::new(DatabaseType::Postgres);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    database.push_error(ErrorCode::QueryTimedOut, "statement timed out");
+    database.push_error(ErrorCode::UnknownDatabaseError, "duplicate key");
+    let executor = con.executor();
+
+    match executor.try_execute("insert into tweet values (1)") {
+        Ok(_) => panic!("Scripted error was not returned."),
+        Err(err) => {
+            assert_eq!(err.code(), ErrorCode::QueryTimedOut);
+            assert_eq!(err.message(), "statement timed out");
+        }
+    }
+    match executor.cursor("select * from tweet").unwrap().try_next() {
+        Ok(_) => panic!("Scripted error was not returned."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::UnknownDatabaseError),
+    }
+
+    // Arguments are checked as the emulated database would bind them.
+    match executor.try_execute_ph("select $1", vec![NativeType::UInt32(1)]) {
+        Ok(_) => panic!("Unsigned argument was bound for Postgres."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::UnsupportedNativeType),
+    }
+    assert_eq!(database.statements().len(), 2);
+}
+
+#[test]
+fn test_mock_transaction() {
+    let database = MockDatabase::new(DatabaseType::MySql);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    database.push_rows_affected(2);
+    let mut transaction = con.begin();
+    assert_eq!(transaction.execute("delete from tweet").rows_affected, 2);
+    transaction.savepoint().unwrap();
+    assert_eq!(transaction.fetch_all("select * from tweet").len(), 0);
+    transaction.commit().unwrap();
+
+    let transaction = con.begin();
+    transaction.rollback().unwrap();
+
+    let statements: Vec<String> = database
+        .statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect();
+    assert_eq!(
+        statements,
+        vec![
+            "BEGIN",
+            "delete from tweet",
+            "SAVEPOINT _dj_savepoint_1",
+            "select * from tweet",
+            "COMMIT",
+            "BEGIN",
+            "ROLLBACK",
+        ]
+    );
+}
+
+#[test]
+fn test_mock_savepoint_release_failure() {
+    let database = MockDatabase::new(DatabaseType::Postgres);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    let mut transaction = con.begin();
+    transaction.savepoint().unwrap();
+    transaction.savepoint().unwrap();
+    database.push_rows_affected(0);
+    database.push_error(ErrorCode::IoError, "connection reset");
+    match transaction.rollback_to_savepoint() {
+        Ok(_) => panic!("Failed savepoint release did not fail."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::IoError),
+    }
+    // The changes were discarded, so the savepoint is no longer counted.
+    assert_eq!(transaction.savepoint_depth(), 1);
+    transaction.release_savepoint().unwrap();
+    assert_eq!(transaction.savepoint_depth(), 0);
+    transaction.commit().unwrap();
+
+    let statements: Vec<String> = database
+        .statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect();
+    assert_eq!(
+        statements,
+        vec![
+            "BEGIN",
+            "SAVEPOINT _dj_savepoint_1",
+            "SAVEPOINT _dj_savepoint_2",
+            "ROLLBACK TO SAVEPOINT _dj_savepoint_2",
+            "RELEASE SAVEPOINT _dj_savepoint_2",
+            "RELEASE SAVEPOINT _dj_savepoint_1",
+            "COMMIT",
+        ]
+    );
+}
+
+#[test]
+fn test_mock_server_info() {
+    let database = MockDatabase::new(DatabaseType::MySql);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+    con.ping().unwrap();
+
+    database.push_rows(
+        MockRows::new(&[
+            ("version()", DataJointType::VarCharN),
+            ("current_user()", DataJointType::VarCharN),
+            ("database()", DataJointType::VarCharN),
+        ])
+        .row(vec![
+            NativeType::String("8.0.30".to_string()),
+            NativeType::String("root@%".to_string()),
+            NativeType::None,
+        ]),
+    );
+    let info = con.server_info();
+    assert_eq!(info.database_type, DatabaseType::MySql);
+    assert_eq!((info.major_version, info.minor_version), (8, 0));
+    assert_eq!(info.current_user, Some("root@%".to_string()));
+    assert_eq!(info.current_database, None);
+}
+
+#[test]
+fn test_mock_prepared_statement() {
+    let database = MockDatabase::new(DatabaseType::Postgres);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    let executor = con.executor();
+    let statement = executor.prepare("select text from tweet where owner_id = $1");
+    // Mock databases do not describe statements, so arguments are not counted.
+    assert_eq!(statement.parameter_count(), None);
+    assert!(statement.columns().is_empty());
+    assert!(database.statements().is_empty());
+
+    database.push_rows(tweet_rows());
+    assert_eq!(statement.fetch_all(vec![NativeType::Int64(1234)]).len(), 2);
+    assert_eq!(
+        statement
+            .execute(vec![NativeType::Int64(5678)])
+            .rows_affected,
+        0
+    );
+    assert_eq!(
+        database.statements(),
+        vec![
+            MockStatement {
+                sql: "select text from tweet where owner_id = $1".to_string(),
+                arguments: vec![NativeType::Int64(1234)],
+            },
+            MockStatement {
+                sql: "select text from tweet where owner_id = $1".to_string(),
+                arguments: vec![NativeType::Int64(5678)],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_mock_execute_many() {
+    let database = MockDatabase::new(DatabaseType::Postgres);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    database.push_rows_affected(3);
+    let executor = con.executor();
+    let rows_affected = executor.execute_many(
+        "insert into tweet (text, owner_id) values ($1, $2)",
+        (0..3)
+            .map(|owner_id| {
+                vec![
+                    NativeType::String("hi".to_string()),
+                    NativeType::Int64(owner_id),
+                ]
+            })
+            .collect(),
+    );
+    assert_eq!(rows_affected, 3);
+
+    let statements = database.statements();
+    assert_eq!(statements.len(), 1);
+    assert_eq!(
+        statements[0].sql,
+        "insert into tweet (text, owner_id) values ($1, $2), ($3, $4), ($5, $6)"
+    );
+    assert_eq!(statements[0].arguments.len(), 6);
+    assert_eq!(statements[0].arguments[5], NativeType::Int64(2));
+}
+
+#[test]
+fn test_mock_bulk_load() {
+    let database = MockDatabase::new(DatabaseType::Sqlite);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    database.push_rows_affected(2);
+    let rows_loaded = con.bulk_load(
+        "lab.tweet",
+        &["text", "owner_id"],
+        vec![
+            vec![NativeType::String("hi".to_string()), NativeType::Int64(1)],
+            vec![NativeType::None, NativeType::Int64(2)],
+        ],
+    );
+    assert_eq!(rows_loaded, 2);
+
+    let statements = database.statements();
+    assert_eq!(statements.len(), 1);
+    assert_eq!(
+        statements[0].sql,
+        "INSERT INTO \"lab\".\"tweet\" (\"text\", \"owner_id\") VALUES (?, ?), (?, ?)"
+    );
+    assert_eq!(statements[0].arguments[2], NativeType::None);
+
+    // The MySQL driver cannot load local files, so MySQL also loads with inserts.
+    let database = MockDatabase::new(DatabaseType::MySql);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+    database.push_rows_affected(1);
+    assert_eq!(
+        con.bulk_load("tweet", &["text"], vec![vec![NativeType::None]]),
+        1
+    );
+    assert_eq!(
+        database.statements()[0].sql,
+        "INSERT INTO `tweet` (`text`) VALUES (?)"
+    );
+}
+
+#[test]
+fn test_mock_execute_script() {
+    let database = MockDatabase::new(DatabaseType::MySql);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    database.push_rows_affected(0);
+    database.push_error(
+        ErrorCode::UnknownDatabaseError,
+        "table tweet already exists",
+    );
+    let executor = con.executor();
+    let result = executor.execute_script(
+        "create table user (id int);\ncreate table tweet (text text);\ninsert into user values (1);",
+        true,
+    );
+    assert!(!result.is_success());
+    assert_eq!(result.statements().len(), 3);
+    assert_eq!(result.rows_affected(), &[0]);
+    assert_eq!(result.failed_statement(), Some(1));
+    assert_eq!(
+        result.error().unwrap().code(),
+        ErrorCode::UnknownDatabaseError
+    );
+    match result.into_result() {
+        Ok(_) => panic!("Failing script succeeded."),
+        Err(err) => assert_eq!(
+            err.message(),
+            "statement 1 of script failed: table tweet already exists"
+        ),
+    }
+
+    let statements: Vec<String> = database
+        .statements()
+        .into_iter()
+        .map(|statement| statement.sql)
+        .collect();
+    assert_eq!(
+        statements,
+        vec![
+            "BEGIN",
+            "create table user (id int)",
+            "create table tweet (text text)",
+            "ROLLBACK",
+        ]
+    );
+}
+
+#[test]
+fn test_mock_cursor_iterator() {
+    let database = MockDatabase::new(DatabaseType::MySql);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    database.push_rows(tweet_rows());
+    let owner_ids: Vec<NativeType> = con
+        .fetch_query("select text, owner_id from tweet")
+        .map(|row| {
+            let row = row.unwrap();
+            row.decode(row.column("owner_id"))
+        })
+        .collect();
+    assert_eq!(
+        owner_ids,
+        vec![NativeType::Int64(1234), NativeType::Int64(5678)]
+    );
+
+    database.push_rows(tweet_rows());
+    let mut cursor = con.fetch_query("select text, owner_id from tweet");
+    assert_eq!(cursor.next_batch(1).len(), 1);
+    assert_eq!(cursor.next_batch(5).len(), 1);
+    assert!(cursor.next_batch(5).is_empty());
+    match cursor.try_next_batch(0) {
+        Ok(_) => panic!("Batch of 0 rows did not fail."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::InvalidBatchSize),
+    }
+    assert!(cursor.next().is_none());
+    match cursor.try_next() {
+        Ok(_) => panic!("Cursor returned a row after the rows ran out."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::NoMoreRows),
+    }
+}
+
+#[test]
+fn test_mock_cursor_columns() {
+    let database = MockDatabase::new(DatabaseType::Postgres);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    database.push_rows(tweet_rows());
+    let mut cursor = con.fetch_query("select text, owner_id from tweet");
+    let columns = cursor.columns();
+    assert_eq!(columns[0].name, "text");
+    assert_eq!(columns[1].type_name, DataJointType::BigInt);
+    assert_eq!(columns[1].nullable, None);
+
+    // The columns are read from the next row, which is still returned, and kept after the
+    // rows run out.
+    assert_eq!(cursor.rest().len(), 2);
+    assert_eq!(cursor.columns().len(), 2);
+}
+
+#[test]
+fn test_mock_execute_result() {
+    let database = MockDatabase::new(DatabaseType::MySql);
+    let mut con = Connection::new_mock(database.clone());
+    con.connect().unwrap();
+
+    database.push_execute_result(ExecuteResult {
+        rows_affected: 2,
+        last_insert_id: Some(7),
+        warnings: Some(1),
+    });
+    let mut executor = con.executor();
+    let result = executor.execute("insert into tweet (text) values ('hello'), ('world')");
+    assert_eq!(result.rows_affected, 2);
+    assert_eq!(result.last_insert_id, Some(7));
+    assert_eq!(result.warnings, None);
+
+    // Warnings are only reported once the executor counts them.
+    executor.set_count_warnings(true);
+    database.push_execute_result(ExecuteResult {
+        rows_affected: 1,
+        last_insert_id: Some(8),
+        warnings: Some(1),
+    });
+    assert_eq!(
+        executor
+            .execute("insert into tweet (text) values ('hello')")
+            .warnings,
+        Some(1)
+    );
+    executor.set_count_warnings(false);
+
+    // Warnings fail the statement, which has already run, with the messages of the server.
+    executor.set_warnings_as_errors(true);
+    database.push_execute_result(ExecuteResult {
+        rows_affected: 1,
+        last_insert_id: Some(9),
+        warnings: Some(1),
+    });
+    database.push_rows(
+        MockRows::new(&[
+            ("Level", DataJointType::VarCharN),
+            ("Code", DataJointType::BigInt),
+            ("Message", DataJointType::VarCharN),
+        ])
+        .row(vec![
+            NativeType::String("Warning".to_string()),
+            NativeType::Int64(1265),
+            NativeType::String("Data truncated for column 'text' at row 1".to_string()),
+        ]),
+    );
+    match executor.try_execute("insert into tweet (text) values ('hello world')") {
+        Ok(_) => panic!("Warnings did not fail the statement."),
+        Err(err) => {
+            assert_eq!(err.code(), ErrorCode::StatementWarnings);
+            assert!(err
+                .message()
+                .contains("Warning 1265: Data truncated for column 'text' at row 1"));
+        }
+    }
+    assert_eq!(database.statements().last().unwrap().sql, "SHOW WARNINGS");
+
+    // Statements without warnings still succeed.
+    database.push_rows_affected(1);
+    assert_eq!(executor.execute("delete from tweet").last_insert_id, None);
+}
+
+#[test]
+fn test_mock_reconnect_closed_pool() {
+    let database = MockDatabase::new(DatabaseType::MySql);
+    let mut con = Connection::new_mock(database.clone());
+    con.settings.reconnect = Some(ReconnectPolicy {
+        max_attempts: 1,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(1),
+    });
+    con.connect().unwrap();
+
+    // The closed pool is replaced by a new pool, which the connection keeps using.
+    let executor = con.executor();
+    con.disconnect();
+    assert!(!con.is_connected());
+    database.push_rows_affected(1);
+    assert_eq!(executor.execute("delete from tweet").rows_affected, 1);
+    assert!(con.is_connected());
+
+    // Without a policy, statements fail once the pool is closed.
+    con.settings.reconnect = None;
+    let executor = con.executor();
+    con.disconnect();
+    match executor.try_execute("delete from tweet") {
+        Ok(_) => panic!("Statement ran over a closed pool."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::PoolClosed),
+    }
+    assert!(!con.is_connected());
+}
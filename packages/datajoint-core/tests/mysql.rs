@@ -1,9 +1,16 @@
 use datajoint_core::{
     common::DatabaseType,
-    connection::{Connection, ConnectionSettings},
+    connection::{
+        CancelHandle, Connection, ConnectionSettings, DelimitedFormat, QueryEvent, QueryObserver,
+        ReconnectPolicy, TlsMode,
+    },
+    error::ErrorCode,
     results::TableRow,
-    types::NativeType,
+    types::{DataJointType, NativeType},
 };
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[test]
 fn test_connection_to_db() {
@@ -13,7 +20,7 @@ fn test_connection_to_db() {
     settings.port = 3306;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "mysql_5_6".to_string();
     let mut conn = Connection::new(settings);
 
@@ -38,7 +45,7 @@ fn test_insert_and_retrieve_one_row() {
     settings.port = 3306;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "mysql_5_6".to_string();
     let mut con = Connection::new(settings);
 
@@ -89,7 +96,7 @@ fn test_insert_and_retrieve_multiple_rows() {
     settings.port = 3306;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "mysql_5_6".to_string();
     let mut con = Connection::new(settings);
 
@@ -129,7 +136,7 @@ fn test_placeholders() {
     settings.port = 3306;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "mysql_5_6".to_string();
     let mut con = Connection::new(settings);
 
@@ -167,7 +174,7 @@ fn test_try_query_after_disconnect() {
     settings.port = 3306;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "mysql_5_6".to_string();
     let mut con = Connection::new(settings);
 
@@ -191,7 +198,7 @@ fn test_postgres_query_to_mysql_db() {
     settings.port = 3306;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "mysql_5_6".to_string();
     let mut con = Connection::new(settings);
 
@@ -212,7 +219,7 @@ fn test_executor() {
     settings.port = 3306;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "mysql_5_6".to_string();
     let mut con = Connection::new(settings);
 
@@ -222,8 +229,9 @@ fn test_executor() {
     let executor = con.executor();
 
     // Execute a non-returning query.
-    let rows_affected: u64 =
-        executor.execute("insert into tweet (text, owner_id) values ('hello world', 1234);");
+    let rows_affected: u64 = executor
+        .execute("insert into tweet (text, owner_id) values ('hello world', 1234);")
+        .rows_affected;
     assert!(rows_affected == 1, "Rows affected did not equal 1.");
 
     // Fetch a single row, ignoring the rest.
@@ -242,10 +250,12 @@ fn test_executor() {
         NativeType::String("hello world".to_string()),
         NativeType::Int32(1234),
     ];
-    let rows_affected = executor.execute_ph(
-        "insert into tweet (text, owner_id) values (?, ?);",
-        placeholders,
-    );
+    let rows_affected = executor
+        .execute_ph(
+            "insert into tweet (text, owner_id) values (?, ?);",
+            placeholders,
+        )
+        .rows_affected;
     assert!(rows_affected == 1, "Rows affected did not equal 1.");
 
     let placeholders: Vec<NativeType> = vec![
@@ -309,7 +319,7 @@ fn run_test() {
     settings.port = 3306;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "mysql_5_6".to_string();
     let mut con = Connection::new(settings);
 
@@ -336,3 +346,671 @@ fn run_test() {
 
     con.disconnect()
 }
+
+#[test]
+fn test_pool_settings() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    settings.max_connections = 2;
+    settings.min_connections = 1;
+    settings.acquire_timeout = Duration::from_secs(5);
+    settings.idle_timeout = None;
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    con.execute_query("truncate tweet");
+    con.execute_query("insert into tweet (text, owner_id) values ('hello world', 1234);");
+
+    // An open cursor holds one pooled connection, so the next query needs a second one.
+    let mut cursor = con.fetch_query("select * from tweet");
+    cursor.next().unwrap().unwrap();
+    let rows_affected =
+        con.execute_query("insert into tweet (text, owner_id) values ('hello world1', 5678);");
+    assert!(rows_affected == 1, "Rows affected did not equal 1.");
+    drop(cursor);
+
+    con.disconnect();
+
+    con.settings.max_connections = 0;
+    match con.connect() {
+        Ok(_) => panic!("Connection did not fail."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::ConfigurationError),
+    }
+}
+
+#[test]
+fn test_tls_settings() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Disabled;
+    settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.disconnect();
+}
+
+#[test]
+fn test_transaction() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    con.execute_query("delete from tweet where owner_id = 4321;");
+    let count_query = "select text from tweet where owner_id = 4321;";
+
+    // Rolled back changes are discarded.
+    let mut transaction = con.begin();
+    transaction.execute("insert into tweet (text, owner_id) values ('rolled back', 4321);");
+    assert!(transaction.fetch_all(count_query).len() == 1);
+    transaction.rollback().unwrap();
+    assert!(con.executor().fetch_all(count_query).is_empty());
+
+    // Dropped transactions are rolled back.
+    let mut transaction = con.begin();
+    transaction.execute("insert into tweet (text, owner_id) values ('dropped', 4321);");
+    drop(transaction);
+    assert!(con.executor().fetch_all(count_query).is_empty());
+
+    // Savepoints discard only the changes made after them.
+    let mut transaction = con.begin();
+    transaction.execute("insert into tweet (text, owner_id) values ('committed', 4321);");
+    transaction.savepoint().unwrap();
+    transaction.execute("insert into tweet (text, owner_id) values ('rolled back', 4321);");
+    assert!(transaction.savepoint_depth() == 1);
+    transaction.rollback_to_savepoint().unwrap();
+    assert!(transaction.savepoint_depth() == 0);
+    match transaction.release_savepoint() {
+        Ok(_) => panic!("Releasing a missing savepoint did not fail."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::NoActiveSavepoint),
+    }
+    let rows: Vec<TableRow> = transaction.cursor(count_query).unwrap().rest();
+    assert!(rows.len() == 1, "Transaction did not see its own rows.");
+    transaction.commit().unwrap();
+
+    let rows = con.executor().fetch_all(count_query);
+    assert!(rows.len() == 1, "Committed row count did not equal 1.");
+    assert!(rows[0].decode(rows[0].column(0)) == NativeType::String("committed".to_string()));
+
+    con.execute_query("delete from tweet where owner_id = 4321;");
+    con.disconnect();
+}
+
+#[test]
+fn test_reconnect() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    settings.max_connections = 1;
+    settings.reconnect = Some(ReconnectPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(50),
+        max_backoff: Duration::from_millis(500),
+    });
+    let mut admin_settings = ConnectionSettings::new();
+    admin_settings.database_type = DatabaseType::MySql;
+    admin_settings.username = "root".to_string();
+    admin_settings.port = 3306;
+    admin_settings.password = "password".to_string();
+    admin_settings.database_name = "datajoint_core".to_string();
+    admin_settings.tls.mode = TlsMode::Preferred;
+    admin_settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+    let mut admin = Connection::new(admin_settings);
+
+    con.connect().unwrap();
+    admin.connect().unwrap();
+
+    // Kill the only pooled connection behind the reconnecting connection's back.
+    let row = con
+        .executor()
+        .fetch_one("select cast(connection_id() as signed)");
+    let session_id = match row.decode(row.column(0)) {
+        NativeType::Int64(id) => id,
+        _ => panic!("Session id was not an integer."),
+    };
+    admin
+        .executor()
+        .try_execute(&format!("kill {}", session_id))
+        .unwrap();
+
+    let rows = con.executor().fetch_all("select 1");
+    assert!(rows.len() == 1, "Query did not succeed after reconnecting.");
+
+    admin.disconnect();
+    con.disconnect();
+}
+
+#[test]
+fn test_concurrent_fetch_all() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Connection>();
+
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    // Every thread shares the same connection and runs its queries at the same time.
+    let query = (1..=100)
+        .map(|i| format!("select {}", i))
+        .collect::<Vec<String>>()
+        .join(" union all ");
+    std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                scope.spawn(|| {
+                    for _ in 0..10 {
+                        let rows = con.executor().fetch_all(&query);
+                        assert_eq!(rows.len(), 100);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    });
+    con.disconnect();
+}
+
+#[test]
+fn test_session_settings() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    settings.session.charset = Some("utf8mb4".to_string());
+    settings.session.time_zone = Some("+00:00".to_string());
+    settings.session.sql_mode = Some("ANSI_QUOTES,STRICT_ALL_TABLES".to_string());
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    let row = con
+        .executor()
+        .fetch_one("select @@character_set_client, @@time_zone, @@sql_mode");
+    let charset: String = row.get(0);
+    let time_zone: String = row.get(1);
+    let sql_mode: String = row.get(2);
+    assert_eq!(charset, "utf8mb4");
+    assert_eq!(time_zone, "+00:00");
+    assert_eq!(sql_mode, "ANSI_QUOTES,STRICT_ALL_TABLES");
+    con.disconnect();
+
+    con.settings.session.search_path = Some("public".to_string());
+    match con.connect() {
+        Ok(_) => panic!("search_path did not fail for MySQL."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::ConfigurationError),
+    }
+}
+
+#[test]
+fn test_socket_connection() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    // The hostname cannot be resolved, so only the socket can be used.
+    settings.hostname = "unreachable.invalid".to_string();
+    let socket_path = std::env::var("DJ_TEST_MYSQL_SOCKET")
+        .unwrap_or_else(|_| "/var/run/mysqld/mysqld.sock".to_string());
+    settings.socket_path = Some(PathBuf::from(socket_path));
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    let row = con.executor().fetch_one("select 1");
+    let one: i64 = row.get(0);
+    assert_eq!(one, 1);
+    con.disconnect();
+
+    con.settings.socket_path = Some(PathBuf::from("/nonexistent/datajoint.sock"));
+    assert!(con.connect().is_err(), "Connection did not fail.");
+}
+
+#[test]
+fn test_server_info() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.ping().unwrap();
+
+    let info = con.server_info();
+    assert_eq!(info.database_type, DatabaseType::MySql);
+    // DataJoint clients pick version-dependent SQL from the parsed version.
+    assert!(info.major_version >= 5);
+    assert!(info
+        .version
+        .starts_with(&format!("{}.{}", info.major_version, info.minor_version)));
+    assert!(info.current_user.unwrap().starts_with("root@"));
+    assert_eq!(info.current_database, Some("datajoint_core".to_string()));
+    con.disconnect();
+
+    match con.ping() {
+        Ok(_) => panic!("Ping did not fail after disconnecting."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::NotConnected),
+    }
+}
+
+#[test]
+fn test_statement_timeout() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    settings.statement_timeout = Some(Duration::from_millis(200));
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    let mut executor = con.executor();
+    assert_eq!(executor.timeout(), Some(Duration::from_millis(200)));
+
+    let start = Instant::now();
+    match executor.try_execute("select sleep(5)") {
+        Ok(_) => panic!("Statement did not time out."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::QueryTimedOut),
+    }
+    let mut cursor = executor.cursor("select sleep(5)").unwrap();
+    match cursor.try_next() {
+        Ok(_) => panic!("Cursor did not time out."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::QueryTimedOut),
+    }
+    drop(cursor);
+    assert!(start.elapsed() < Duration::from_secs(4));
+
+    // Timed out statements are stopped on the server.
+    executor.set_timeout(None);
+    std::thread::sleep(Duration::from_millis(500));
+    let rows = executor
+        .fetch_all("select id from information_schema.processlist where info = 'select sleep(5)'");
+    assert_eq!(rows.len(), 0);
+
+    executor.set_timeout(Some(Duration::from_secs(10)));
+    executor.fetch_one("select sleep(0.1)");
+    con.disconnect();
+}
+
+#[test]
+fn test_cancel_query() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    let handle = con.cancel_handle();
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(Duration::from_millis(200));
+            handle.cancel();
+        });
+        match con.try_execute_query("select sleep(5)") {
+            Ok(_) => panic!("Statement was not cancelled."),
+            Err(err) => assert_eq!(err.code(), ErrorCode::QueryCancelled),
+        }
+    });
+
+    // Executors may use their own handle.
+    let mut executor = con.executor();
+    let cursor_handle = CancelHandle::new();
+    executor.set_cancel_handle(cursor_handle.clone());
+    let mut cursor = executor.cursor("select sleep(5)").unwrap();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(Duration::from_millis(200));
+            cursor_handle.cancel();
+        });
+        match cursor.try_next() {
+            Ok(_) => panic!("Cursor was not cancelled."),
+            Err(err) => assert_eq!(err.code(), ErrorCode::QueryCancelled),
+        }
+    });
+    drop(cursor);
+    assert!(start.elapsed() < Duration::from_secs(4));
+
+    // Earlier cancellations do not affect new statements.
+    let row = con.executor().fetch_one("select 1");
+    assert_eq!(row.get::<i64, usize>(0), 1);
+    con.disconnect();
+}
+
+#[derive(Default)]
+struct RecordingObserver {
+    events: Mutex<Vec<(String, Vec<String>, u64, ErrorCode)>>,
+}
+
+impl QueryObserver for RecordingObserver {
+    fn on_query(&self, event: &QueryEvent) {
+        self.events.lock().unwrap().push((
+            event.query.to_string(),
+            event.arguments.to_vec(),
+            event.rows,
+            event.error_code,
+        ));
+    }
+}
+
+#[test]
+fn test_query_observer() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+    let observer = Arc::new(RecordingObserver::default());
+    con.set_query_observer(Some(observer.clone()));
+
+    con.connect().unwrap();
+    let executor = con.executor();
+    let query = "select ? union all select 2";
+    executor.execute_ph(query, vec![NativeType::Int32(1)]);
+    assert_eq!(executor.fetch_all("select 1 union all select 2").len(), 2);
+    assert_eq!(
+        executor
+            .cursor("select 1 union all select 2")
+            .unwrap()
+            .rest()
+            .len(),
+        2
+    );
+    match executor.try_fetch_one("select * from missing_table") {
+        Ok(_) => panic!("Query of a missing table did not fail."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::UnknownDatabaseError),
+    }
+
+    let events = observer.events.lock().unwrap();
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[0].0, query);
+    assert_eq!(events[0].1, vec!["<int32>".to_string()]);
+    assert_eq!(events[1].2, 2);
+    assert_eq!(events[2].2, 2);
+    assert_eq!(events[3].3, ErrorCode::UnknownDatabaseError);
+    drop(events);
+    con.disconnect();
+}
+
+#[test]
+fn test_execute_many() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.execute_query("drop table if exists tweet_batch");
+    con.execute_query("create table tweet_batch (text varchar(255) not null, owner_id bigint)");
+
+    // The rows take up more than the default max_allowed_packet, so they are split
+    // into several statements.
+    let executor = con.executor();
+    let text = "x".repeat(250);
+    let args: Vec<Vec<NativeType>> = (0..20000)
+        .map(|owner_id| {
+            vec![
+                NativeType::String(text.clone()),
+                NativeType::Int64(owner_id),
+            ]
+        })
+        .collect();
+    assert_eq!(
+        executor.execute_many(
+            "insert into tweet_batch (text, owner_id) values (?, ?)",
+            args
+        ),
+        20000
+    );
+    let row = executor.fetch_one("select count(*) from tweet_batch");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(20000));
+
+    con.execute_query("drop table tweet_batch");
+    con.disconnect();
+}
+
+#[test]
+fn test_bulk_load() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.execute_query("drop table if exists spike");
+    con.execute_query("create table spike (unit int not null, label varchar(255), waveform blob)");
+
+    // The driver does not support LOAD DATA LOCAL INFILE, so rows are loaded with batched
+    // inserts.
+    let rows: Vec<Vec<NativeType>> = (0..20000)
+        .map(|unit| {
+            vec![
+                NativeType::Int32(unit),
+                NativeType::None,
+                NativeType::Bytes(vec![0, 1, 255]),
+            ]
+        })
+        .collect();
+    assert_eq!(
+        con.bulk_load("spike", &["unit", "label", "waveform"], rows),
+        20000
+    );
+    assert_eq!(
+        con.bulk_load_reader(
+            "spike",
+            &["unit", "label"],
+            "-1,\"quoted, label\"\n".as_bytes(),
+            DelimitedFormat::Csv
+        ),
+        1
+    );
+
+    let executor = con.executor();
+    let row = executor.fetch_one("select count(*), count(label) from spike");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(20001));
+    assert_eq!(row.decode(row.column(1)), NativeType::Int64(1));
+    let row = executor.fetch_one("select waveform from spike where unit = 7");
+    assert_eq!(
+        row.decode(row.column(0)),
+        NativeType::Bytes(vec![0, 1, 255])
+    );
+
+    con.execute_query("drop table spike");
+    con.disconnect();
+}
+
+#[test]
+fn test_execute_script() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    let executor = con.executor();
+    let script = r#"
+        drop table if exists script_tweet;
+        drop procedure if exists script_insert;
+        create table script_tweet (id int auto_increment primary key, text varchar(255) not null);
+        # Semicolons inside of the procedure body do not end the statement.
+        DELIMITER //
+        create procedure script_insert(in value varchar(255))
+        begin
+            insert into script_tweet (text) values (value);
+            insert into script_tweet (text) values (concat(value, ';'));
+        end//
+        DELIMITER ;
+        set @text = 'it\'s; quoted';
+        call script_insert(@text);
+    "#;
+    let result = executor.execute_script(script, false);
+    assert!(result.is_success(), "{:?}", result.error());
+    assert_eq!(result.statements().len(), 6);
+    let row = executor.fetch_one("select count(*) from script_tweet");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(2));
+
+    let result = executor.execute_script("select 1; select * from missing_table; select 2;", false);
+    assert_eq!(result.failed_statement(), Some(1));
+    assert_eq!(result.rows_affected().len(), 1);
+
+    executor.execute_script(
+        "drop procedure script_insert; drop table script_tweet;",
+        false,
+    );
+    con.disconnect();
+}
+
+#[test]
+fn test_describe() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.execute_query("drop table if exists described");
+    con.execute_query("create table described (id int not null, note text)");
+    let executor = con.executor();
+
+    let columns = executor.describe("select id, note from described where id = ?");
+    let names: Vec<&str> = columns.iter().map(|column| column.name.as_str()).collect();
+    assert_eq!(names, vec!["id", "note"]);
+    assert_eq!(columns[0].type_name, DataJointType::Int);
+    assert_eq!(columns[0].nullable, Some(false));
+    assert_eq!(columns[1].nullable, Some(true));
+    assert!(executor.describe("delete from described").is_empty());
+
+    let mut cursor = executor.cursor("select note from described").unwrap();
+    assert_eq!(cursor.columns()[0].name, "note");
+    assert!(cursor.next().is_none());
+
+    con.execute_query("drop table described");
+    con.disconnect();
+}
+
+#[test]
+fn test_execute_result() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::MySql;
+    settings.username = "root".to_string();
+    settings.port = 3306;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "mysql_5_6".to_string();
+    settings.session.sql_mode = Some("".to_string());
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.execute_query("drop table if exists inserted");
+    con.execute_query("create table inserted (id int auto_increment primary key, note varchar(4))");
+    let mut executor = con.executor();
+
+    // Multi-row inserts report the id of the first row.
+    let result = executor.execute("insert into inserted (note) values ('a'), ('b')");
+    assert_eq!(result.rows_affected, 2);
+    assert_eq!(result.last_insert_id, Some(1));
+    assert_eq!(result.warnings, None);
+
+    // Without a strict sql_mode, long values are truncated with a warning.
+    executor.set_count_warnings(true);
+    let result = executor.execute("insert into inserted (note) values ('truncated')");
+    assert_eq!(result.last_insert_id, Some(3));
+    assert_eq!(result.warnings, Some(1));
+    let result = executor.execute("insert into inserted (note) values ('e')");
+    assert_eq!(result.warnings, Some(0));
+    executor.set_count_warnings(false);
+
+    executor.set_warnings_as_errors(true);
+    match executor.try_execute("insert into inserted (note) values ('truncated')") {
+        Ok(_) => panic!("Warnings did not fail the statement."),
+        Err(err) => {
+            assert_eq!(err.code(), ErrorCode::StatementWarnings);
+            assert!(err.message().contains("1265"));
+        }
+    }
+    let result = executor.execute("update inserted set note = 'c' where id = 1");
+    assert_eq!(result.rows_affected, 1);
+    assert_eq!(result.last_insert_id, None);
+
+    con.execute_query("drop table inserted");
+    con.disconnect();
+}
@@ -1,9 +1,16 @@
 use datajoint_core::{
     common::DatabaseType,
-    connection::{Connection, ConnectionSettings},
+    connection::{
+        CancelHandle, Connection, ConnectionSettings, CredentialProvider, DelimitedFormat,
+        QueryEvent, QueryObserver, ReconnectPolicy, TlsMode,
+    },
+    error::ErrorCode,
     results::TableRow,
-    types::NativeType,
+    types::{DataJointType, NativeType},
 };
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[test]
 fn test_connection_to_db() {
@@ -13,7 +20,7 @@ fn test_connection_to_db() {
     settings.port = 5432;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "postgres_13".to_string();
     let mut conn = Connection::new(settings);
 
@@ -36,7 +43,7 @@ fn test_insert_and_retrieve_one_row() {
     settings.port = 5432;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "postgres_13".to_string();
     let mut con = Connection::new(settings);
 
@@ -79,7 +86,7 @@ fn test_insert_and_retrieve_multiple_rows() {
     settings.port = 5432;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "postgres_13".to_string();
     let mut con = Connection::new(settings);
 
@@ -119,7 +126,7 @@ fn test_placeholders() {
     settings.port = 5432;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "postgres_13".to_string();
     let mut con = Connection::new(settings);
 
@@ -157,7 +164,7 @@ fn test_try_query_after_disconnect() {
     settings.port = 5432;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "postgres_13".to_string();
     let mut con = Connection::new(settings);
 
@@ -180,7 +187,7 @@ fn test_executor() {
     settings.port = 5432;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "postgres_13".to_string();
     let mut con = Connection::new(settings);
 
@@ -190,8 +197,9 @@ fn test_executor() {
     let executor = con.executor();
 
     // Execute a non-returning query.
-    let rows_affected: u64 =
-        executor.execute("insert into tweet (text, owner_id) values ('hello world', 1234);");
+    let rows_affected: u64 = executor
+        .execute("insert into tweet (text, owner_id) values ('hello world', 1234);")
+        .rows_affected;
     assert!(rows_affected == 1, "Rows affected did not equal 1.");
 
     // Fetch a single row, ignoring the rest.
@@ -210,10 +218,12 @@ fn test_executor() {
         NativeType::String("hello world".to_string()),
         NativeType::Int32(1234),
     ];
-    let rows_affected = executor.execute_ph(
-        "insert into tweet (text, owner_id) values ($1, $2);",
-        placeholders,
-    );
+    let rows_affected = executor
+        .execute_ph(
+            "insert into tweet (text, owner_id) values ($1, $2);",
+            placeholders,
+        )
+        .rows_affected;
     assert!(rows_affected == 1, "Rows affected did not equal 1.");
 
     let placeholders: Vec<NativeType> = vec![NativeType::Int32(1234)];
@@ -274,7 +284,7 @@ fn run_test() {
     settings.port = 5432;
     settings.password = "password".to_string();
     settings.database_name = "datajoint_core".to_string();
-    settings.use_tls = Some(true);
+    settings.tls.mode = TlsMode::Preferred;
     settings.hostname = "postgres_13".to_string();
     let mut con = Connection::new(settings);
 
@@ -300,3 +310,908 @@ fn run_test() {
 
     con.disconnect()
 }
+
+#[test]
+fn test_pool_settings() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    settings.max_connections = 2;
+    settings.min_connections = 1;
+    settings.acquire_timeout = Duration::from_secs(5);
+    settings.idle_timeout = None;
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    con.execute_query("truncate tweet");
+    con.execute_query("insert into tweet (text, owner_id) values ('hello world', 1234);");
+
+    // An open cursor holds one pooled connection, so the next query needs a second one.
+    let mut cursor = con.fetch_query("select * from tweet");
+    cursor.next().unwrap().unwrap();
+    let rows_affected =
+        con.execute_query("insert into tweet (text, owner_id) values ('hello world1', 5678);");
+    assert!(rows_affected == 1, "Rows affected did not equal 1.");
+    drop(cursor);
+
+    con.disconnect();
+
+    con.settings.max_connections = 0;
+    match con.connect() {
+        Ok(_) => panic!("Connection did not fail."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::ConfigurationError),
+    }
+}
+
+#[test]
+fn test_tls_settings() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Disabled;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.disconnect();
+}
+
+#[test]
+fn test_transaction() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    con.execute_query("delete from tweet where owner_id = 4321;");
+    let count_query = "select text from tweet where owner_id = 4321;";
+
+    // Rolled back changes are discarded.
+    let mut transaction = con.begin();
+    transaction.execute("insert into tweet (text, owner_id) values ('rolled back', 4321);");
+    assert!(transaction.fetch_all(count_query).len() == 1);
+    transaction.rollback().unwrap();
+    assert!(con.executor().fetch_all(count_query).is_empty());
+
+    // Dropped transactions are rolled back.
+    let mut transaction = con.begin();
+    transaction.execute("insert into tweet (text, owner_id) values ('dropped', 4321);");
+    drop(transaction);
+    assert!(con.executor().fetch_all(count_query).is_empty());
+
+    // Savepoints discard only the changes made after them.
+    let mut transaction = con.begin();
+    transaction.execute("insert into tweet (text, owner_id) values ('committed', 4321);");
+    transaction.savepoint().unwrap();
+    transaction.execute("insert into tweet (text, owner_id) values ('rolled back', 4321);");
+    assert!(transaction.savepoint_depth() == 1);
+    transaction.rollback_to_savepoint().unwrap();
+    assert!(transaction.savepoint_depth() == 0);
+    match transaction.release_savepoint() {
+        Ok(_) => panic!("Releasing a missing savepoint did not fail."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::NoActiveSavepoint),
+    }
+    let rows: Vec<TableRow> = transaction.cursor(count_query).unwrap().rest();
+    assert!(rows.len() == 1, "Transaction did not see its own rows.");
+    transaction.commit().unwrap();
+
+    let rows = con.executor().fetch_all(count_query);
+    assert!(rows.len() == 1, "Committed row count did not equal 1.");
+    assert!(rows[0].decode(rows[0].column(0)) == NativeType::String("committed".to_string()));
+
+    con.execute_query("delete from tweet where owner_id = 4321;");
+    con.disconnect();
+}
+
+#[test]
+fn test_reconnect() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    settings.max_connections = 1;
+    settings.reconnect = Some(ReconnectPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(50),
+        max_backoff: Duration::from_millis(500),
+    });
+    let mut admin_settings = ConnectionSettings::new();
+    admin_settings.database_type = DatabaseType::Postgres;
+    admin_settings.username = "postgres".to_string();
+    admin_settings.port = 5432;
+    admin_settings.password = "password".to_string();
+    admin_settings.database_name = "datajoint_core".to_string();
+    admin_settings.tls.mode = TlsMode::Preferred;
+    admin_settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+    let mut admin = Connection::new(admin_settings);
+
+    con.connect().unwrap();
+    admin.connect().unwrap();
+
+    // Kill the only pooled connection behind the reconnecting connection's back.
+    let row = con
+        .executor()
+        .fetch_one("select cast(pg_backend_pid() as bigint)");
+    let session_id = match row.decode(row.column(0)) {
+        NativeType::Int64(id) => id,
+        _ => panic!("Session id was not an integer."),
+    };
+    admin
+        .executor()
+        .try_fetch_all(&format!("select pg_terminate_backend({})", session_id))
+        .unwrap();
+
+    let rows = con.executor().fetch_all("select 1");
+    assert!(rows.len() == 1, "Query did not succeed after reconnecting.");
+
+    admin.disconnect();
+    con.disconnect();
+}
+
+#[test]
+fn test_concurrent_fetch_all() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Connection>();
+
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    // Every thread shares the same connection and runs its queries at the same time.
+    let query = (1..=100)
+        .map(|i| format!("select {}", i))
+        .collect::<Vec<String>>()
+        .join(" union all ");
+    std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                scope.spawn(|| {
+                    for _ in 0..10 {
+                        let rows = con.executor().fetch_all(&query);
+                        assert_eq!(rows.len(), 100);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    });
+    con.disconnect();
+}
+
+#[test]
+fn test_session_settings() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    settings.max_connections = 2;
+    settings.session.charset = Some("UTF8".to_string());
+    settings.session.time_zone = Some("America/Chicago".to_string());
+    settings.session.search_path = Some("pg_catalog, public".to_string());
+    settings.session.init_statements = vec!["SET application_name = 'datajoint'".to_string()];
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    // Every pooled connection is set up, so hold two connections at once.
+    let mut transactions = vec![con.begin(), con.begin()];
+    for transaction in transactions.iter_mut() {
+        let row = transaction.fetch_one(
+            "select current_setting('TimeZone'), current_setting('search_path'), current_setting('application_name')",
+        );
+        let time_zone: String = row.get(0);
+        let search_path: String = row.get(1);
+        let application_name: String = row.get(2);
+        assert_eq!(time_zone, "America/Chicago");
+        assert_eq!(search_path, "pg_catalog, public");
+        assert_eq!(application_name, "datajoint");
+    }
+    drop(transactions);
+    con.disconnect();
+
+    con.settings.session.sql_mode = Some("ANSI".to_string());
+    match con.connect() {
+        Ok(_) => panic!("sql_mode did not fail for Postgres."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::ConfigurationError),
+    }
+}
+
+#[test]
+fn test_socket_connection() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    // The hostname cannot be resolved, so only the socket can be used.
+    settings.hostname = "unreachable.invalid".to_string();
+    let socket_path = std::env::var("DJ_TEST_POSTGRES_SOCKET")
+        .unwrap_or_else(|_| "/var/run/postgresql".to_string());
+    settings.socket_path = Some(PathBuf::from(socket_path));
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    // Nothing listens on a TCP address over a socket connection.
+    let tcp_address_is_null: bool = con
+        .executor()
+        .fetch_one("select inet_server_addr() is null")
+        .get(0);
+    assert!(tcp_address_is_null, "Connection did not use the socket.");
+    con.disconnect();
+
+    con.settings.socket_path = Some(PathBuf::from("/nonexistent/datajoint.sock"));
+    assert!(con.connect().is_err(), "Connection did not fail.");
+}
+
+#[test]
+fn test_server_info() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.ping().unwrap();
+
+    let info = con.server_info();
+    assert_eq!(info.database_type, DatabaseType::Postgres);
+    assert!(info.major_version >= 10);
+    assert!(info
+        .version
+        .starts_with(&format!("{}.{}", info.major_version, info.minor_version)));
+    assert_eq!(info.current_user, Some("postgres".to_string()));
+    assert_eq!(info.current_database, Some("datajoint_core".to_string()));
+    con.disconnect();
+
+    match con.ping() {
+        Ok(_) => panic!("Ping did not fail after disconnecting."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::NotConnected),
+    }
+}
+
+#[test]
+fn test_statement_timeout() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    settings.statement_timeout = Some(Duration::from_millis(200));
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    let mut executor = con.executor();
+    assert_eq!(executor.timeout(), Some(Duration::from_millis(200)));
+
+    let start = Instant::now();
+    match executor.try_execute("select pg_sleep(5)") {
+        Ok(_) => panic!("Statement did not time out."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::QueryTimedOut),
+    }
+    let mut cursor = executor.cursor("select pg_sleep(5)").unwrap();
+    match cursor.try_next() {
+        Ok(_) => panic!("Cursor did not time out."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::QueryTimedOut),
+    }
+    drop(cursor);
+    assert!(start.elapsed() < Duration::from_secs(4));
+
+    // Timed out statements are stopped on the server.
+    executor.set_timeout(None);
+    std::thread::sleep(Duration::from_millis(500));
+    let rows = executor.fetch_all(
+        "select pid from pg_stat_activity where query = 'select pg_sleep(5)' and state = 'active'",
+    );
+    assert_eq!(rows.len(), 0);
+
+    executor.set_timeout(Some(Duration::from_secs(10)));
+    executor.fetch_one("select pg_sleep(0.1)");
+    con.disconnect();
+}
+
+#[test]
+fn test_cancel_query() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    let handle = con.cancel_handle();
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(Duration::from_millis(200));
+            handle.cancel();
+        });
+        match con.try_execute_query("select pg_sleep(5)") {
+            Ok(_) => panic!("Statement was not cancelled."),
+            Err(err) => assert_eq!(err.code(), ErrorCode::QueryCancelled),
+        }
+    });
+
+    // Executors may use their own handle.
+    let mut executor = con.executor();
+    let cursor_handle = CancelHandle::new();
+    executor.set_cancel_handle(cursor_handle.clone());
+    let mut cursor = executor.cursor("select pg_sleep(5)").unwrap();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(Duration::from_millis(200));
+            cursor_handle.cancel();
+        });
+        match cursor.try_next() {
+            Ok(_) => panic!("Cursor was not cancelled."),
+            Err(err) => assert_eq!(err.code(), ErrorCode::QueryCancelled),
+        }
+    });
+    drop(cursor);
+    assert!(start.elapsed() < Duration::from_secs(4));
+
+    // Earlier cancellations do not affect new statements.
+    let row = con.executor().fetch_one("select 1");
+    assert_eq!(row.get::<i32, usize>(0), 1);
+    con.disconnect();
+}
+
+#[derive(Default)]
+struct RecordingObserver {
+    events: Mutex<Vec<(String, Vec<String>, u64, ErrorCode)>>,
+}
+
+impl QueryObserver for RecordingObserver {
+    fn on_query(&self, event: &QueryEvent) {
+        self.events.lock().unwrap().push((
+            event.query.to_string(),
+            event.arguments.to_vec(),
+            event.rows,
+            event.error_code,
+        ));
+    }
+}
+
+#[test]
+fn test_query_observer() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+    let observer = Arc::new(RecordingObserver::default());
+    con.set_query_observer(Some(observer.clone()));
+
+    con.connect().unwrap();
+    let executor = con.executor();
+    let query = "select $1 union all select 2";
+    executor.execute_ph(query, vec![NativeType::Int32(1)]);
+    assert_eq!(executor.fetch_all("select 1 union all select 2").len(), 2);
+    assert_eq!(
+        executor
+            .cursor("select 1 union all select 2")
+            .unwrap()
+            .rest()
+            .len(),
+        2
+    );
+    match executor.try_fetch_one("select * from missing_table") {
+        Ok(_) => panic!("Query of a missing table did not fail."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::UnknownDatabaseError),
+    }
+
+    let events = observer.events.lock().unwrap();
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[0].0, query);
+    assert_eq!(events[0].1, vec!["<int32>".to_string()]);
+    assert_eq!(events[1].2, 2);
+    assert_eq!(events[2].2, 2);
+    assert_eq!(events[3].3, ErrorCode::UnknownDatabaseError);
+    drop(events);
+    con.disconnect();
+}
+
+#[test]
+fn test_credential_providers() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+
+    // The callback is called again every time the connection connects.
+    let calls = Arc::new(Mutex::new(0));
+    let counter = calls.clone();
+    settings.credentials = CredentialProvider::callback(move |_: &ConnectionSettings| {
+        *counter.lock().unwrap() += 1;
+        Ok("password".to_string())
+    });
+    assert!(!settings.uri().contains("password@"));
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+    con.ping().unwrap();
+    con.disconnect();
+    con.connect().unwrap();
+    con.disconnect();
+    assert_eq!(*calls.lock().unwrap(), 2);
+
+    let pgpass = std::env::temp_dir().join("datajoint_core_test_pgpass");
+    std::fs::write(&pgpass, "postgres_13:5432:*:postgres:password\n").unwrap();
+    con.settings.credentials = CredentialProvider::PgPass(Some(pgpass.clone()));
+    con.connect().unwrap();
+    con.ping().unwrap();
+    con.disconnect();
+
+    std::fs::write(&pgpass, "wrongpassword\n").unwrap();
+    con.settings.credentials = CredentialProvider::PasswordFile(pgpass.clone());
+    match con.connect() {
+        Ok(_) => panic!("Connection with the wrong password did not fail."),
+        Err(err) => assert!(!err.message().contains("wrongpassword")),
+    }
+    std::fs::remove_file(&pgpass).unwrap();
+}
+
+#[test]
+fn test_credentials_resolved_on_reconnect() {
+    let mut admin_settings = ConnectionSettings::new();
+    admin_settings.database_type = DatabaseType::Postgres;
+    admin_settings.username = "postgres".to_string();
+    admin_settings.port = 5432;
+    admin_settings.password = "password".to_string();
+    admin_settings.database_name = "datajoint_core".to_string();
+    admin_settings.tls.mode = TlsMode::Preferred;
+    admin_settings.hostname = "postgres_13".to_string();
+    let mut admin = Connection::new(admin_settings);
+    admin.connect().unwrap();
+    admin.execute_query("drop role if exists datajoint_core_rotating");
+    admin.execute_query("create role datajoint_core_rotating login password 'first'");
+
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "datajoint_core_rotating".to_string();
+    settings.port = 5432;
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    settings.max_connections = 1;
+    settings.reconnect = Some(ReconnectPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(50),
+        max_backoff: Duration::from_millis(500),
+    });
+
+    // The callback hands out the current password and counts how often it is called.
+    let password = Arc::new(Mutex::new("first".to_string()));
+    let calls = Arc::new(Mutex::new(0));
+    let (current, counter) = (password.clone(), calls.clone());
+    settings.credentials = CredentialProvider::callback(move |_: &ConnectionSettings| {
+        *counter.lock().unwrap() += 1;
+        Ok(current.lock().unwrap().clone())
+    });
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+    let row = con
+        .executor()
+        .fetch_one("select cast(pg_backend_pid() as bigint)");
+    let session_id = match row.decode(row.column(0)) {
+        NativeType::Int64(id) => id,
+        _ => panic!("Session id was not an integer."),
+    };
+    assert_eq!(*calls.lock().unwrap(), 1);
+
+    // Rotate the password and drop the only pooled connection, so the pool has to log in
+    // again with a password it has not resolved yet.
+    admin.execute_query("alter role datajoint_core_rotating password 'second'");
+    *password.lock().unwrap() = "second".to_string();
+    admin
+        .executor()
+        .try_fetch_all(&format!("select pg_terminate_backend({})", session_id))
+        .unwrap();
+
+    let rows = con.executor().fetch_all("select 1");
+    assert_eq!(
+        rows.len(),
+        1,
+        "Query did not succeed after the password changed."
+    );
+    assert_eq!(*calls.lock().unwrap(), 2);
+
+    con.disconnect();
+    admin.execute_query("drop role datajoint_core_rotating");
+    admin.disconnect();
+}
+
+#[test]
+fn test_prepared_statement() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    let executor = con.executor();
+    let statement = executor.prepare("select $1::int4 + $2::int4 as total, $1::int4 as first");
+    assert_eq!(statement.parameter_count(), Some(2));
+    let columns: Vec<&str> = statement
+        .columns()
+        .iter()
+        .map(|column| column.name.as_str())
+        .collect();
+    assert_eq!(columns, vec!["total", "first"]);
+
+    for value in 1..4 {
+        let rows = statement.fetch_all(vec![NativeType::Int32(value), NativeType::Int32(10)]);
+        assert_eq!(
+            rows[0].decode(rows[0].column("total")),
+            NativeType::Int32(value + 10)
+        );
+    }
+    match statement.try_fetch_all(vec![NativeType::Int32(1)]) {
+        Ok(_) => panic!("Statement ran with too few arguments."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::WrongArgumentCount),
+    }
+    con.disconnect();
+}
+
+#[test]
+fn test_execute_many() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.execute_query("drop table if exists tweet_batch");
+    con.execute_query("create table tweet_batch (text text not null, owner_id bigint, score int)");
+
+    // Three parameters per row exceed the 65535-parameter cap after 21845 rows.
+    let executor = con.executor();
+    let args: Vec<Vec<NativeType>> = (0..30000)
+        .map(|owner_id| {
+            vec![
+                NativeType::String("hello".to_string()),
+                NativeType::Int64(owner_id),
+                NativeType::Int32(1),
+            ]
+        })
+        .collect();
+    assert_eq!(
+        executor.execute_many(
+            "insert into tweet_batch (text, owner_id, score) values ($1, $2, $3)",
+            args
+        ),
+        30000
+    );
+    let row = executor.fetch_one("select count(*), sum(score) from tweet_batch");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(30000));
+    assert_eq!(row.decode(row.column(1)), NativeType::Int64(30000));
+
+    con.execute_query("drop table tweet_batch");
+    con.disconnect();
+}
+
+#[test]
+fn test_bulk_load() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.execute_query("drop table if exists spike");
+    con.execute_query("create table spike (unit int not null, label text, waveform bytea)");
+
+    let mut rows: Vec<Vec<NativeType>> = (0..50000)
+        .map(|unit| {
+            vec![
+                NativeType::Int32(unit),
+                NativeType::String(format!("unit \"{}\",\n", unit)),
+                NativeType::Bytes(vec![0, 1, 255]),
+            ]
+        })
+        .collect();
+    rows.push(vec![
+        NativeType::Int32(-1),
+        NativeType::String("".to_string()),
+        NativeType::None,
+    ]);
+    assert_eq!(
+        con.bulk_load("spike", &["unit", "label", "waveform"], rows),
+        50001
+    );
+
+    let executor = con.executor();
+    let row = executor.fetch_one("select count(*), sum(unit) from spike");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(50001));
+    assert_eq!(row.decode(row.column(1)), NativeType::Int64(1249974999));
+    let row = executor.fetch_one("select label, waveform from spike where unit = 7");
+    assert_eq!(
+        row.decode(row.column(0)),
+        NativeType::String("unit \"7\",\n".to_string())
+    );
+    assert_eq!(
+        row.decode(row.column(1)),
+        NativeType::Bytes(vec![0, 1, 255])
+    );
+    let row = executor.fetch_one("select label, waveform from spike where unit = -1");
+    assert_eq!(
+        row.decode(row.column(0)),
+        NativeType::String("".to_string())
+    );
+    assert_eq!(row.decode_optional(row.column(1)), None);
+
+    let text = "-2,\"quoted, label\",\n-3,,\\x00ff\n";
+    assert_eq!(
+        con.bulk_load_reader(
+            "spike",
+            &["unit", "label", "waveform"],
+            text.as_bytes(),
+            DelimitedFormat::Csv
+        ),
+        2
+    );
+    let row = executor.fetch_one("select label, waveform from spike where unit = -3");
+    assert_eq!(row.decode_optional(row.column(0)), None);
+    assert_eq!(row.decode(row.column(1)), NativeType::Bytes(vec![0, 255]));
+
+    // Malformed text aborts the copy, loading none of its rows.
+    match con.try_bulk_load_reader(
+        "spike",
+        &["unit", "label"],
+        "-4,a\nnot a number,b\n".as_bytes(),
+        DelimitedFormat::Csv,
+    ) {
+        Ok(_) => panic!("Malformed text was loaded."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::UnknownDatabaseError),
+    }
+    let row = executor.fetch_one("select count(*) from spike where unit = -4");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(0));
+
+    con.execute_query("drop table spike");
+    con.disconnect();
+}
+
+#[test]
+fn test_bulk_load_timeout() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.execute_query("drop table if exists slow_spike");
+    con.execute_query("create table slow_spike (unit int not null)");
+    con.execute_query(
+        "create or replace function slow_spike_insert() returns trigger as $$ \
+         begin perform pg_sleep(5); return new; end $$ language plpgsql",
+    );
+    con.execute_query(
+        "create trigger slow_spike_insert before insert on slow_spike \
+         for each row execute function slow_spike_insert()",
+    );
+
+    // The copy is stopped on the server once the timeout of the executor passes.
+    let mut executor = con.executor();
+    executor.set_timeout(Some(Duration::from_millis(200)));
+    let start = Instant::now();
+    match executor.try_bulk_load("slow_spike", &["unit"], vec![vec![NativeType::Int32(1)]]) {
+        Ok(_) => panic!("Bulk load was not interrupted."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::QueryTimedOut),
+    }
+    assert!(start.elapsed() < Duration::from_secs(4));
+
+    let row = con.executor().fetch_one("select count(*) from slow_spike");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(0));
+
+    con.execute_query("drop table slow_spike");
+    con.execute_query("drop function slow_spike_insert");
+    con.disconnect();
+}
+
+#[test]
+fn test_execute_script() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    let executor = con.executor();
+    let script = r#"
+        drop table if exists script_tweet;
+        create table script_tweet (id serial primary key, text text not null);
+        create or replace function script_count() returns bigint as $$
+            select count(*) from script_tweet; -- Semicolons in the body.
+        $$ language sql;
+        insert into script_tweet (text) values (E'it\'s; quoted'), ('plain');
+        set search_path to public;
+    "#;
+    let result = executor.execute_script(script, true);
+    assert!(result.is_success(), "{:?}", result.error());
+    assert_eq!(result.statements().len(), 5);
+    assert_eq!(result.rows_affected()[3], 2);
+    let row = executor.fetch_one("select script_count()");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(2));
+
+    let result = executor.execute_script(
+        "insert into script_tweet (text) values ('rolled back'); select 1 / 0;",
+        true,
+    );
+    assert_eq!(result.failed_statement(), Some(1));
+    assert_eq!(result.rows_affected(), &[1]);
+    let row = executor.fetch_one("select script_count()");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(2));
+
+    executor.execute_script(
+        "drop function script_count; drop table script_tweet;",
+        false,
+    );
+    con.disconnect();
+}
+
+#[test]
+fn test_describe() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.execute_query("drop table if exists described");
+    con.execute_query("create table described (id integer not null, note text)");
+    let executor = con.executor();
+
+    let columns = executor.describe("select id, note from described where id = $1");
+    let names: Vec<&str> = columns.iter().map(|column| column.name.as_str()).collect();
+    assert_eq!(names, vec!["id", "note"]);
+    assert_eq!(columns[0].type_name, DataJointType::Int);
+    assert_eq!(columns[0].nullable, Some(false));
+    assert_eq!(columns[1].nullable, Some(true));
+    assert!(executor.describe("delete from described").is_empty());
+
+    let mut cursor = executor.cursor("select note from described").unwrap();
+    assert_eq!(cursor.columns()[0].name, "note");
+    assert!(cursor.next().is_none());
+
+    con.execute_query("drop table described");
+    con.disconnect();
+}
+
+#[test]
+fn test_execute_result() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Postgres;
+    settings.username = "postgres".to_string();
+    settings.port = 5432;
+    settings.password = "password".to_string();
+    settings.database_name = "datajoint_core".to_string();
+    settings.tls.mode = TlsMode::Preferred;
+    settings.hostname = "postgres_13".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    con.execute_query("drop table if exists inserted");
+    con.execute_query("create table inserted (id serial primary key, note text)");
+    let executor = con.executor();
+
+    // Postgres reports no id without a returning clause.
+    let result = executor.execute("insert into inserted (note) values ('a')");
+    assert_eq!(result.rows_affected, 1);
+    assert_eq!(result.last_insert_id, None);
+    assert_eq!(result.warnings, None);
+
+    let result = executor.execute_ph(
+        "insert into inserted (note) values ($1), ($2) returning id",
+        vec![
+            NativeType::String("b".to_string()),
+            NativeType::String("c".to_string()),
+        ],
+    );
+    assert_eq!(result.rows_affected, 2);
+    assert_eq!(result.last_insert_id, Some(3));
+
+    // Ids that are not integers are not reported.
+    let result = executor.execute("insert into inserted (note) values ('d') returning note");
+    assert_eq!(result.rows_affected, 1);
+    assert_eq!(result.last_insert_id, None);
+
+    // Inserts after a with clause report their id too.
+    let result = executor.execute_ph(
+        "with notes (note) as (values ($1), ($2)) \
+         insert into inserted (note) select note from notes returning id",
+        vec![
+            NativeType::String("e".to_string()),
+            NativeType::String("f".to_string()),
+        ],
+    );
+    assert_eq!(result.rows_affected, 2);
+    assert_eq!(result.last_insert_id, Some(6));
+
+    con.execute_query("drop table inserted");
+    con.disconnect();
+}
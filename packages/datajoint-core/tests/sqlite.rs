@@ -0,0 +1,951 @@
+use datajoint_core::{
+    common::DatabaseType,
+    connection::{
+        CacheMode, CacheSettings, Connection, ConnectionSettings, Cursor, DelimitedFormat,
+        Executor, QueryEvent, QueryObserver, Transaction,
+    },
+    error::ErrorCode,
+    results::TableRow,
+    types::{DataJointType, NativeType},
+};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn create_tweet_table(con: &Connection) {
+    con.execute_query(
+        "create table tweet (id integer primary key autoincrement, text text not null, owner_id bigint not null)",
+    );
+}
+
+#[test]
+fn test_connection_to_db() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = ":memory:".to_string();
+    let mut conn = Connection::new(settings);
+
+    let result = conn.connect();
+    assert!(result.is_ok(), "Connection did not connect.");
+
+    conn.disconnect();
+
+    conn.settings.database_name = "/nonexistent/directory/datajoint_core.db".to_string();
+
+    let result = conn.connect();
+    assert!(result.is_err(), "Connection did not fail.");
+}
+
+#[test]
+fn test_insert_and_retrieve_one_row() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = ":memory:".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    create_tweet_table(&con);
+
+    con.execute_query("insert into tweet (text, owner_id) values ('hello world', 1234);");
+    let cursor = &mut con.fetch_query("select text, owner_id from tweet where owner_id=1234");
+
+    let rows: Vec<TableRow> = cursor.rest();
+    let cols = rows[0].columns();
+    assert_eq!(cols[0].type_name(), DataJointType::VarCharN);
+    assert_eq!(cols[1].type_name(), DataJointType::BigInt);
+
+    let text = rows[0].decode(cols[0]);
+    let owner_id = rows[0].decode(cols[1]);
+
+    assert!(
+        text == NativeType::String("hello world".to_string()),
+        "text did not match \"hello world\"."
+    );
+    assert!(
+        owner_id == NativeType::Int64(1234),
+        "owner_id did not equal 1234."
+    );
+    con.disconnect();
+}
+
+#[test]
+fn test_expression_columns() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    // Expressions have no declared type, so values are decoded by their storage class.
+    let row = con
+        .executor()
+        .fetch_one("select 1, 'text', 1.5, x'0102', null");
+    let cols = row.columns();
+    assert_eq!(row.decode(cols[0]), NativeType::Int64(1));
+    assert_eq!(row.decode(cols[1]), NativeType::String("text".to_string()));
+    assert_eq!(row.decode(cols[2]), NativeType::Float64(1.5));
+    assert_eq!(row.decode(cols[3]), NativeType::Bytes(vec![1, 2]));
+    assert_eq!(row.decode_optional(cols[4]), None);
+    con.disconnect();
+}
+
+#[test]
+fn test_placeholders() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = ":memory:".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    create_tweet_table(&con);
+
+    let placeholders: Vec<NativeType> = vec![
+        NativeType::String("hello world".to_string()),
+        NativeType::UInt32(1234),
+    ];
+    con.execute_query_ph(
+        "insert into tweet (text, owner_id) values (?, ?);",
+        placeholders,
+    );
+
+    let placeholders: Vec<NativeType> = vec![NativeType::Int32(1234)];
+    let mut cursor = con.fetch_query_ph("select text from tweet where owner_id = ?;", placeholders);
+    let rows: Vec<TableRow> = cursor.rest();
+    assert_eq!(rows.len(), 1);
+    drop(cursor);
+
+    let placeholders: Vec<NativeType> = vec![NativeType::UInt64(1234)];
+    match con.try_execute_query_ph("delete from tweet where owner_id = ?", placeholders) {
+        Ok(_) => panic!("Unsigned 64-bit placeholder did not fail."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::UnsupportedNativeType),
+    }
+
+    let placeholders: Vec<NativeType> = vec![NativeType::Int64(1234)];
+    let rows_affected = con.execute_query_ph("delete from tweet where owner_id = ?", placeholders);
+    assert!(rows_affected == 1, "Rows affected did not equal 1.");
+
+    con.disconnect()
+}
+
+#[test]
+fn test_executor() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = ":memory:".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    create_tweet_table(&con);
+    let executor = con.executor();
+
+    // Execute a non-returning query.
+    let rows_affected: u64 = executor
+        .execute("insert into tweet (text, owner_id) values ('hello world', 1234);")
+        .rows_affected;
+    assert!(rows_affected == 1, "Rows affected did not equal 1.");
+
+    // Fetch a single row, ignoring the rest.
+    let single_row: TableRow = executor.fetch_one("select * from tweet;");
+    assert_eq!(single_row.column_count(), 3);
+
+    // Fetch all rows at once without a cursor.
+    executor.execute("insert into tweet (text, owner_id) values ('hello world1', 5678);");
+    let all_rows: Vec<TableRow> = executor.fetch_all("select * from tweet;");
+    assert_eq!(all_rows.len(), 2);
+
+    // Create a cursor for the given query.
+    let cursor = &mut executor.cursor("select * from tweet;").unwrap();
+    let rows: Vec<TableRow> = cursor.rest();
+    assert_eq!(rows.len(), 2);
+}
+
+#[test]
+fn test_transaction() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = ":memory:".to_string();
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    create_tweet_table(&con);
+
+    let mut transaction = con.begin();
+    transaction.execute("insert into tweet (text, owner_id) values ('hello world', 4321);");
+    transaction.savepoint().unwrap();
+    transaction.execute("insert into tweet (text, owner_id) values ('hello world1', 4321);");
+    transaction.rollback_to_savepoint().unwrap();
+    let text: String = transaction
+        .fetch_one_ph(
+            "select text from tweet where owner_id = ?",
+            vec![NativeType::Int32(4321)],
+        )
+        .get(0);
+    assert_eq!(text, "hello world");
+    let rows = transaction.fetch_all_ph(
+        "select text from tweet where owner_id = ?",
+        vec![NativeType::Int32(1234)],
+    );
+    assert!(rows.is_empty());
+    transaction.commit().unwrap();
+
+    let count: i64 = con
+        .executor()
+        .fetch_one("select count(*) from tweet where owner_id = 4321")
+        .get(0);
+    assert_eq!(count, 1);
+
+    let mut transaction = con.begin();
+    transaction.execute("delete from tweet");
+    drop(transaction);
+
+    let count: i64 = con
+        .executor()
+        .fetch_one("select count(*) from tweet")
+        .get(0);
+    assert_eq!(count, 1);
+    con.disconnect();
+}
+
+#[test]
+fn test_file_database() {
+    let path = std::env::temp_dir().join("datajoint_core_test_file_database.db");
+    let _ = std::fs::remove_file(&path);
+
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = path.to_str().unwrap().to_string();
+    settings.options = vec![("mode".to_string(), "rwc".to_string())];
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    create_tweet_table(&con);
+    con.execute_query("insert into tweet (text, owner_id) values ('hello world', 1234);");
+    con.disconnect();
+
+    // The data outlives the connection.
+    con.connect().unwrap();
+    let rows = con.executor().fetch_all("select text from tweet");
+    assert_eq!(rows.len(), 1);
+    con.disconnect();
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_concurrent_fetch_all() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Connection>();
+    assert_send_sync::<Executor>();
+    fn assert_send<T: Send>() {}
+    assert_send::<Cursor>();
+    assert_send::<Transaction>();
+
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = ":memory:".to_string();
+    settings.max_connections = 4;
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    // Every thread shares the same connection and runs its queries at the same time.
+    let query = (1..=100)
+        .map(|i| format!("select {}", i))
+        .collect::<Vec<String>>()
+        .join(" union all ");
+    std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                scope.spawn(|| {
+                    for _ in 0..10 {
+                        let rows = con.executor().fetch_all(&query);
+                        assert_eq!(rows.len(), 100);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    });
+    con.disconnect();
+}
+
+#[test]
+fn test_session_settings() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.database_name = ":memory:".to_string();
+    settings.session.init_statements = vec![
+        "PRAGMA foreign_keys = ON".to_string(),
+        "PRAGMA recursive_triggers = ON".to_string(),
+    ];
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+
+    let row = con.executor().fetch_one("PRAGMA foreign_keys");
+    let foreign_keys: i64 = row.get(0);
+    assert_eq!(foreign_keys, 1);
+    let row = con.executor().fetch_one("PRAGMA recursive_triggers");
+    let recursive_triggers: i64 = row.get(0);
+    assert_eq!(recursive_triggers, 1);
+    con.disconnect();
+
+    con.settings.session.time_zone = Some("UTC".to_string());
+    match con.connect() {
+        Ok(_) => panic!("Time zone did not fail for SQLite."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::ConfigurationError),
+    }
+}
+
+#[test]
+fn test_socket_path_not_supported() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.socket_path = Some(std::path::PathBuf::from("/var/run/mysqld/mysqld.sock"));
+    let mut con = Connection::new(settings);
+
+    match con.connect() {
+        Ok(_) => panic!("Socket path did not fail for SQLite."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::ConfigurationError),
+    }
+}
+
+#[test]
+fn test_server_info() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+
+    match con.ping() {
+        Ok(_) => panic!("Ping did not fail before connecting."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::NotConnected),
+    }
+
+    con.connect().unwrap();
+    con.ping().unwrap();
+
+    let info = con.server_info();
+    assert_eq!(info.database_type, DatabaseType::Sqlite);
+    assert_eq!(info.major_version, 3);
+    assert!(info.version.starts_with("3."));
+    assert_eq!(info.current_user, None);
+    assert_eq!(info.current_database, Some("main".to_string()));
+    con.disconnect();
+
+    match con.ping() {
+        Ok(_) => panic!("Ping did not fail after disconnecting."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::NotConnected),
+    }
+}
+
+#[test]
+fn test_statement_timeout() {
+    // Counts to a large number, taking several seconds.
+    let slow_query = "with recursive c(x) as (select 1 union all select x + 1 from c limit 50000000) select count(*) from c";
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.statement_timeout = Some(Duration::from_millis(100));
+    let mut con = Connection::new(settings);
+
+    con.connect().unwrap();
+    let start = Instant::now();
+    match con.executor().try_fetch_one(slow_query) {
+        Ok(_) => panic!("Statement did not time out."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::QueryTimedOut),
+    }
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    let handle = con.cancel_handle();
+    let mut executor = con.executor();
+    executor.set_timeout(None);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(Duration::from_millis(100));
+            handle.cancel();
+        });
+        match executor.try_fetch_one(slow_query) {
+            Ok(_) => panic!("Statement was not cancelled."),
+            Err(err) => assert_eq!(err.code(), ErrorCode::QueryCancelled),
+        }
+    });
+}
+
+#[derive(Default)]
+struct RecordingObserver {
+    events: Mutex<Vec<(String, Vec<String>, u64, ErrorCode)>>,
+}
+
+impl QueryObserver for RecordingObserver {
+    fn on_query(&self, event: &QueryEvent) {
+        self.events.lock().unwrap().push((
+            event.query.to_string(),
+            event.arguments.to_vec(),
+            event.rows,
+            event.error_code,
+        ));
+    }
+}
+
+#[test]
+fn test_query_observer() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+    let observer = Arc::new(RecordingObserver::default());
+    con.set_query_observer(Some(observer.clone()));
+
+    con.connect().unwrap();
+    create_tweet_table(&con);
+    observer.events.lock().unwrap().clear();
+
+    let insert = "insert into tweet (text, owner_id) values (?, ?)";
+    con.execute_query_ph(
+        insert,
+        vec![
+            NativeType::String("secret".to_string()),
+            NativeType::Int64(1),
+        ],
+    );
+    con.execute_query("insert into tweet (text, owner_id) values ('hello', 2)");
+    assert_eq!(con.executor().fetch_all("select * from tweet").len(), 2);
+    assert_eq!(con.fetch_query("select * from tweet").rest().len(), 2);
+    match con.executor().try_fetch_one("select * from missing_table") {
+        Ok(_) => panic!("Query of a missing table did not fail."),
+        Err(_) => {}
+    }
+    let mut transaction = con.begin();
+    transaction.execute("delete from tweet");
+    transaction.commit().unwrap();
+
+    let events = observer.events.lock().unwrap();
+    assert_eq!(events.len(), 6);
+    assert_eq!(
+        events[0],
+        (
+            insert.to_string(),
+            vec!["<string>".to_string(), "<int64>".to_string()],
+            1,
+            ErrorCode::Success
+        )
+    );
+    assert_eq!(events[1].2, 1);
+    assert!(events[1].1.is_empty());
+    assert_eq!((events[2].2, events[2].3), (2, ErrorCode::Success));
+    assert_eq!(
+        (events[3].0.as_str(), events[3].2, events[3].3),
+        ("select * from tweet", 2, ErrorCode::Success)
+    );
+    assert_ne!(events[4].3, ErrorCode::Success);
+    assert_eq!(
+        (events[5].0.as_str(), events[5].2),
+        ("delete from tweet", 2)
+    );
+    assert!(!format!("{:?}", *events).contains("secret"));
+    drop(events);
+
+    con.set_query_observer(None);
+    con.execute_query("delete from tweet");
+    assert_eq!(observer.events.lock().unwrap().len(), 6);
+}
+
+#[test]
+fn test_result_cache() {
+    let path = std::env::temp_dir().join("datajoint_core_test_result_cache");
+    let _ = std::fs::remove_dir_all(&path);
+
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.cache = CacheSettings::new(CacheMode::ReadWrite, &path);
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+    create_tweet_table(&con);
+    con.execute_query("insert into tweet (text, owner_id) values ('hello', 1), ('world', 2)");
+
+    let query =
+        "select id, text, owner_id, null as missing from tweet where owner_id >= ? order by id";
+    let live = con
+        .executor()
+        .fetch_all_ph(query, vec![NativeType::Int64(1)]);
+    assert_eq!(live.len(), 2);
+    assert!(matches!(live[0], TableRow::Sqlite(_)));
+
+    // Cached results are returned even after the table changes.
+    con.execute_query("delete from tweet");
+    let cached = con
+        .executor()
+        .fetch_all_ph(query, vec![NativeType::Int64(1)]);
+    assert_eq!(cached.len(), 2);
+    for (live_row, cached_row) in live.iter().zip(cached.iter()) {
+        assert!(matches!(cached_row, TableRow::Owned(_)));
+        assert_eq!(cached_row.column_count(), live_row.column_count());
+        let columns = live_row.columns().into_iter().zip(cached_row.columns());
+        for (live_column, cached_column) in columns {
+            assert_eq!(cached_column.to_owned(), live_column.to_owned());
+            assert_eq!(
+                cached_row.decode_optional(cached_column),
+                live_row.decode_optional(live_column)
+            );
+        }
+    }
+    let text = cached[1].column("text");
+    assert_eq!(text.ordinal(), 1);
+    assert_eq!(text.type_name(), DataJointType::VarCharN);
+    assert_eq!(
+        cached[1].decode(text),
+        NativeType::String("world".to_string())
+    );
+    assert_eq!(cached[1].decode_optional(cached[1].column(3)), None);
+    assert_eq!(
+        cached[1].try_column("unknown").err().unwrap().code(),
+        ErrorCode::ColumnNotFound
+    );
+    assert_eq!(
+        cached[1].try_get::<i64, usize>(0).err().unwrap().code(),
+        ErrorCode::ColumnDecodeError
+    );
+
+    // Other argument values and fetching one row are cached separately.
+    assert!(con
+        .executor()
+        .fetch_all_ph(query, vec![NativeType::Int64(2)])
+        .is_empty());
+    assert!(con
+        .executor()
+        .try_fetch_one_ph(query, vec![NativeType::Int64(1)])
+        .is_err());
+
+    // Read-only caches return cached results without caching new ones.
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    settings.cache = CacheSettings::new(CacheMode::ReadOnly, &path);
+    let mut read_only = Connection::new(settings);
+    read_only.connect().unwrap();
+    let files = std::fs::read_dir(&path).unwrap().count();
+    assert_eq!(
+        read_only
+            .executor()
+            .fetch_all_ph(query, vec![NativeType::Int64(1)])
+            .len(),
+        2
+    );
+    let row = read_only.executor().fetch_one("select 7");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(7));
+    assert_eq!(std::fs::read_dir(&path).unwrap().count(), files);
+
+    // Without a cache, queries always run on the database.
+    con.disconnect();
+    con.settings.cache.mode = CacheMode::Off;
+    con.connect().unwrap();
+    create_tweet_table(&con);
+    assert!(con
+        .executor()
+        .fetch_all_ph(query, vec![NativeType::Int64(1)])
+        .is_empty());
+
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn test_prepared_statement() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+    create_tweet_table(&con);
+
+    let executor = con.executor();
+    let insert = executor.prepare("insert into tweet (text, owner_id) values (?, ?)");
+    assert_eq!(insert.parameter_count(), Some(2));
+    assert!(insert.columns().is_empty());
+    for (text, owner_id) in [("hello", 1), ("world", 1), ("other", 2)] {
+        assert_eq!(
+            insert
+                .execute(vec![
+                    NativeType::String(text.to_string()),
+                    NativeType::Int64(owner_id),
+                ])
+                .rows_affected,
+            1
+        );
+    }
+    match insert.try_execute(vec![NativeType::String("missing".to_string())]) {
+        Ok(_) => panic!("Statement ran with too few arguments."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::WrongArgumentCount),
+    }
+
+    let select = executor.prepare("select text, owner_id from tweet where owner_id = ?");
+    assert_eq!(select.parameter_count(), Some(1));
+    let columns: Vec<(&str, DataJointType)> = select
+        .columns()
+        .iter()
+        .map(|column| (column.name.as_str(), column.type_name))
+        .collect();
+    assert_eq!(
+        columns,
+        vec![
+            ("text", DataJointType::VarCharN),
+            ("owner_id", DataJointType::BigInt)
+        ]
+    );
+
+    assert_eq!(select.fetch_all(vec![NativeType::Int64(1)]).len(), 2);
+    let row = select.fetch_one(vec![NativeType::Int64(2)]);
+    assert_eq!(
+        row.decode(row.column(0)),
+        NativeType::String("other".to_string())
+    );
+    let mut cursor = select.cursor(vec![NativeType::Int64(1)]).unwrap();
+    assert_eq!(cursor.rest().len(), 2);
+
+    match executor.try_prepare("select * from missing_table") {
+        Ok(_) => panic!("Statement for a missing table was prepared."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::UnknownDatabaseError),
+    }
+}
+
+#[test]
+fn test_execute_many() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+    create_tweet_table(&con);
+
+    let executor = con.executor();
+    let args: Vec<Vec<NativeType>> = (0..40000)
+        .map(|owner_id| {
+            vec![
+                NativeType::String(format!("tweet {}", owner_id)),
+                NativeType::Int64(owner_id),
+            ]
+        })
+        .collect();
+    assert_eq!(
+        executor.execute_many("insert into tweet (text, owner_id) values (?, ?)", args),
+        40000
+    );
+    let row = executor.fetch_one("select count(*), sum(owner_id) from tweet");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(40000));
+    assert_eq!(row.decode(row.column(1)), NativeType::Int64(799980000));
+
+    // Statements that cannot be batched run once for every set of arguments.
+    assert_eq!(
+        executor.execute_many(
+            "update tweet set text = ? where owner_id = ?",
+            vec![
+                vec![
+                    NativeType::String("first".to_string()),
+                    NativeType::Int64(0)
+                ],
+                vec![
+                    NativeType::String("second".to_string()),
+                    NativeType::Int64(1)
+                ],
+            ]
+        ),
+        2
+    );
+    assert_eq!(executor.execute_many("delete from tweet", vec![]), 0);
+    match executor.try_execute_many(
+        "insert into tweet (text, owner_id) values (?, ?)",
+        vec![
+            vec![
+                NativeType::String("hello".to_string()),
+                NativeType::Int64(1),
+            ],
+            vec![NativeType::String("world".to_string())],
+        ],
+    ) {
+        Ok(_) => panic!("Argument sets of different lengths were executed."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::WrongArgumentCount),
+    }
+}
+
+#[test]
+fn test_bulk_load() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+    create_tweet_table(&con);
+
+    let rows: Vec<Vec<NativeType>> = (0..20000)
+        .map(|owner_id| {
+            vec![
+                NativeType::String(format!("tweet {}", owner_id)),
+                NativeType::Int64(owner_id),
+            ]
+        })
+        .collect();
+    assert_eq!(con.bulk_load("tweet", &["text", "owner_id"], rows), 20000);
+
+    let text = "tab\\there\t20000\nplain\t20001\n";
+    assert_eq!(
+        con.bulk_load_reader(
+            "tweet",
+            &["text", "owner_id"],
+            text.as_bytes(),
+            DelimitedFormat::Tsv
+        ),
+        2
+    );
+    let executor = con.executor();
+    let row = executor.fetch_one("select count(*), sum(owner_id) from tweet");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(20002));
+    assert_eq!(row.decode(row.column(1)), NativeType::Int64(200030001));
+    let row = executor.fetch_one("select text from tweet where owner_id = 20000");
+    assert_eq!(
+        row.decode(row.column(0)),
+        NativeType::String("tab\there".to_string())
+    );
+
+    // Empty unquoted CSV fields are loaded as nulls.
+    con.execute_query("create table note (owner_id bigint not null, body text)");
+    assert_eq!(
+        con.bulk_load_reader(
+            "note",
+            &["owner_id", "body"],
+            "1,\n2,\"\"\n".as_bytes(),
+            DelimitedFormat::Csv
+        ),
+        2
+    );
+    let row = executor.fetch_one("select count(*), count(body) from note");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(2));
+    assert_eq!(row.decode(row.column(1)), NativeType::Int64(1));
+
+    match con.try_bulk_load(
+        "tweet",
+        &["text", "owner_id"],
+        vec![vec![NativeType::String("hello".to_string())]],
+    ) {
+        Ok(_) => panic!("Row without a value for every column was loaded."),
+        Err(err) => assert_eq!(err.code(), ErrorCode::WrongArgumentCount),
+    }
+}
+
+#[test]
+fn test_execute_script() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+
+    let executor = con.executor();
+    let script = "
+        -- Bootstrap the schema.
+        create table tweet (id integer primary key, text text not null);
+        insert into tweet (text) values ('semi; colon'), ('it''s');
+        /* A comment; with a semicolon. */
+        update tweet set text = text || ';' where id = 1;
+    ";
+    let result = executor.execute_script(script, false);
+    assert!(result.is_success());
+    assert_eq!(result.rows_affected(), &[0, 2, 1]);
+    let row = executor.fetch_one("select text from tweet where id = 1");
+    assert_eq!(
+        row.decode(row.column(0)),
+        NativeType::String("semi; colon;".to_string())
+    );
+
+    // A failing statement rolls back the statements before it in a transaction.
+    let result = executor.execute_script(
+        "insert into tweet (text) values ('kept?'); insert into missing values (1); delete from tweet;",
+        true,
+    );
+    assert_eq!(result.failed_statement(), Some(1));
+    assert_eq!(result.rows_affected(), &[1]);
+    let row = executor.fetch_one("select count(*) from tweet");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(2));
+
+    // Without a transaction, the statements before the failing one are kept.
+    let result = executor.execute_script(
+        "insert into tweet (text) values ('kept'); insert into missing values (1); delete from tweet;",
+        false,
+    );
+    assert_eq!(result.failed_statement(), Some(1));
+    let row = executor.fetch_one("select count(*) from tweet");
+    assert_eq!(row.decode(row.column(0)), NativeType::Int64(3));
+}
+
+#[test]
+fn test_cursor_batches() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+    create_tweet_table(&con);
+
+    let executor = con.executor();
+    executor.execute_many(
+        "insert into tweet (text, owner_id) values (?, ?)",
+        (0..2500)
+            .map(|owner_id| {
+                vec![
+                    NativeType::String("hi".to_string()),
+                    NativeType::Int64(owner_id),
+                ]
+            })
+            .collect(),
+    );
+
+    let mut cursor = executor
+        .cursor("select owner_id from tweet order by owner_id")
+        .unwrap();
+    let mut sizes = Vec::new();
+    loop {
+        let batch = cursor.try_next_batch(1000).unwrap();
+        if batch.is_empty() {
+            break;
+        }
+        sizes.push(batch.len());
+    }
+    assert_eq!(sizes, vec![1000, 1000, 500]);
+
+    let cursor = executor
+        .cursor("select owner_id from tweet order by owner_id")
+        .unwrap();
+    let total: i64 = cursor.map(|row| row.unwrap().get::<i64, _>(0)).sum();
+    assert_eq!(total, 3123750);
+}
+
+#[test]
+fn test_cursor_outlives_connection() {
+    let cursor: Cursor<'static> = {
+        let mut settings = ConnectionSettings::new();
+        settings.database_type = DatabaseType::Sqlite;
+        let mut con = Connection::new(settings);
+        con.connect().unwrap();
+        create_tweet_table(&con);
+
+        let executor = con.executor();
+        executor.execute_many(
+            "insert into tweet (text, owner_id) values (?, ?)",
+            (0..10)
+                .map(|owner_id| {
+                    vec![
+                        NativeType::String("hi".to_string()),
+                        NativeType::Int64(owner_id),
+                    ]
+                })
+                .collect(),
+        );
+        let query = String::from("select owner_id from tweet where owner_id >= ?");
+        executor
+            .cursor_ph(&query, vec![NativeType::Int64(4)])
+            .unwrap()
+    };
+
+    // The cursor owns its statement, arguments and connection, so it can be read from
+    // another thread after the connection it was created from is dropped.
+    let owner_ids = std::thread::spawn(move || {
+        cursor
+            .map(|row| row.unwrap().get::<i64, _>(0))
+            .collect::<Vec<i64>>()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(owner_ids, vec![4, 5, 6, 7, 8, 9]);
+}
+
+#[test]
+fn test_disconnect_with_live_cursor() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+    create_tweet_table(&con);
+
+    let executor = con.executor();
+    executor.execute_many(
+        "insert into tweet (text, owner_id) values (?, ?)",
+        (0..10)
+            .map(|owner_id| {
+                vec![
+                    NativeType::String("hi".to_string()),
+                    NativeType::Int64(owner_id),
+                ]
+            })
+            .collect(),
+    );
+    let mut cursor = executor.cursor("select owner_id from tweet").unwrap();
+    assert!(cursor.next().unwrap().is_ok());
+
+    // Disconnecting does not wait for the cursor, which releases its connection and fails
+    // once it is read.
+    con.disconnect();
+    assert!(!con.is_connected());
+    let error = cursor.next().unwrap().err().unwrap();
+    assert_eq!(error.code(), ErrorCode::NotConnected);
+    assert!(cursor.next().is_none());
+}
+
+#[test]
+fn test_describe() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+    create_tweet_table(&con);
+
+    let executor = con.executor();
+    let columns = executor.describe("select text, owner_id from tweet");
+    let names: Vec<&str> = columns.iter().map(|column| column.name.as_str()).collect();
+    assert_eq!(names, vec!["text", "owner_id"]);
+    assert_eq!(columns[0].type_name, DataJointType::VarCharN);
+    assert_eq!(columns[1].type_name, DataJointType::BigInt);
+    assert_eq!(columns[1].ordinal, 1);
+    assert_eq!(columns[0].nullable, Some(false));
+    assert!(executor.describe("delete from tweet").is_empty());
+
+    // The columns of a cursor are known even if it returns no rows.
+    let mut cursor = executor.cursor("select text, owner_id from tweet").unwrap();
+    assert_eq!(cursor.columns().len(), 2);
+    assert!(cursor.next().is_none());
+
+    // Reading the columns does not consume the next row.
+    con.execute_query("insert into tweet (text, owner_id) values ('hello', 7)");
+    let mut cursor = executor.cursor("select owner_id from tweet").unwrap();
+    assert_eq!(cursor.columns()[0].name, "owner_id");
+    let row = cursor.next().unwrap().unwrap();
+    assert_eq!(row.get::<i64, _>(0), 7);
+    assert!(cursor.next().is_none());
+}
+
+#[test]
+fn test_execute_result() {
+    let mut settings = ConnectionSettings::new();
+    settings.database_type = DatabaseType::Sqlite;
+    let mut con = Connection::new(settings);
+    con.connect().unwrap();
+    create_tweet_table(&con);
+
+    let executor = con.executor();
+    let result = executor.execute("insert into tweet (text, owner_id) values ('hello', 1)");
+    assert_eq!(result.rows_affected, 1);
+    assert_eq!(result.last_insert_id, Some(1));
+    assert_eq!(result.warnings, None);
+
+    // Multi-row inserts report the rowid of the last row.
+    let result = executor.execute_ph(
+        "insert into tweet (text, owner_id) values (?, ?), (?, ?)",
+        vec![
+            NativeType::String("a".to_string()),
+            NativeType::Int64(2),
+            NativeType::String("b".to_string()),
+            NativeType::Int64(3),
+        ],
+    );
+    assert_eq!(result.rows_affected, 2);
+    assert_eq!(result.last_insert_id, Some(3));
+
+    let result = executor.execute("update tweet set owner_id = 4");
+    assert_eq!(result.rows_affected, 3);
+    assert_eq!(result.last_insert_id, None);
+
+    let mut transaction = con.begin();
+    let result = transaction.execute("insert into tweet (text, owner_id) values ('c', 5)");
+    assert_eq!(result.last_insert_id, Some(4));
+    transaction.commit().unwrap();
+}